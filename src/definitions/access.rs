@@ -0,0 +1,152 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to control access to the web interface.
+
+// Import crate definitions
+use crate::definitions::*;
+
+/// An enum to describe the access granted to a client of the web interface,
+/// ordered from the least to the most access
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// A variant which may only read the status and playback events
+    Observer,
+
+    /// A variant which may send any request
+    Operator,
+}
+
+/// A struct to hold the keys which grant access to the web interface
+///
+/// # Note
+///
+/// If no keys are specified, every client is granted operator access.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeys {
+    #[serde(default)]
+    pub operator: Vec<String>, // the keys which grant operator access
+    #[serde(default)]
+    pub observer: Vec<String>, // the keys which grant observer access
+}
+
+// Implement key functionality for the API keys
+impl ApiKeys {
+    /// A method to check if no keys are specified (and access is unrestricted)
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.operator.is_empty() && self.observer.is_empty()
+    }
+
+    /// A method to add the keys from another list of keys
+    ///
+    pub fn extend(&mut self, other: ApiKeys) {
+        self.operator.extend(other.operator);
+        self.observer.extend(other.observer);
+    }
+
+    /// A method to find the role granted by the provided key, if any. If no
+    /// keys are specified, every request is granted operator access.
+    ///
+    pub fn role(&self, key: Option<&str>) -> Option<Role> {
+        // If access is unrestricted, grant operator access
+        if self.is_empty() {
+            return Some(Role::Operator);
+        }
+
+        // Otherwise, check the key against each list (operator keys take priority)
+        let key = key?;
+        if self
+            .operator
+            .iter()
+            .any(|valid| ApiKeys::matches(valid, key))
+        {
+            Some(Role::Operator)
+        } else if self
+            .observer
+            .iter()
+            .any(|valid| ApiKeys::matches(valid, key))
+        {
+            Some(Role::Observer)
+        } else {
+            None
+        }
+    }
+
+    // A helper function to compare two keys without stopping at the first
+    // difference (so that the timing doesn't reveal part of a valid key)
+    fn matches(valid: &str, key: &str) -> bool {
+        valid.len() == key.len()
+            && valid
+                .bytes()
+                .zip(key.bytes())
+                .fold(0, |difference, (a, b)| difference | (a ^ b))
+                == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the roles granted by each key
+    #[test]
+    fn role() {
+        // Without any keys, every request has operator access
+        let mut api_keys = ApiKeys::default();
+        assert_eq!(api_keys.role(None), Some(Role::Operator));
+        assert_eq!(api_keys.role(Some("anything")), Some(Role::Operator));
+
+        // With keys, every request needs a valid key
+        api_keys.extend(ApiKeys {
+            operator: vec!["operator-secret".to_string()],
+            observer: vec!["observer-secret".to_string(), "shared".to_string()],
+        });
+        assert_eq!(api_keys.role(Some("operator-secret")), Some(Role::Operator));
+        assert_eq!(api_keys.role(Some("observer-secret")), Some(Role::Observer));
+        assert_eq!(api_keys.role(Some("operator-secre")), None);
+        assert_eq!(api_keys.role(Some("")), None);
+        assert_eq!(api_keys.role(None), None);
+
+        // Operator keys take priority over observer keys
+        api_keys.operator.push("shared".to_string());
+        assert_eq!(api_keys.role(Some("shared")), Some(Role::Operator));
+
+        // Observer keys alone still restrict access
+        let api_keys = ApiKeys {
+            operator: Vec::new(),
+            observer: vec!["observer-secret".to_string()],
+        };
+        assert_eq!(api_keys.role(Some("observer-secret")), Some(Role::Observer));
+        assert_eq!(api_keys.role(None), None);
+        assert!(Role::Operator > Role::Observer);
+    }
+
+    // Test the comparison of keys
+    #[test]
+    fn matches() {
+        assert!(ApiKeys::matches("secret", "secret"));
+        assert!(!ApiKeys::matches("secret", "secreT"));
+        assert!(!ApiKeys::matches("secret", "secret2"));
+        assert!(!ApiKeys::matches("secret", "secre"));
+        assert!(!ApiKeys::matches("secret", ""));
+        assert!(ApiKeys::matches("", ""));
+    }
+}
@@ -28,11 +28,23 @@ use fnv::FnvHashMap;
 
 /// A structure to save a media cue with timing information
 ///
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MediaPlayback {
     pub media_cue: MediaCue,  // the media information that was cued
     pub seek_to: Duration,    // the last known position of the media
     pub state: PlaybackState, // the current state of the media
+    #[serde(default)]
+    pub updated: u64, // the time of the last known position, in milliseconds since the epoch (zero if unknown)
+    #[serde(default)]
+    pub group: Option<PlaybackGroup>, // the group of channels which play together with this channel, if any
+}
+
+/// A structure to save the group of channels which play together
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaybackGroup {
+    pub id: u64, // the unique identifier of the group (shared by every channel in the group)
+    pub loop_group: bool, // a flag to restart every channel together once all the media has ended
 }
 
 /// Implement time updates for the MediaPlayback
@@ -49,3 +61,6 @@ impl MediaPlayback {
 
 /// A structure to store the media playbacks in a playlist
 pub type MediaPlaylist = FnvHashMap<u32, MediaPlayback>;
+
+/// A structure to store the most recent volume of each channel
+pub type VolumeList = FnvHashMap<u32, ChannelVolume>;
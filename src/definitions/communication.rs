@@ -21,8 +21,11 @@
 // Import crate definitions
 use crate::definitions::*;
 
+// Import Gstreamer Library
+use gstreamer as gst;
+
 // Import Tokio features
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 
 // Import standard library features
 use std::sync::{Arc, Mutex, mpsc as std_mpsc};
@@ -59,6 +62,42 @@ impl WebSend {
     }
 }
 
+/// The stucture and methods to share playback events with any listeners
+///
+#[derive(Clone, Debug)]
+pub struct EventSend {
+    event_send: broadcast::Sender<PlaybackEvent>, // the broadcast line to pass playback events
+}
+
+// Implement the key features of the event send struct
+impl EventSend {
+    /// A function to create a new EventSend
+    ///
+    /// Listeners can subscribe to the returned structure to receive a copy of
+    /// every subsequent event.
+    ///
+    pub fn new() -> Self {
+        // Create the new channel (the initial receiver is not needed)
+        let (event_send, _) = broadcast::channel(256);
+
+        // Create and return the new item
+        EventSend { event_send }
+    }
+
+    /// A method to send a playback event. This method fails silently,
+    /// including when there are no listeners.
+    ///
+    pub fn send(&self, event: PlaybackEvent) {
+        self.event_send.send(event).unwrap_or(0);
+    }
+
+    /// A method to create a new receiver for all subsequent playback events
+    ///
+    pub fn subscribe(&self) -> broadcast::Receiver<PlaybackEvent> {
+        self.event_send.subscribe()
+    }
+}
+
 /// A structure for carrying requests from the web interface
 ///
 pub struct WebRequest {
@@ -76,8 +115,8 @@ pub enum Request {
         channel_realignment: ChannelRealignment,
     },
 
-    /// A variant to stop all playing media
-    AllStop,
+    /// A variant to stop all playing media, with an optional fade out
+    AllStop { fade_out: Option<AudioFade> },
 
     /// A variant to define a new window
     DefineWindow {
@@ -89,20 +128,102 @@ pub enum Request {
         media_channel: MediaChannel, // the new media channel definition
     },
 
+    /// A variant to remove an existing window
+    RemoveWindow { window_number: u32 },
+
+    /// A variant to replace the definition of an existing window
+    RedefineWindow {
+        window: WindowDefinition, // the new application window definition
+    },
+
+    /// A variant to stop and remove an existing channel
+    RemoveChannel { channel: u32 },
+
+    /// A variant to replace the definition of an existing channel
+    RedefineChannel {
+        media_channel: MediaChannel, // the new media channel definition
+    },
+
     /// A variant to cue media to play on a specific channel
     CueMedia { media_cue: MediaCue },
 
+    /// A variant to cue media on several channels which start together and
+    /// play on a shared clock
+    CueGroup { cue_group: CueGroup },
+
+    /// A variant to load media on a specific channel in the background so
+    /// that a later cue of the same media starts instantly
+    PreloadMedia { media_cue: MediaCue },
+
     /// A variant to change the playback state of a channel
     ChangeState { channel_state: ChannelState },
 
+    /// A variant to change the volume of a channel
+    ChangeVolume { channel_volume: ChannelVolume },
+
     /// A variant to change location and/or size of a video frame
     ResizeChannel {
         channel_allocation: ChannelAllocation,
     },
 
+    /// A variant to change the background of a window or the idle background
+    /// of a channel
+    ChangeBackground { background_change: BackgroundChange },
+
+    /// A variant to change the stacking order of a video frame
+    ChangeLayer { channel_layer: ChannelLayer },
+
+    /// A variant to change the opacity of a video frame, with an optional fade
+    ChangeOpacity { channel_opacity: ChannelOpacity },
+
+    /// A variant to hide or show a video frame without changing the media
+    ChangeVisibility {
+        channel_visibility: ChannelVisibility,
+    },
+
     /// A variant to seek within the media of a channel
     Seek { channel_seek: ChannelSeek },
 
+    /// A variant to report the status of every window and channel
+    GetStatus,
+
+    /// A variant to report the status of a single channel
+    GetChannelStatus { channel: u32 },
+
+    /// A variant to report every monitor connected to the display
+    GetMonitors,
+
+    /// A variant to load a new cue list (and replace any previous list)
+    LoadCueList { cue_list: CueList },
+
+    /// A variant to play the standby cue in the cue list
+    Go,
+
+    /// A variant to play the cue before the most recent cue in the cue list
+    Back,
+
+    /// A variant to play a specific cue in the cue list
+    GoTo { cue: usize },
+
+    /// A variant to report the current position in the cue list
+    GetCueList,
+
+    /// A variant to run a request at a future time
+    ScheduleRequest { schedule: Schedule },
+
+    /// A variant to report every request waiting to run
+    ListScheduled,
+
+    /// A variant to cancel a request waiting to run
+    CancelScheduled { id: u64 },
+
+    /// A variant to run each request in a preset from the show file
+    RecallPreset { name: String },
+
+    /// A variant to save the current windows, channels, media, cue list, and
+    /// presets as a show file
+    SaveShow { path: String },
+
     /// A variant to close the program and unload all the data
     Close,
 }
@@ -118,6 +239,41 @@ pub enum WebReply {
         is_valid: bool,  // a flag to indicate the result of the request
         message: String, // a message describing the success or failure
     },
+
+    // A variant for replies with the status of every window and channel
+    #[serde(rename_all = "camelCase")]
+    Status {
+        is_valid: bool,              // a flag to indicate the result of the request
+        system_status: SystemStatus, // the current status of the system
+    },
+
+    // A variant for replies with the status of a single channel
+    #[serde(rename_all = "camelCase")]
+    Channel {
+        is_valid: bool,                // a flag to indicate the result of the request
+        channel_status: ChannelStatus, // the current status of the channel
+    },
+
+    // A variant for replies with the current position in the cue list
+    #[serde(rename_all = "camelCase")]
+    CueList {
+        is_valid: bool,                 // a flag to indicate the result of the request
+        cue_list_status: CueListStatus, // the current position in the cue list
+    },
+
+    // A variant for replies with every request waiting to run
+    #[serde(rename_all = "camelCase")]
+    Scheduled {
+        is_valid: bool,              // a flag to indicate the result of the request
+        schedule_list: ScheduleList, // the requests waiting to run, in the order they will run
+    },
+
+    // A variant for replies with every monitor connected to the display
+    #[serde(rename_all = "camelCase")]
+    Monitors {
+        is_valid: bool,        // a flag to indicate the result of the request
+        monitors: MonitorList, // the monitors, in order of their index
+    },
 }
 
 // Implement key features of the web reply
@@ -143,25 +299,81 @@ impl WebReply {
         }
     }
 
+    /// A function to return a new web reply with the system status
+    ///
+    pub fn status(system_status: SystemStatus) -> WebReply {
+        WebReply::Status {
+            is_valid: true,
+            system_status,
+        }
+    }
+
+    /// A function to return a new web reply with the status of a channel
+    ///
+    pub fn channel(channel_status: ChannelStatus) -> WebReply {
+        WebReply::Channel {
+            is_valid: true,
+            channel_status,
+        }
+    }
+
+    /// A function to return a new web reply with the position in the cue list
+    ///
+    pub fn cue_list(cue_list_status: CueListStatus) -> WebReply {
+        WebReply::CueList {
+            is_valid: true,
+            cue_list_status,
+        }
+    }
+
+    /// A function to return a new web reply with the scheduled requests
+    ///
+    pub fn scheduled(schedule_list: ScheduleList) -> WebReply {
+        WebReply::Scheduled {
+            is_valid: true,
+            schedule_list,
+        }
+    }
+
+    /// A function to return a new web reply with the monitors
+    ///
+    pub fn monitors(monitors: MonitorList) -> WebReply {
+        WebReply::Monitors {
+            is_valid: true,
+            monitors,
+        }
+    }
+
     /// A method to check if the reply is a success
     ///
     pub fn is_success(&self) -> bool {
         match self {
             &WebReply::Generic { ref is_valid, .. } => is_valid.clone(),
+            &WebReply::Status { ref is_valid, .. } => is_valid.clone(),
+            &WebReply::Channel { ref is_valid, .. } => is_valid.clone(),
+            &WebReply::CueList { ref is_valid, .. } => is_valid.clone(),
+            &WebReply::Scheduled { ref is_valid, .. } => is_valid.clone(),
+            &WebReply::Monitors { ref is_valid, .. } => is_valid.clone(),
         }
     }
 }
 
 /// An enum type to provide updates to the user interface thread.
 ///
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum InterfaceUpdate {
     /// A variant to define window properties
     Window { window: WindowDefinition },
 
+    /// A variant to remove an application window
+    RemoveWindow { window_number: u32 },
+
     /// A variant to create a new video channel
     Video { video_stream: VideoStream },
 
+    /// A variant to remove a video channel
+    RemoveVideo { channel: u32 },
+
     /// A variant to resize the video frame
     Resize {
         channel_allocation: ChannelAllocation,
@@ -172,6 +384,39 @@ pub enum InterfaceUpdate {
         channel_realignment: ChannelRealignment,
     },
 
+    /// A variant to change the background of an application window
+    Background {
+        window_number: u32,
+        background: Option<Background>,
+    },
+
+    /// A variant to change the idle background of the video frame
+    IdleBackground {
+        channel: u32,
+        background: Option<Background>,
+    },
+
+    /// A variant to show or hide the idle background of the video frame
+    Idle { channel: u32, is_idle: bool },
+
+    /// A variant to change the stacking order of the video frame
+    Layer { channel_layer: ChannelLayer },
+
+    /// A variant to change the opacity of the video frame
+    Opacity { channel_opacity: ChannelOpacity },
+
+    /// A variant to hide or show the video frame
+    Visibility {
+        channel_visibility: ChannelVisibility,
+    },
+
+    /// A variant to show the mixed video for every channel in an application window
+    Compositor {
+        window_number: u32,
+        video_sink: gst::Element,
+        ready: VideoReady,
+    },
+
     /// A variant to close all the windows and exit
     Close,
 }
@@ -181,6 +426,7 @@ pub enum InterfaceUpdate {
 #[derive(Clone, Debug)]
 pub struct InterfaceSend {
     gtk_interface_send: Arc<Mutex<std_mpsc::Sender<InterfaceUpdate>>>, // the line to pass updates to the gtk user interface
+    monitors: Arc<Mutex<MonitorList>>, // the monitors reported by the gtk user interface
 }
 
 // Implement the key features of interface send
@@ -198,6 +444,7 @@ impl InterfaceSend {
         return (
             InterfaceSend {
                 gtk_interface_send: Arc::new(Mutex::new(gtk_interface_send)),
+                monitors: Arc::new(Mutex::new(Vec::new())),
             },
             gtk_receive,
         );
@@ -212,4 +459,74 @@ impl InterfaceSend {
             gtk_send.send(update.clone()).unwrap_or(());
         }
     }
+
+    /// A method to get the shared list of monitors, so that the user interface
+    /// can keep it up to date
+    ///
+    pub fn monitor_share(&self) -> Arc<Mutex<MonitorList>> {
+        self.monitors.clone()
+    }
+
+    /// A method to get a copy of the current monitors (empty if there is no
+    /// display)
+    ///
+    pub fn monitors(&self) -> MonitorList {
+        match self.monitors.lock() {
+            Ok(monitors) => monitors.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The structure and methods to confirm that a video sink is embedded in an
+/// application window. The output which plays to the sink should only start
+/// after confirmation (otherwise the sink opens a window of its own).
+///
+#[derive(Clone, Debug)]
+pub struct VideoReady {
+    ready_send: Arc<Mutex<Option<oneshot::Sender<()>>>>, // the line to confirm the video sink, until used
+}
+
+// Implement the key features of video ready
+impl VideoReady {
+    /// A function to create a new VideoReady
+    ///
+    /// The function returns the VideoReady structure and the receiving line
+    /// which completes once the video sink is confirmed. The receiving line
+    /// returns an error if every copy of the VideoReady is dropped without
+    /// confirmation.
+    ///
+    pub fn new() -> (Self, oneshot::Receiver<()>) {
+        // Create the new line
+        let (ready_send, ready_receive) = oneshot::channel();
+
+        // Create and return the new items
+        (
+            VideoReady {
+                ready_send: Arc::new(Mutex::new(Some(ready_send))),
+            },
+            ready_receive,
+        )
+    }
+
+    /// A method to confirm that the video sink is embedded. Only the first
+    /// confirmation is sent and this method fails silently.
+    ///
+    pub fn confirm(&self) {
+        // Get a lock on the send line
+        if let Ok(mut ready_send) = self.ready_send.lock()
+            && let Some(ready_send) = ready_send.take()
+        {
+            // Send the confirmation
+            ready_send.send(()).unwrap_or(());
+        }
+    }
+}
+
+// Implement equality for video ready (copies of the same confirmation are equal)
+impl PartialEq for VideoReady {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.ready_send, &other.ready_send)
+    }
 }
+impl Eq for VideoReady {}
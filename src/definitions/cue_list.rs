@@ -0,0 +1,87 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to define a sequence of cues.
+
+// Import crate definitions
+use crate::definitions::*;
+
+/// A struct to define a single cue in a cue list
+///
+/// # Note
+///
+/// When the cue fires, the channel allocations are applied first, then the
+/// media cues, and then the channel states. The wait delays the cue after it
+/// is triggered, and the follow (if any) triggers the next cue in the list.
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+    #[serde(default)]
+    pub name: Option<String>, // the optional name of the cue
+    #[serde(default)]
+    pub media_cues: Vec<MediaCue>, // the media to play when the cue fires
+    #[serde(default)]
+    pub channel_states: Vec<ChannelState>, // the channel states to change when the cue fires
+    #[serde(default)]
+    pub channel_allocations: Vec<ChannelAllocation>, // the video frames to change when the cue fires
+    #[serde(default)]
+    pub wait: u64, // the delay between triggering the cue and firing the cue, in milliseconds
+    #[serde(default)]
+    pub follow: Option<CueFollow>, // the optional trigger for the next cue
+}
+
+/// An enum to define how a cue triggers the next cue in the list
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CueFollow {
+    /// A variant to trigger the next cue after a delay (in milliseconds) from
+    /// when this cue fires
+    AutoContinue { delay: u64 },
+
+    /// A variant to trigger the next cue after a delay (in milliseconds) from
+    /// when the media on the channel ends
+    #[serde(rename_all = "camelCase")]
+    AutoFollow {
+        channel: u32,
+        #[serde(default)]
+        delay: u64,
+    },
+}
+
+/// A struct to define a named, ordered list of cues
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CueList {
+    pub name: String,   // the name of the cue list
+    pub cues: Vec<Cue>, // the cues, in the order they are played
+}
+
+/// A struct to report the current position in the cue list
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CueListStatus {
+    pub name: Option<String>,   // the name of the loaded cue list, if any
+    pub current: Option<usize>, // the index of the most recent cue
+    #[serde(default)]
+    pub pending: Option<usize>, // the index of the cue waiting to fire after its wait time, if any
+    pub standby: Option<usize>, // the index of the cue which will play on the next go
+    pub standby_cue: Option<Cue>, // the cue which will play on the next go
+}
@@ -17,8 +17,11 @@
 
 //! This module implements structures to communicate various media parameters.
 
+// Import crate definitions
+use crate::definitions::*;
+
 // Import Gstreamer Library
-use gstreamer_video as gst_video;
+use gstreamer as gst;
 
 /// A struct to define a single media track to play
 ///
@@ -31,12 +34,81 @@ use gstreamer_video as gst_video;
 /// media when this media completes. This takes priority over the channel loop
 /// media field.
 ///
+/// If a fade in is specified, the audio will rise from silence to the channel
+/// volume as the media starts. If a fade out is specified, the audio will fall
+/// to silence as the media approaches the end.
+///
+/// If a transition is specified, the previous media on the channel remains
+/// until the new media is ready and then the channel blends from the previous
+/// media to the new media. Otherwise, the new media replaces the previous
+/// media instantly. Transitions are only possible on channels defined with
+/// transitions enabled (a cue with a transition on any other channel is
+/// refused).
+///
+/// If a start time is specified, the media loads and then starts at that
+/// time on the clock shared with other instances (in the same way as a group
+/// with one channel), so transitions and loop media are ignored. The start
+/// time is not kept in the backup.
+///
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaCue {
     pub uri: String,                // the location of the video or audio file to play
     pub channel: u32, // the channel of the video or audio. New media sent to the same channel will replace the old media, starting instantly
     pub loop_media: Option<String>, // the location of media to loop after this media is complete
+    #[serde(default)]
+    pub fade_in: Option<AudioFade>, // the optional fade at the start of the media
+    #[serde(default)]
+    pub fade_out: Option<AudioFade>, // the optional fade at the end of the media
+    #[serde(default)]
+    pub transition: Option<Transition>, // the optional transition from the previous media
+    #[serde(default)]
+    pub start_at: Option<u64>, // the time to start the media, in milliseconds since the Unix epoch. Defaults to immediately
+}
+
+/// A struct to define several media tracks which start together and play on
+/// a shared clock
+///
+/// # Note
+///
+/// Each media cue must be on a different channel. Pausing or seeking any of
+/// the channels pauses or seeks every channel in the group, so the channels
+/// stay together. If the group loops, every channel restarts together once
+/// all the media has ended.
+///
+/// Transitions and loop media in the media cues are ignored. A channel leaves
+/// the group as soon as other media is cued on the channel.
+///
+/// If a start time is specified, the group starts at that time on the clock
+/// shared with other instances (or the system clock, if not shared). Groups
+/// cued with the same start time on several instances start together.
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CueGroup {
+    pub media_cues: Vec<MediaCue>, // the media to start together, each on a different channel
+    #[serde(default)]
+    pub loop_group: bool, // a flag to restart every channel together once all the media has ended
+    #[serde(default)]
+    pub start_at: Option<u64>, // the time to start the group, in milliseconds since the Unix epoch. Defaults to as soon as the media is loaded
+}
+
+/// An enum to define the transition from the previous media on a channel
+///
+/// # Note
+///
+/// Video transitions are only possible on channels with a video frame. Audio
+/// transitions are possible on every channel.
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Transition {
+    /// A variant to blend the previous media directly into the new media
+    Crossfade { duration: u64 },
+
+    /// A variant to fade the previous media to black (and silence) and then
+    /// fade in the new media, each over half of the duration
+    FadeThroughBlack { duration: u64 },
 }
 
 /// A struct to hold the dimensions of a video frame
@@ -81,15 +153,31 @@ pub enum AudioDevice {
 /// If media is specified in the loop media field, the channel will loop this
 /// media when the first media completes and anytime no other media has been
 /// directed to play on the channel. If no loop media is specified, the channel
-/// will hold on the last frame of the most recent media.
+/// will hold on the last frame of the most recent media (or show the idle
+/// background, if specified, once the media ends or is stopped).
 ///
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// The layer, opacity, and hidden fields are kept up to date as the channel
+/// is changed during the show (so that they are restored with the channel).
+/// The layer of each channel in a window is renumbered from 0 (the bottom) as
+/// channels are added, moved, and removed.
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaChannel {
     pub channel: u32,                              // the channel number
     pub video_frame: Option<VideoFrameWithWindow>, // the video frame. Defaults to a new application window generated by gstreamer
     pub audio_device: Option<AudioDevice>, // the audio device. Defaults to the system default
     pub loop_media: Option<String>, // the media (video or audio) to loop when no other media is playing
+    #[serde(default)]
+    pub idle_background: Option<Background>, // the color or image to show in the video frame when no media is playing
+    #[serde(default)]
+    pub transitions: bool, // a flag to mix two decks on the channel, allowing transitions and preloaded media. Defaults to playing directly to the outputs
+    #[serde(default)]
+    pub layer: Option<u32>, // the stacking order of the video frame in the window (where 0 is the bottom). Defaults to above every other channel in the window
+    #[serde(default)]
+    pub opacity: Option<f64>, // the opacity of the video frame, from 0.0 (transparent) to 1.0 (opaque). Defaults to opaque
+    #[serde(default)]
+    pub hidden: bool, // a flag to hide the video frame without stopping the media. Defaults to shown
 }
 
 /// A type to store a list of media channels
@@ -117,6 +205,68 @@ pub struct ChannelState {
     pub state: PlaybackState, // the new playback state
 }
 
+/// A helper enum to define the shape of an audio fade
+///
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FadeCurve {
+    /// A variant to change the volume evenly over the fade
+    #[default]
+    Linear,
+
+    /// A variant to change the volume evenly in perceived loudness
+    Cubic,
+}
+
+// Implement key features of the fade curve
+impl FadeCurve {
+    /// A method to calculate the level at a point in a fade (with progress
+    /// from 0.0 to 1.0)
+    ///
+    pub fn level(&self, start: f64, target: f64, progress: f64) -> f64 {
+        match self {
+            // Change the level evenly
+            FadeCurve::Linear => start + (target - start) * progress,
+
+            // Change the level evenly in perceived loudness
+            FadeCurve::Cubic => {
+                let level = start.cbrt() + (target.cbrt() - start.cbrt()) * progress;
+                level.powi(3)
+            }
+        }
+    }
+}
+
+/// A struct to define the timing and shape of an audio fade
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioFade {
+    pub duration: u64, // the length of the fade in milliseconds
+    #[serde(default)]
+    pub curve: FadeCurve, // the shape of the fade, defaults to linear
+}
+
+/// A struct to define changes to the volume of a channel
+///
+/// # Note
+///
+/// The volume is a linear multiplier where 1.0 is the original level of the
+/// media (valid from 0.0 to 10.0). The mute flag takes effect instantly and
+/// does not change the volume (if omitted, the channel stays muted or unmuted
+/// as before). If a fade is specified, the volume changes
+/// gradually from the current level to the new level.
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelVolume {
+    pub channel: u32, // the channel of the video or audio
+    pub volume: f64,  // the new volume of the channel
+    #[serde(default)]
+    pub mute: Option<bool>, // an optional flag to indicate whether the channel should be muted
+    pub fade: Option<AudioFade>, // the optional fade to the new volume
+}
+
 /// A struct to define changes to seek position of a channel
 ///
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -134,8 +284,48 @@ pub struct WindowDefinition {
     pub window_number: u32, // the channel where the video should be played
     pub fullscreen: bool,   // a flag to indicate whether the window should be fullscreen
     pub dimensions: Option<(i32, i32)>, // the minimum dimensions of the window
+    #[serde(default)]
+    pub background: Option<Background>, // the color or image behind the video frames. Defaults to black
+    #[serde(default)]
+    pub monitor: Option<MonitorTarget>, // the monitor for the window. Defaults to the choice of the window manager
+    #[serde(default)]
+    pub position: Option<(i32, i32)>, // the location of the top left corner of the window (relative to the monitor, if specified)
+    #[serde(default)]
+    pub compositor: bool, // a flag to mix every channel in the window into a single video
+}
+
+/// An enum to select a monitor by connector name (e.g. HDMI-1) or by index
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MonitorTarget {
+    /// A variant to select the monitor by index, starting from zero
+    Index(i32),
+
+    /// A variant to select the monitor by connector name
+    Name(String),
 }
 
+/// A struct to describe a monitor connected to the display
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub index: i32,                   // the index of the monitor
+    pub name: Option<String>, // the connector name of the monitor (or the model name on Wayland), if available
+    pub manufacturer: Option<String>, // the manufacturer of the monitor, if available
+    pub left: i32,            // the location of the left side of the monitor on the desktop
+    pub top: i32,             // the location of the top of the monitor on the desktop
+    pub width: i32,           // the width of the monitor
+    pub height: i32,          // the height of the monitor
+    pub scale: i32,           // the scale factor of the monitor
+    pub is_primary: bool,     // a flag to indicate the primary monitor
+}
+
+/// A type to store a list of monitors
+///
+pub type MonitorList = Vec<MonitorInfo>;
+
 /// A type to store a list of window definitions
 ///
 pub type WindowList = Vec<WindowDefinition>;
@@ -144,10 +334,76 @@ pub type WindowList = Vec<WindowDefinition>;
 ///
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct VideoStream {
-    pub channel: u32,               // the channel where the video should be played
-    pub window_number: u32,         // the window where the video should be played
-    pub allocation: gtk::Rectangle, // the location of the video in the screen
-    pub video_overlay: gst_video::VideoOverlay, // the video overlay which should be connected to the video id
+    pub channel: u32,             // the channel where the video should be played
+    pub window_number: u32,       // the window where the video should be played
+    pub allocation: VideoFrame,   // the location of the video in the screen
+    pub video_sink: gst::Element, // the video sink, which either supports a video overlay or provides a widget
+    pub idle_background: Option<Background>, // the color or image to show in place of the video when no media is playing
+    pub ready: VideoReady, // the confirmation that the video sink is embedded in the window
+}
+
+/// An enum to describe how video is shown on the display
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VideoOutput {
+    /// A variant to draw video directly into a native window (e.g. on X11)
+    Overlay,
+
+    /// A variant to draw video in a widget provided by the video sink (e.g. on Wayland)
+    Widget,
+
+    /// A variant to send the video to the compositor for the application window
+    Composited,
+
+    /// A variant to discard the video when there is no display
+    Headless,
+}
+
+/// An enum to define a background for a window or video frame
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Background {
+    /// A variant for a solid color, with each component from 0 to 255
+    Color {
+        red: u8,
+        green: u8,
+        blue: u8,
+        #[serde(default = "Background::opaque")]
+        alpha: u8,
+    },
+
+    /// A variant for a still image, scaled to fit (the remaining space is black)
+    Image { path: String },
+}
+
+// Implement helper functionality for the background
+impl Background {
+    // A helper function to provide the default (fully opaque) alpha
+    fn opaque() -> u8 {
+        u8::MAX
+    }
+}
+
+/// An enum to define a change to the background of a window or the idle
+/// background of a channel
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackgroundChange {
+    /// A variant to change the background of a window
+    #[serde(rename_all = "camelCase")]
+    Window {
+        window_number: u32,             // the window number
+        background: Option<Background>, // the new background. Defaults to black
+    },
+
+    /// A variant to change the idle background of a channel
+    #[serde(rename_all = "camelCase")]
+    Channel {
+        channel: u32,                   // the channel number
+        background: Option<Background>, // the new idle background. Defaults to black
+    },
 }
 
 /// A struct to define a new location for a single video channel
@@ -178,3 +434,111 @@ pub struct ChannelRealignment {
     pub channel: u32,         // the channel number
     pub direction: Direction, // the direction of the change
 }
+
+/// An enum to define a change to the stacking order of a video channel
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LayerChange {
+    /// A variant to move the channel up one layer
+    Raise,
+
+    /// A variant to move the channel down one layer
+    Lower,
+
+    /// A variant to move the channel to a specific layer (where 0 is the bottom)
+    Index(u32),
+}
+
+// Implement key features of the layer change
+impl LayerChange {
+    /// A method to calculate the new position of a layer from the current
+    /// position and the number of layers (the result is always a valid position)
+    ///
+    pub fn position(&self, current: usize, count: usize) -> usize {
+        let last = count.saturating_sub(1);
+        match self {
+            LayerChange::Raise => (current + 1).min(last),
+            LayerChange::Lower => current.saturating_sub(1),
+            LayerChange::Index(index) => (*index as usize).min(last),
+        }
+    }
+}
+
+/// A struct to define a new stacking order for a video channel
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelLayer {
+    pub channel: u32,       // the channel number
+    pub layer: LayerChange, // the change to the stacking order
+}
+
+/// A struct to define a new opacity for a video channel
+///
+/// # Note
+///
+/// The opacity ranges from 0.0 (transparent) to 1.0 (opaque). If a fade is
+/// specified, the opacity changes gradually from the current opacity.
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelOpacity {
+    pub channel: u32,            // the channel number
+    pub opacity: f64,            // the new opacity of the channel
+    pub fade: Option<AudioFade>, // the optional fade to the new opacity
+}
+
+/// A struct to hide or show the video frame of a channel (without changing
+/// the media)
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelVisibility {
+    pub channel: u32,  // the channel number
+    pub visible: bool, // a flag to indicate whether the video frame is shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the new position of a layer after each change
+    #[test]
+    fn layer_position() {
+        // Raise and lower stop at the top and bottom
+        assert_eq!(LayerChange::Raise.position(0, 3), 1);
+        assert_eq!(LayerChange::Raise.position(2, 3), 2);
+        assert_eq!(LayerChange::Lower.position(1, 3), 0);
+        assert_eq!(LayerChange::Lower.position(0, 3), 0);
+
+        // A specific layer is limited to the top layer
+        assert_eq!(LayerChange::Index(0).position(2, 3), 0);
+        assert_eq!(LayerChange::Index(1).position(0, 3), 1);
+        assert_eq!(LayerChange::Index(10).position(0, 3), 2);
+
+        // A single layer (or none) never moves
+        assert_eq!(LayerChange::Raise.position(0, 1), 0);
+        assert_eq!(LayerChange::Index(5).position(0, 0), 0);
+    }
+
+    // Test the level at each point in a fade
+    #[test]
+    fn fade_level() {
+        // Both curves start and end at the same levels
+        for curve in [FadeCurve::Linear, FadeCurve::Cubic] {
+            assert!((curve.level(0.0, 1.0, 0.0) - 0.0).abs() < 1e-9);
+            assert!((curve.level(0.0, 1.0, 1.0) - 1.0).abs() < 1e-9);
+            assert!((curve.level(1.0, 0.25, 1.0) - 0.25).abs() < 1e-9);
+        }
+
+        // The linear curve changes evenly
+        assert!((FadeCurve::Linear.level(0.0, 1.0, 0.5) - 0.5).abs() < 1e-9);
+        assert!((FadeCurve::Linear.level(1.0, 0.0, 0.25) - 0.75).abs() < 1e-9);
+
+        // The cubic curve changes evenly in perceived loudness
+        assert!((FadeCurve::Cubic.level(0.0, 1.0, 0.5) - 0.125).abs() < 1e-9);
+        assert!((FadeCurve::Cubic.level(1.0, 0.0, 0.5) - 0.125).abs() < 1e-9);
+        assert!(FadeCurve::Cubic.level(0.2, 0.8, 0.5) < FadeCurve::Linear.level(0.2, 0.8, 0.5));
+    }
+}
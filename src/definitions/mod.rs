@@ -27,11 +27,23 @@ pub const DEFAULT_ADDRESS: &str = "127.0.0.1:27655";
 pub const DEFAULT_LOGLEVEL: Level = Level::WARN;
 
 // Define submodules
+mod access;
 mod backup;
 mod communication;
+mod cue_list;
 mod media;
+mod schedule;
+mod settings;
+mod show;
+mod status;
 
 // Reexport all the definitions from the submodules
+pub use self::access::*;
 pub use self::backup::*;
 pub use self::communication::*;
+pub use self::cue_list::*;
 pub use self::media::*;
+pub use self::schedule::*;
+pub use self::settings::*;
+pub use self::show::*;
+pub use self::status::*;
@@ -0,0 +1,61 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to run requests at a future time.
+
+// Import crate definitions
+use crate::definitions::*;
+
+/// A struct to define a request to run at a future time
+///
+/// # Note
+///
+/// Specify either a delay (in milliseconds) or a local wall clock time in the
+/// form "HH:MM:SS". If the time has already passed today, the request runs at
+/// that time tomorrow. If both are specified, the delay is used.
+///
+/// A delay is timed from when the request is scheduled and is not affected by
+/// changes to the system time. A wall clock time follows the system time. If
+/// the time is skipped by a change to daylight saving time, the request runs
+/// when the clocks change. If the time occurs twice, the request runs the
+/// first time.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    #[serde(default)]
+    pub delay: Option<u64>, // the delay before running the request, in milliseconds
+    #[serde(default)]
+    pub time: Option<String>, // the wall clock time to run the request
+    pub request: Box<Request>, // the request to run
+}
+
+/// A struct to describe a request which is waiting to run
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRequest {
+    pub id: u64,      // the unique identifier for the request
+    pub fire_at: u64, // the time to run the request, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub wall_clock: bool, // a flag to indicate the request runs at a wall clock time (and follows any change to the system time)
+    pub request: Request, // the request to run
+}
+
+/// A type to store a list of scheduled requests
+///
+pub type ScheduleList = Vec<ScheduledRequest>;
@@ -0,0 +1,92 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to hold the options given on the
+//! command line.
+
+// Import crate definitions
+use crate::definitions::*;
+
+/// A struct to hold the options given on the command line, shared by the
+/// system, web, and OSC interfaces
+///
+#[derive(Clone, Debug)]
+pub struct UserSettings {
+    pub address: String,        // the main listening address for the web interface
+    pub listen: ListenSettings, // the other listening addresses for the web interface
+    pub server_location: Option<String>, // the location of the backup server, if any
+    pub show_file: Option<String>, // the show file to load at startup, if any
+    pub show_directory: Option<String>, // the directory for saving show files, if any
+    pub standby: Option<String>, // the address of the primary instance to follow, if on standby
+    pub clock: Option<ClockSource>, // the network clock to publish or follow, if any
+    pub api_keys: ApiKeys,      // the keys which grant access to the web interface
+    pub osc_address: Option<String>, // the listening address for OSC messages, if any
+    pub osc_reply_address: Option<String>, // the address to send replies to OSC messages, if any
+    pub osc_allow: Vec<String>, // the sender addresses allowed to send OSC messages (any sender if empty)
+    pub is_osc_unrestricted: bool, // a flag to accept OSC messages from any sender, even if API keys are required
+    pub is_headless: bool,         // a flag to run without a display
+}
+
+// Implement the default settings
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            address: DEFAULT_ADDRESS.to_string(),
+            listen: ListenSettings::default(),
+            server_location: None,
+            show_file: None,
+            show_directory: None,
+            standby: None,
+            clock: None,
+            api_keys: ApiKeys::default(),
+            osc_address: None,
+            osc_reply_address: None,
+            osc_allow: Vec::new(),
+            is_osc_unrestricted: false,
+            is_headless: !cfg!(feature = "display"), // always headless if built without a display
+        }
+    }
+}
+
+/// A structure to describe any listening addresses for the web interface in
+/// addition to the main address
+///
+/// # Note
+///
+/// If the interface is encrypted only, the main address is not used for
+/// plaintext connections (it should match one of the encrypted addresses).
+///
+#[derive(Clone, Debug, Default)]
+pub struct ListenSettings {
+    pub addresses: Vec<String>, // the other addresses to listen for plaintext connections
+    pub is_encrypted_only: bool, // a flag to listen only for encrypted connections (not on the main address)
+    pub tls_addresses: Vec<String>, // the addresses to listen for encrypted connections
+    pub tls_cert: Option<String>, // the location of the TLS certificate (in PEM format)
+    pub tls_key: Option<String>, // the location of the TLS private key (in PEM format)
+}
+
+/// An enum to select whether this instance publishes or follows the network
+/// clock
+///
+#[derive(Clone, Debug)]
+pub enum ClockSource {
+    /// A variant to publish the clock of this instance at the address
+    Publish { address: String },
+
+    /// A variant to follow the clock published at the address
+    Follow { address: String },
+}
@@ -0,0 +1,405 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to describe a complete show in a single
+//! file.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import standard library features
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// Import FNV HashMap
+use fnv::FnvHashMap;
+
+// Import anyhow features
+use anyhow::{Context, Result};
+
+/// A type to store the named presets, each a list of requests to run in order
+///
+pub type PresetMap = FnvHashMap<String, Vec<Request>>;
+
+/// A struct to define the windows, channels, cues, and presets for a show
+///
+/// # Note
+///
+/// The show file may be written in YAML or JSON. Every section is optional.
+/// The cues are played once the windows and channels are defined, and the
+/// presets can be recalled by name at any time. The API keys are added to any
+/// keys given on the command line.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowFile {
+    #[serde(default)]
+    pub windows: WindowList, // the application windows, in the order to define them
+    #[serde(default)]
+    pub channels: ChannelList, // the media channels, in the order to define them
+    #[serde(default)]
+    pub cues: Vec<MediaCue>, // the media to play once the show is loaded
+    #[serde(default)]
+    pub cue_list: Option<CueList>, // the cue list to load, if any
+    #[serde(default)]
+    pub presets: PresetMap, // the named lists of requests to recall
+    #[serde(default, skip_serializing_if = "ApiKeys::is_empty")]
+    pub api_keys: ApiKeys, // the keys which grant access to the web interface
+}
+
+// Implement key functionality for the show file
+impl ShowFile {
+    /// A function to load and validate a show file from the provided path
+    ///
+    pub fn load<P>(path: P) -> Result<ShowFile>
+    where
+        P: AsRef<Path>,
+    {
+        // Read the file
+        let contents = fs::read_to_string(path.as_ref()).context("Unable to read show file.")?;
+
+        // Parse the file (JSON is read as a subset of YAML)
+        let show_file: ShowFile =
+            serde_yaml::from_str(&contents).context("Unable to parse show file.")?;
+
+        // Make sure the show is valid before returning it
+        show_file.validate()?;
+        Ok(show_file)
+    }
+
+    /// A method to save the show file to the provided path. Paths ending
+    /// in .json are saved as JSON, and all other paths are saved as YAML.
+    ///
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        // Compose the file contents in the correct format
+        let path = path.as_ref();
+        let contents = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::to_string_pretty(self).context("Unable to compose show file.")?
+        } else {
+            serde_yaml::to_string(self).context("Unable to compose show file.")?
+        };
+
+        // Write the file
+        fs::write(path, contents).context("Unable to write show file.")
+    }
+
+    /// A function to find the location to save a show file. The path must be
+    /// relative to the show directory, and may not leave the directory.
+    ///
+    pub fn save_path(directory: Option<&Path>, path: &str) -> Result<PathBuf> {
+        // Make sure there is a show directory
+        let Some(directory) = directory else {
+            return Err(anyhow!("Unable to save show: No show directory specified."));
+        };
+
+        // Make sure the path is a file name (or a relative path) inside the directory
+        let path = Path::new(path);
+        if path.file_name().is_none()
+            || !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(anyhow!(
+                "Unable to save show: The path must be relative to the show directory."
+            ));
+        }
+
+        // Return the complete path
+        Ok(directory.join(path))
+    }
+
+    /// A method to check that the show is consistent. Windows and channels
+    /// must be unique, channels must use defined windows, cues must use
+    /// defined channels, transitions and preloaded media must use channels
+    /// with transitions enabled, and presets may not recall other presets or
+    /// save or close the show.
+    ///
+    pub fn validate(&self) -> Result<()> {
+        // Check each window
+        let mut window_numbers = Vec::new();
+        for window in self.windows.iter() {
+            // Make sure the window is unique
+            if window_numbers.contains(&window.window_number) {
+                return Err(anyhow!(
+                    "Invalid show file: Window {} is defined more than once.",
+                    window.window_number
+                ));
+            }
+            window_numbers.push(window.window_number);
+        }
+
+        // Check each channel
+        let mut channel_numbers = Vec::new();
+        for channel in self.channels.iter() {
+            // Make sure the channel is unique
+            if channel_numbers.contains(&channel.channel) {
+                return Err(anyhow!(
+                    "Invalid show file: Channel {} is defined more than once.",
+                    channel.channel
+                ));
+            }
+            channel_numbers.push(channel.channel);
+
+            // Make sure the window for the channel is defined
+            if let Some(video_frame) = channel.video_frame.as_ref()
+                && !window_numbers.contains(&video_frame.window_number)
+            {
+                return Err(anyhow!(
+                    "Invalid show file: Channel {} uses undefined window {}.",
+                    channel.channel,
+                    video_frame.window_number
+                ));
+            }
+        }
+
+        // Make sure each cue uses a defined channel
+        for media_cue in self.cues.iter() {
+            if !channel_numbers.contains(&media_cue.channel) {
+                return Err(anyhow!(
+                    "Invalid show file: Cue for {} uses undefined channel {}.",
+                    media_cue.uri,
+                    media_cue.channel
+                ));
+            }
+        }
+
+        // Collect the media cued and preloaded by the show, the cue lists, and the presets
+        let mut media_cues: Vec<&MediaCue> = self.cues.iter().collect();
+        let mut preloads: Vec<&MediaCue> = Vec::new();
+        let mut cue_lists: Vec<&CueList> = self.cue_list.iter().collect();
+        for request in self.presets.values().flatten() {
+            match request {
+                Request::CueMedia { media_cue } => media_cues.push(media_cue),
+                Request::PreloadMedia { media_cue } => preloads.push(media_cue),
+                Request::LoadCueList { cue_list } => cue_lists.push(cue_list),
+                _ => (),
+            }
+        }
+        for cue_list in cue_lists {
+            for cue in cue_list.cues.iter() {
+                media_cues.extend(cue.media_cues.iter());
+            }
+        }
+
+        // Make sure each transition and preload uses a channel with transitions
+        // enabled (media with a start time ignores the transition)
+        for media_cue in media_cues
+            .into_iter()
+            .filter(|media_cue| media_cue.transition.is_some() && media_cue.start_at.is_none())
+            .chain(preloads)
+        {
+            if self
+                .channels
+                .iter()
+                .any(|channel| channel.channel == media_cue.channel && !channel.transitions)
+            {
+                return Err(anyhow!(
+                    "Invalid show file: Cue for {} needs transitions enabled on channel {}.",
+                    media_cue.uri,
+                    media_cue.channel
+                ));
+            }
+        }
+
+        // Make sure no preset recalls a preset, saves the show, or closes the program
+        for (name, requests) in self.presets.iter() {
+            if requests.iter().any(|request| {
+                matches!(
+                    request,
+                    Request::RecallPreset { .. } | Request::SaveShow { .. } | Request::Close
+                )
+            }) {
+                return Err(anyhow!(
+                    "Invalid show file: Preset {} contains a request which is not allowed.",
+                    name
+                ));
+            }
+        }
+
+        // Indicate success
+        Ok(())
+    }
+}
+
+// Tests of the show module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A helper function to create a show with one window, channel, and cue
+    fn test_show() -> ShowFile {
+        let mut presets = PresetMap::default();
+        presets.insert(
+            "Blackout".to_string(),
+            vec![Request::AllStop { fade_out: None }],
+        );
+        ShowFile {
+            windows: vec![WindowDefinition {
+                window_number: 1,
+                fullscreen: true,
+                dimensions: None,
+                background: None,
+                monitor: None,
+                position: None,
+                compositor: false,
+            }],
+            channels: vec![MediaChannel {
+                channel: 1,
+                video_frame: Some(VideoFrameWithWindow {
+                    window_number: 1,
+                    top: 0,
+                    left: 0,
+                    height: 1080,
+                    width: 1920,
+                }),
+                audio_device: None,
+                loop_media: None,
+                idle_background: None,
+                transitions: true,
+                layer: None,
+                opacity: None,
+                hidden: false,
+            }],
+            cues: vec![MediaCue {
+                uri: "file:///video.mp4".to_string(),
+                channel: 1,
+                loop_media: None,
+                fade_in: Some(AudioFade {
+                    duration: 1000,
+                    curve: FadeCurve::Cubic,
+                }),
+                fade_out: None,
+                transition: Some(Transition::Crossfade { duration: 2000 }),
+                start_at: None,
+            }],
+            cue_list: None,
+            presets,
+            api_keys: ApiKeys::default(),
+        }
+    }
+
+    // Test the checks of the show
+    #[test]
+    fn validate_show() {
+        // A consistent show is valid
+        assert!(test_show().validate().is_ok());
+
+        // Windows must be unique
+        let mut show = test_show();
+        show.windows.push(show.windows[0].clone());
+        assert!(show.validate().is_err());
+
+        // Channels must be unique
+        let mut show = test_show();
+        show.channels.push(show.channels[0].clone());
+        assert!(show.validate().is_err());
+
+        // Channels must use a defined window
+        let mut show = test_show();
+        show.windows.clear();
+        assert!(show.validate().is_err());
+
+        // Cues must use a defined channel
+        let mut show = test_show();
+        show.cues[0].channel = 2;
+        assert!(show.validate().is_err());
+
+        // Transitions must use a channel with transitions enabled
+        let mut show = test_show();
+        show.channels[0].transitions = false;
+        assert!(show.validate().is_err());
+        show.cues[0].transition = None;
+        assert!(show.validate().is_ok());
+
+        // Preloaded media must also use a channel with transitions enabled
+        show.presets.insert(
+            "Preload".to_string(),
+            vec![Request::PreloadMedia {
+                media_cue: show.cues[0].clone(),
+            }],
+        );
+        assert!(show.validate().is_err());
+
+        // Presets may not save or close the show
+        let mut show = test_show();
+        show.presets
+            .insert("Close".to_string(), vec![Request::Close]);
+        assert!(show.validate().is_err());
+    }
+
+    // Test the location for saving a show
+    #[test]
+    fn save_path() {
+        // A file or relative path inside the directory is allowed
+        let directory = Path::new("/shows");
+        assert_eq!(
+            ShowFile::save_path(Some(directory), "show.yaml").unwrap(),
+            PathBuf::from("/shows/show.yaml")
+        );
+        assert_eq!(
+            ShowFile::save_path(Some(directory), "act_one/show.json").unwrap(),
+            PathBuf::from("/shows/act_one/show.json")
+        );
+
+        // Paths which could leave the directory are refused
+        assert!(ShowFile::save_path(Some(directory), "/etc/show.yaml").is_err());
+        assert!(ShowFile::save_path(Some(directory), "../show.yaml").is_err());
+        assert!(ShowFile::save_path(Some(directory), "act_one/../../show.yaml").is_err());
+        assert!(ShowFile::save_path(Some(directory), "").is_err());
+
+        // Nothing can be saved without a show directory
+        assert!(ShowFile::save_path(None, "show.yaml").is_err());
+    }
+
+    // Test saving and loading a show in each format
+    #[test]
+    fn save_and_load() {
+        // Create a unique directory for the test
+        let directory = std::env::temp_dir().join(format!(
+            "apollo-show-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+
+        // Save and reload the show as YAML and JSON
+        let show = test_show();
+        for name in ["show.yaml", "show.json"] {
+            let path = ShowFile::save_path(Some(&directory), name).unwrap();
+            show.save(&path).unwrap();
+            let loaded = ShowFile::load(&path).unwrap();
+
+            // Make sure the show is unchanged (including the fades and transition)
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&show).unwrap()
+            );
+        }
+
+        // Remove the directory
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements structures to report the live status of the program.
+
+// Import crate definitions
+use crate::definitions::*;
+
+/// A struct to report the current status of a single media channel
+///
+/// # Note
+///
+/// The state, position, and duration are queried directly from the channel
+/// and are empty if no media is currently loaded on the channel.
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelStatus {
+    pub media_channel: MediaChannel, // the current definition of the channel
+    pub current_uri: Option<String>, // the location of the media currently loaded on the channel
+    pub loop_media: Option<String>,  // the media that will loop when the current media completes
+    pub state: Option<PlaybackState>, // the current playback state of the channel
+    pub position: Option<u64>,       // the current position of the media in milliseconds
+    pub duration: Option<u64>,       // the total length of the media in milliseconds
+}
+
+/// A type to store a list of channel statuses
+///
+pub type ChannelStatusList = Vec<ChannelStatus>;
+
+/// A struct to report the current status of every window and channel
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemStatus {
+    pub windows: WindowList, // the application windows, in the order defined
+    pub channels: ChannelStatusList, // the status of each channel, sorted by channel number
+}
+
+/// An enum to report changes in the playback of a channel
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaybackEvent {
+    /// A variant to indicate that new media started on a channel
+    MediaStarted { channel: u32, uri: Option<String> },
+
+    /// A variant to indicate that the media on a channel reached the end
+    MediaEnded { channel: u32, uri: Option<String> },
+
+    /// A variant to indicate that the loop media restarted on a channel
+    LoopRestarted { channel: u32, uri: String },
+
+    /// A variant to indicate that the playback state of a channel changed
+    StateChanged {
+        channel: u32,
+        state: Option<PlaybackState>,
+    },
+
+    /// A variant to indicate that a seek on a channel completed
+    SeekCompleted { channel: u32, position: Option<u64> },
+
+    /// A variant to indicate the buffering progress of a channel
+    Buffering { channel: u32, percent: i32 },
+
+    /// A variant to report an error from a channel
+    Error { channel: u32, message: String },
+
+    /// A variant to report a warning from a channel
+    Warning { channel: u32, message: String },
+}
@@ -34,7 +34,7 @@ use self::video_window::VideoWindow;
 // Import standard library features
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::sync::mpsc;
+use std::sync::{Arc, Mutex, mpsc};
 use std::time::Duration;
 
 // Import GTK and GDK libraries
@@ -61,6 +61,7 @@ impl GtkInterface {
     pub fn spawn_interface(
         application: &gtk::Application,
         interface_receive: mpsc::Receiver<InterfaceUpdate>,
+        monitors: Arc<Mutex<MonitorList>>,
     ) {
         // Create the empty placeholder window
         let empty_window = gtk::ApplicationWindow::new(application);
@@ -77,6 +78,24 @@ impl GtkInterface {
             empty_window,
         };
 
+        // Share the current monitors and update them whenever they change
+        if let Some(display) = gdk::Display::default() {
+            GtkInterface::update_monitors(&display, &monitors);
+            for index in 0..display.n_monitors() {
+                if let Some(monitor) = display.monitor(index) {
+                    GtkInterface::watch_monitor(&display, &monitor, &monitors);
+                }
+            }
+            let added = monitors.clone();
+            display.connect_monitor_added(move |display, monitor| {
+                GtkInterface::watch_monitor(display, monitor, &added);
+                GtkInterface::update_monitors(display, &added);
+            });
+            display.connect_monitor_removed(move |display, _| {
+                GtkInterface::update_monitors(display, &monitors);
+            });
+        }
+
         // Launch the interface monitoring interrupt, currently set to ten times a second FIXME make this async
         let update_interface = clone!(gtk_interface => move || {
             gtk_interface.check_updates(&interface_receive);
@@ -86,6 +105,66 @@ impl GtkInterface {
         // triggers once every 10ms
     }
 
+    /// A function to choose how video is shown, based on the type of display.
+    /// Video is drawn directly into a native window on X11 (and Quartz), and
+    /// drawn in a widget provided by the video sink on any other display
+    /// (such as Wayland).
+    ///
+    pub fn video_output() -> VideoOutput {
+        // Check the type of the default display
+        match gdk::Display::default().map(|display| display.type_().name()) {
+            Some("GdkX11Display") | Some("GdkQuartzDisplay") => VideoOutput::Overlay,
+            _ => VideoOutput::Widget,
+        }
+    }
+
+    /// A helper function to update the shared list of monitors whenever the
+    /// position, size, or scale of a monitor changes (or the display reports
+    /// the model name of the monitor)
+    ///
+    fn watch_monitor(
+        display: &gdk::Display,
+        monitor: &gdk::Monitor,
+        monitors: &Arc<Mutex<MonitorList>>,
+    ) {
+        for property in ["geometry", "scale-factor", "model"] {
+            let display = display.clone();
+            let monitors = monitors.clone();
+            monitor.connect_notify_local(Some(property), move |_, _| {
+                GtkInterface::update_monitors(&display, &monitors);
+            });
+        }
+    }
+
+    /// A helper function to replace the shared list of monitors with those
+    /// currently connected to the display
+    ///
+    fn update_monitors(display: &gdk::Display, monitors: &Arc<Mutex<MonitorList>>) {
+        // Describe each monitor
+        let list = (0..display.n_monitors())
+            .filter_map(|index| {
+                let monitor = display.monitor(index)?;
+                let geometry = monitor.geometry();
+                Some(MonitorInfo {
+                    index,
+                    name: monitor.model().map(|name| name.to_string()),
+                    manufacturer: monitor.manufacturer().map(|name| name.to_string()),
+                    left: geometry.x(),
+                    top: geometry.y(),
+                    width: geometry.width(),
+                    height: geometry.height(),
+                    scale: monitor.scale_factor(),
+                    is_primary: monitor.is_primary(),
+                })
+            })
+            .collect();
+
+        // Replace the shared list
+        if let Ok(mut lock) = monitors.lock() {
+            *lock = list;
+        }
+    }
+
     /// A method to listen for modifications to the gtk interface.
     ///
     /// This method listens on the provided interface_update line for any changes
@@ -115,12 +194,24 @@ impl GtkInterface {
                     video_window.define_window(window);
                 }
 
+                // Remove a window
+                InterfaceUpdate::RemoveWindow { window_number } => {
+                    // Remove the window and any videos in it
+                    video_window.remove_window(window_number);
+                }
+
                 // Load the new video stream
                 InterfaceUpdate::Video { video_stream } => {
                     // Add the new video stream
                     video_window.add_new_video(video_stream);
                 }
 
+                // Remove a video stream
+                InterfaceUpdate::RemoveVideo { channel } => {
+                    // Remove the video area
+                    video_window.remove_video(channel);
+                }
+
                 // Resize a video stream
                 InterfaceUpdate::Resize { channel_allocation } => {
                     // Change the location of the video stream
@@ -135,6 +226,51 @@ impl GtkInterface {
                     video_window.change_alignment(channel_realignment);
                 }
 
+                // Change the idle background of a video stream
+                InterfaceUpdate::IdleBackground {
+                    channel,
+                    background,
+                } => {
+                    video_window.change_idle_background(channel, background);
+                }
+
+                // Show or hide the idle background of a video stream
+                InterfaceUpdate::Idle { channel, is_idle } => {
+                    video_window.change_idle(channel, is_idle);
+                }
+
+                // Change the stacking order of a video stream
+                InterfaceUpdate::Layer { channel_layer } => {
+                    video_window.change_layer(channel_layer);
+                }
+
+                // Change the opacity of a video stream
+                InterfaceUpdate::Opacity { channel_opacity } => {
+                    video_window.change_opacity(channel_opacity);
+                }
+
+                // Hide or show a video stream
+                InterfaceUpdate::Visibility { channel_visibility } => {
+                    video_window.change_visibility(channel_visibility);
+                }
+
+                // Change the background of a window
+                InterfaceUpdate::Background {
+                    window_number,
+                    background,
+                } => {
+                    video_window.change_background(window_number, background);
+                }
+
+                // Show the mixed video for a window
+                InterfaceUpdate::Compositor {
+                    window_number,
+                    video_sink,
+                    ready,
+                } => {
+                    video_window.add_compositor(window_number, video_sink, ready);
+                }
+
                 // Clear all the video channels and exit
                 InterfaceUpdate::Close => {
                     // Otherwise, destroy the video window
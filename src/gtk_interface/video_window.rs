@@ -22,16 +22,20 @@
 use crate::definitions::*;
 
 // Import standard library features
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ffi::c_void;
+use std::fs;
 use std::rc::Rc;
+use std::time::Duration;
 
 // Import GTK and GDK libraries
 use gdk::Cursor;
+use gdk_pixbuf::Pixbuf;
 use gtk::prelude::*;
 
 // Import Gstreamer Library
 use gst_video::prelude::*;
+use gstreamer as gst;
 use gstreamer_video as gst_video;
 
 // Import FNV HashMap
@@ -40,12 +44,45 @@ use fnv::FnvHashMap;
 // Import tracing features
 use tracing::error;
 
+// Define module constants
+const COMPOSITOR_NAME: &str = "compositor"; // the widget name for the mixed video of a window (which fills the window)
+const VIDEO_NAME: &str = "video"; // the name of the video in the stack for each channel
+const IDLE_NAME: &str = "idle"; // the name of the idle background in the stack for each channel
+const FADE_INTERVAL: u64 = 20; // the time between opacity steps during a fade, in milliseconds
+const DRM_DIRECTORY: &str = "/sys/class/drm"; // the directory where the kernel lists the connectors of each graphics card
+
+/// A helper enum to hold a window background, ready to draw
+///
+enum LoadedBackground {
+    /// A variant for a solid color (red, green, blue, and alpha from 0.0 to 1.0)
+    Color(f64, f64, f64, f64),
+
+    /// A variant for a still image
+    Image(Pixbuf),
+}
+
+/// A helper type to share a window background with the draw callback
+///
+type SharedBackground = Rc<RefCell<Option<LoadedBackground>>>;
+
+/// A helper structure to hold the idle background of a channel, which is
+/// shown in place of the video when no media is playing
+///
+struct ChannelIdle {
+    background: SharedBackground, // the idle background, shared with the draw callback
+    is_idle: bool,                // a flag to indicate that no media is playing
+    stack: gtk::Stack, // the stack which switches between the video and the idle background
+}
+
 /// A structure to contain the window for displaying video streams.
 ///
 pub struct VideoWindow {
     overlay_map: FnvHashMap<u32, gtk::Overlay>, // the mapping of the overlay widgets
     channel_map: Rc<RefCell<FnvHashMap<std::string::String, gtk::Rectangle>>>, // the mapping of channel numbers to allocations
     window_map: FnvHashMap<u32, u32>, // the mapping of channel numbers to windows
+    background_map: FnvHashMap<u32, SharedBackground>, // the mapping of window numbers to backgrounds
+    fade_map: FnvHashMap<u32, Rc<Cell<u64>>>, // the mapping of channel numbers to the most recent opacity change (to cancel older fades)
+    idle_map: FnvHashMap<u32, ChannelIdle>,   // the mapping of channel numbers to idle backgrounds
 }
 
 // Implement key features for the video window
@@ -66,6 +103,9 @@ impl VideoWindow {
             overlay_map,
             channel_map,
             window_map,
+            background_map: FnvHashMap::default(),
+            fade_map: FnvHashMap::default(),
+            idle_map: FnvHashMap::default(),
         }
     }
 
@@ -86,18 +126,46 @@ impl VideoWindow {
             map.clear();
         }
 
-        // Empty the window map
+        // Empty the window map, background map, fade map, and idle map
         self.window_map = FnvHashMap::default();
+        self.background_map.clear();
+        self.fade_map.clear();
+        self.idle_map.clear();
     }
 
-    /// A method to define a new application window
+    /// A method to define a new application window. If the window already
+    /// exists, the existing window is updated to the new definition.
     ///
     pub fn define_window(&mut self, definition: WindowDefinition) {
         // Copy the window number
         let window_number = definition.window_number;
 
+        // If the window already exists
+        if let Some(overlay) = self.overlay_map.get(&window_number) {
+            // Remove the mixed video if the window no longer has a compositor
+            if !definition.compositor {
+                VideoWindow::remove_named(overlay, COMPOSITOR_NAME);
+            }
+
+            // Try to get the window and the background
+            let window = overlay
+                .parent()
+                .and_then(|parent| parent.downcast::<gtk::Window>().ok());
+            let background = overlay
+                .child()
+                .and_then(|child| child.downcast::<gtk::DrawingArea>().ok());
+
+            // Update the window settings
+            if let (Some(window), Some(background), Some(shared)) =
+                (window, background, self.background_map.get(&window_number))
+            {
+                VideoWindow::apply_definition(&window, &background, shared, Some(definition));
+            }
+            return;
+        }
+
         // Create the new window and pass dimensions if specified
-        let (window, overlay) = self.new_window(Some(definition));
+        let (window, overlay) = self.new_window(window_number, Some(definition));
 
         // Save the overlay in the overlay map
         self.overlay_map.insert(window_number, overlay);
@@ -106,17 +174,126 @@ impl VideoWindow {
         window.show_all();
     }
 
+    /// A method to change the background of an application window
+    ///
+    pub fn change_background(&mut self, window_number: u32, background: Option<Background>) {
+        // Try to find the window and the background
+        let shared = self.background_map.get(&window_number);
+        let area = self
+            .overlay_map
+            .get(&window_number)
+            .and_then(|overlay| overlay.child());
+
+        // Replace the background and redraw the window
+        if let (Some(shared), Some(area)) = (shared, area) {
+            if let Ok(mut current) = shared.try_borrow_mut() {
+                *current = VideoWindow::load_background(background);
+            }
+            area.queue_draw();
+
+        // Otherwise, warn the user
+        } else {
+            error!(
+                "Unable to change background: Window {} not found.",
+                window_number
+            );
+        }
+    }
+
+    /// A method to remove an application window and any videos in the window
+    ///
+    pub fn remove_window(&mut self, window_number: u32) {
+        // Remove the background from the background map
+        self.background_map.remove(&window_number);
+
+        // Try to remove the overlay from the overlay map
+        if let Some(overlay) = self.overlay_map.remove(&window_number) {
+            // Close the window
+            if let Some(window) = overlay
+                .parent()
+                .and_then(|parent| parent.downcast::<gtk::Window>().ok())
+            {
+                window.close();
+            }
+        }
+
+        // Remove any channels in the window from the channel map and idle map
+        let mut removed = Vec::new();
+        self.window_map.retain(|channel, window| {
+            let is_removed = *window == window_number;
+            if is_removed {
+                removed.push(*channel);
+            }
+            !is_removed
+        });
+        for channel in removed.iter() {
+            self.idle_map.remove(channel);
+        }
+        if let Ok(mut map) = self.channel_map.try_borrow_mut() {
+            for channel in removed {
+                map.remove(&channel.to_string());
+            }
+        }
+    }
+
     /// A method to add a new video to the video window
     ///
     pub fn add_new_video(&mut self, video_stream: VideoStream) {
-        // Create a new video area
-        let video_area = gtk::DrawingArea::new();
+        // Create a new video area (connected to a video overlay or provided by the sink)
+        let video_area = match video_stream
+            .video_sink
+            .clone()
+            .dynamic_cast::<gst_video::VideoOverlay>()
+        {
+            Ok(video_overlay) => VideoWindow::new_overlay_area(video_overlay),
+            Err(video_sink) => match VideoWindow::sink_widget(&video_sink) {
+                Some(widget) => widget,
+                None => return,
+            },
+        };
+
+        // Create the idle background, drawn over the rest of the window
+        let background: SharedBackground = Rc::new(RefCell::new(VideoWindow::load_background(
+            video_stream.idle_background.clone(),
+        )));
+        let idle_area = gtk::DrawingArea::new();
+        let draw_background = background.clone();
+        idle_area.connect_draw(move |area, cr| {
+            // Draw the current idle background
+            VideoWindow::paint_background(area, cr, &draw_background);
+            glib::Propagation::Stop
+        });
+
+        // Switch between the video and the idle background (which starts shown)
+        let stack = gtk::Stack::new();
+        stack.set_transition_type(gtk::StackTransitionType::None);
+        video_area.show();
+        idle_area.show();
+        stack.add_named(&video_area, VIDEO_NAME);
+        stack.add_named(&idle_area, IDLE_NAME);
+        let idle = ChannelIdle {
+            background,
+            is_idle: true,
+            stack: stack.clone(),
+        };
+        VideoWindow::show_idle(&idle);
+        let video_widget = video_area;
+        let video_area: gtk::Widget = stack.upcast();
 
         // Try to add the video area to the channel map
         match self.channel_map.try_borrow_mut() {
             // Insert the new channel
             Ok(mut map) => {
-                map.insert(video_stream.channel.to_string(), video_stream.allocation);
+                let allocation = video_stream.allocation;
+                map.insert(
+                    video_stream.channel.to_string(),
+                    gtk::Rectangle::new(
+                        allocation.left,
+                        allocation.top,
+                        allocation.width,
+                        allocation.height,
+                    ),
+                );
             }
 
             // Fail silently
@@ -127,89 +304,65 @@ impl VideoWindow {
         // Extract the window number (for use below)
         let window_number = video_stream.window_number;
 
-        // Save the channel -> window mapping to the map
+        // Save the channel -> window mapping and the idle background to the maps
         self.window_map
             .insert(video_stream.channel, video_stream.window_number);
+        self.idle_map.insert(video_stream.channel, idle);
 
-        // Draw a black background
-        video_area.connect_draw(|_, cr| {
-            // Draw the background black
-            cr.set_source_rgb(0.0, 0.0, 0.0);
-            cr.paint().unwrap_or(());
-            glib::Propagation::Stop
-        });
+        // Check to see if there is already a matching window
+        if let Some(overlay) = self.overlay_map.get(&window_number) {
+            // Add the video area to the overlay
+            overlay.add_overlay(&video_area);
 
-        // Connect the realize signal for the video area
-        video_area.connect_realize(move |video_area| {
-            // Extract a reference for the video overlay
-            let video_overlay = &video_stream.video_overlay;
+            // Show the video area
+            video_area.show();
 
-            // Try to get a copy of the GDk window
-            let gdk_window = match video_area.window() {
-                Some(window) => window,
-                None => {
-                    error!("Unable to get current window for video overlay.");
-                    return;
-                }
-            };
+        // Otherwise, create a new window
+        } else {
+            // Create the new window
+            let (window, overlay) = self.new_window(window_number, None);
 
-            // Check to make sure the window is native
-            if !gdk_window.ensure_native() {
-                error!("Widget is not located inside a native window.");
-                return;
-            }
+            // Add the video area to the overlay
+            overlay.add_overlay(&video_area);
 
-            // Extract the display type of the window
-            let display_type = gdk_window.display().type_().name();
+            // Save the overlay in the overlay map
+            self.overlay_map.insert(window_number, overlay);
 
-            // Switch based on the platform
-            #[cfg(target_os = "linux")]
-            {
-                // Check if we're using X11
-                if display_type == "GdkX11Display" {
-                    // Connect to the get_xid function
-                    unsafe extern "C" {
-                        pub fn gdk_x11_window_get_xid(
-                            window: *mut glib::object::Object,
-                        ) -> *mut c_void;
-                    }
+            // Show the window
+            window.show_all();
+        }
 
-                    // Connect the video overlay to the correct window handle
-                    #[allow(clippy::cast_ptr_alignment)]
-                    unsafe {
-                        let xid = gdk_x11_window_get_xid(gdk_window.as_ptr() as *mut _);
-                        video_overlay.set_window_handle(xid as usize);
-                    }
-                } else {
-                    error!("Unsupported display type: {}.", display_type);
-                }
-            }
+        // Realize the video right away, even while the idle background is
+        // shown (so that the video sink always has somewhere to draw)
+        video_widget.realize();
 
-            // If on Mac OS
-            #[cfg(target_os = "macos")]
-            {
-                // Check if we're using Quartz
-                if display_type_name == "GdkQuartzDisplay" {
-                    extern "C" {
-                        pub fn gdk_quartz_window_get_nsview(
-                            window: *mut glib::object::GObject,
-                        ) -> *mut c_void;
-                    }
+        // Confirm that the video sink is embedded (so that the channel can start)
+        video_stream.ready.confirm();
+    }
 
-                    #[allow(clippy::cast_ptr_alignment)]
-                    unsafe {
-                        let window = gdk_quartz_window_get_nsview(gdk_window.as_ptr() as *mut _);
-                        video_overlay.set_window_handle(window as usize);
-                    }
-                } else {
-                    error!("Unsupported display type {}.", display_type);
-                }
-            }
-        });
+    /// A method to add the mixed video for every channel in a window. The
+    /// mixed video fills the window.
+    ///
+    pub fn add_compositor(
+        &mut self,
+        window_number: u32,
+        video_sink: gst::Element,
+        ready: VideoReady,
+    ) {
+        // Create a new video area (connected to a video overlay or provided by the sink)
+        let video_area = match video_sink.dynamic_cast::<gst_video::VideoOverlay>() {
+            Ok(video_overlay) => VideoWindow::new_overlay_area(video_overlay),
+            Err(video_sink) => match VideoWindow::sink_widget(&video_sink) {
+                Some(widget) => widget,
+                None => return,
+            },
+        };
+        video_area.set_widget_name(COMPOSITOR_NAME);
 
         // Check to see if there is already a matching window
         if let Some(overlay) = self.overlay_map.get(&window_number) {
-            // Add the video area to the overlay
+            // Replace any previous mixed video
+            VideoWindow::remove_named(overlay, COMPOSITOR_NAME);
             overlay.add_overlay(&video_area);
 
             // Show the video area
@@ -217,18 +370,73 @@ impl VideoWindow {
 
         // Otherwise, create a new window
         } else {
-            // Create the new window
-            let (window, overlay) = self.new_window(None);
-
-            // Add the video area to the overlay
+            // Create the new window and add the video area
+            let (window, overlay) = self.new_window(window_number, None);
             overlay.add_overlay(&video_area);
 
-            // Save the overlay in the overlay map
+            // Save the overlay and show the window
             self.overlay_map.insert(window_number, overlay);
-
-            // Show the window
             window.show_all();
         }
+
+        // Confirm that the video sink is embedded (so that the compositor can start)
+        video_area.realize();
+        ready.confirm();
+    }
+
+    /// A method to remove a video from the video window
+    ///
+    pub fn remove_video(&mut self, channel: u32) {
+        // Remove the channel from the channel map
+        if let Ok(mut map) = self.channel_map.try_borrow_mut() {
+            map.remove(&channel.to_string());
+        }
+
+        // Cancel any fade in progress and remove the idle background
+        if let Some(generation) = self.fade_map.remove(&channel) {
+            generation.set(generation.get() + 1);
+        }
+        self.idle_map.remove(&channel);
+
+        // Try to locate the correct window number
+        if let Some(window_number) = self.window_map.remove(&channel) {
+            // Try to get a copy of the overlay
+            if let Some(overlay) = self.overlay_map.get(&window_number) {
+                // Remove the video area from the overlay
+                VideoWindow::remove_named(overlay, &channel.to_string());
+            }
+        }
+    }
+
+    /// A method to change the idle background of a video within the window.
+    /// If no background is specified, the video is always shown (holding the
+    /// last frame of the media).
+    ///
+    pub fn change_idle_background(&mut self, channel: u32, background: Option<Background>) {
+        // Try to find the idle background
+        if let Some(idle) = self.idle_map.get(&channel) {
+            // Replace the background and redraw it
+            if let Ok(mut current) = idle.background.try_borrow_mut() {
+                *current = VideoWindow::load_background(background);
+            }
+            VideoWindow::show_idle(idle);
+            idle.stack.queue_draw();
+
+        // Otherwise, warn the user
+        } else {
+            error!("Unable to find current settings for channel {}.", channel);
+        }
+    }
+
+    /// A method to show the idle background of a video in place of the video
+    /// (when no media is playing) or to show the video again
+    ///
+    pub fn change_idle(&mut self, channel: u32, is_idle: bool) {
+        // Save the new setting and update the video area (ignoring removed channels)
+        if let Some(idle) = self.idle_map.get_mut(&channel) {
+            idle.is_idle = is_idle;
+            VideoWindow::show_idle(idle);
+        }
     }
 
     /// A method to resize  a video within the window
@@ -338,9 +546,492 @@ impl VideoWindow {
         }
     }
 
+    /// A method to change the stacking order of a video within the window
+    ///
+    pub fn change_layer(&mut self, channel_layer: ChannelLayer) {
+        // Try to find the overlay and video area
+        let Some((overlay, video_area)) = self.find_video_area(channel_layer.channel) else {
+            error!(
+                "Unable to find current settings for channel {}.",
+                channel_layer.channel
+            );
+            return;
+        };
+
+        // Count the videos in the overlay (ignoring the background)
+        let count = overlay.children().len().saturating_sub(1);
+
+        // Move the video area to the new position
+        let current = overlay.child_property::<i32>(&video_area, "index").max(0) as usize;
+        let position = channel_layer.layer.position(current, count);
+        overlay.reorder_overlay(&video_area, position as i32);
+    }
+
+    /// A method to change the opacity of a video within the window, with an
+    /// optional fade
+    ///
+    pub fn change_opacity(&mut self, channel_opacity: ChannelOpacity) {
+        // Try to find the video area
+        let Some((_, video_area)) = self.find_video_area(channel_opacity.channel) else {
+            error!(
+                "Unable to find current settings for channel {}.",
+                channel_opacity.channel
+            );
+            return;
+        };
+
+        // Claim a new generation for this change (cancelling any older fade)
+        let generation = self
+            .fade_map
+            .entry(channel_opacity.channel)
+            .or_default()
+            .clone();
+        generation.set(generation.get() + 1);
+        let current = generation.get();
+
+        // If no fade was specified, change the opacity immediately
+        let opacity = channel_opacity.opacity;
+        let Some(fade) = channel_opacity.fade else {
+            video_area.set_opacity(opacity);
+            return;
+        };
+
+        // Calculate the number of steps in the fade
+        let start = video_area.opacity();
+        let steps = (fade.duration / FADE_INTERVAL).max(1);
+        let mut step = 0;
+
+        // Step through the fade on the main thread
+        glib::timeout_add_local(Duration::from_millis(FADE_INTERVAL), move || {
+            // Stop if a newer change has started
+            if generation.get() != current {
+                return glib::ControlFlow::Break;
+            }
+
+            // Set the new opacity
+            step += 1;
+            let progress = step as f64 / steps as f64;
+            video_area.set_opacity(fade.curve.level(start, opacity, progress));
+
+            // Continue until the fade is complete
+            if step < steps {
+                glib::ControlFlow::Continue
+            } else {
+                glib::ControlFlow::Break
+            }
+        });
+    }
+
+    /// A method to hide or show a video within the window
+    ///
+    pub fn change_visibility(&mut self, channel_visibility: ChannelVisibility) {
+        // Try to find the video area
+        if let Some((_, video_area)) = self.find_video_area(channel_visibility.channel) {
+            video_area.set_visible(channel_visibility.visible);
+
+        // Otherwise, warn the user
+        } else {
+            error!(
+                "Unable to find current settings for channel {}.",
+                channel_visibility.channel
+            );
+        }
+    }
+
+    // A helper function to find the overlay and video area of a channel
+    //
+    fn find_video_area(&self, channel: u32) -> Option<(gtk::Overlay, gtk::Widget)> {
+        // Locate the overlay for the channel
+        let overlay = self.overlay_map.get(self.window_map.get(&channel)?)?;
+
+        // Look for the video area with the channel name (ignoring the background)
+        let background = overlay.child();
+        let name = channel.to_string();
+        overlay
+            .children()
+            .into_iter()
+            .find(|widget| {
+                Some(widget) != background.as_ref() && widget.widget_name().as_str() == name
+            })
+            .map(|widget| (overlay.clone(), widget))
+    }
+
+    // A helper function to show the idle background in place of the video, if
+    // there is a background and no media is playing
+    //
+    fn show_idle(idle: &ChannelIdle) {
+        let has_background = idle
+            .background
+            .try_borrow()
+            .map(|current| current.is_some())
+            .unwrap_or(false);
+        idle.stack
+            .set_visible_child_name(if idle.is_idle && has_background {
+                IDLE_NAME
+            } else {
+                VIDEO_NAME
+            });
+    }
+
+    // A helper function to apply a window definition to a window and its background
+    //
+    fn apply_definition(
+        window: &gtk::Window,
+        background: &gtk::DrawingArea,
+        shared: &SharedBackground,
+        definition: Option<WindowDefinition>,
+    ) {
+        // If there is a definition
+        if let Some(detail) = definition {
+            // Change the background and redraw the window
+            if let Ok(mut current) = shared.try_borrow_mut() {
+                *current = VideoWindow::load_background(detail.background.clone());
+            }
+            background.queue_draw();
+
+            // Find the monitor for the window, if specified
+            let monitor = detail.monitor.as_ref().and_then(VideoWindow::find_monitor);
+
+            // Move the window to the position (relative to the monitor) or to the monitor
+            let (left, top) = match &monitor {
+                Some((_, monitor)) => (monitor.geometry().x(), monitor.geometry().y()),
+                None => (0, 0),
+            };
+            let is_wayland = WidgetExt::display(window).type_().name() == "GdkWaylandDisplay";
+            match (detail.position, &monitor) {
+                // Wayland doesn't allow windows to move themselves, so warn the user
+                (Some(_), _) if is_wayland => error!(
+                    "Unable to move window {}: Windows can't be moved on Wayland.",
+                    detail.window_number
+                ),
+                (None, Some(_)) if is_wayland && !detail.fullscreen => error!(
+                    "Unable to move window {} to the monitor: Windows can only be placed on a monitor when fullscreen on Wayland.",
+                    detail.window_number
+                ),
+
+                // Otherwise, move the window
+                (Some((x, y)), _) => window.move_(left + x, top + y),
+                (None, Some(_)) => window.move_(left, top),
+                (None, None) => (),
+            }
+
+            // Change the fullscreen setting (on the monitor, if specified)
+            if detail.fullscreen {
+                match (&monitor, WidgetExt::screen(window)) {
+                    (Some((index, _)), Some(screen)) => {
+                        window.fullscreen_on_monitor(&screen, *index)
+                    }
+                    _ => window.fullscreen(),
+                }
+            } else {
+                window.unfullscreen();
+            }
+
+            // Set the minimum window dimensions, if specified
+            if let Some((height, width)) = detail.dimensions {
+                background.set_size_request(height, width);
+            } else {
+                background.set_size_request(-1, -1);
+            }
+
+        // Otherwise, default to fullscreen
+        } else {
+            window.fullscreen();
+        }
+    }
+
+    // A helper function to create a video area which is connected to the video
+    // overlay once the area is realized (for displays with native windows)
+    //
+    fn new_overlay_area(video_overlay: gst_video::VideoOverlay) -> gtk::Widget {
+        // Create a new video area
+        let video_area = gtk::DrawingArea::new();
+
+        // Draw a black background
+        video_area.connect_draw(|_, cr| {
+            // Draw the background black
+            cr.set_source_rgb(0.0, 0.0, 0.0);
+            cr.paint().unwrap_or(());
+            glib::Propagation::Stop
+        });
+
+        // Connect the realize signal for the video area
+        video_area.connect_realize(move |video_area| {
+            // Try to get a copy of the GDk window
+            let gdk_window = match video_area.window() {
+                Some(window) => window,
+                None => {
+                    error!("Unable to get current window for video overlay.");
+                    return;
+                }
+            };
+
+            // Check to make sure the window is native
+            if !gdk_window.ensure_native() {
+                error!("Widget is not located inside a native window.");
+                return;
+            }
+
+            // Extract the display type of the window
+            let display_type = gdk_window.display().type_().name();
+
+            // Switch based on the platform
+            #[cfg(target_os = "linux")]
+            {
+                // Check if we're using X11
+                if display_type == "GdkX11Display" {
+                    // Connect to the get_xid function
+                    unsafe extern "C" {
+                        pub fn gdk_x11_window_get_xid(
+                            window: *mut glib::object::Object,
+                        ) -> *mut c_void;
+                    }
+
+                    // Connect the video overlay to the correct window handle
+                    #[allow(clippy::cast_ptr_alignment)]
+                    unsafe {
+                        let xid = gdk_x11_window_get_xid(gdk_window.as_ptr() as *mut _);
+                        video_overlay.set_window_handle(xid as usize);
+                    }
+                } else {
+                    error!("Unsupported display type: {}.", display_type);
+                }
+            }
+
+            // If on Mac OS
+            #[cfg(target_os = "macos")]
+            {
+                // Check if we're using Quartz
+                if display_type_name == "GdkQuartzDisplay" {
+                    extern "C" {
+                        pub fn gdk_quartz_window_get_nsview(
+                            window: *mut glib::object::GObject,
+                        ) -> *mut c_void;
+                    }
+
+                    #[allow(clippy::cast_ptr_alignment)]
+                    unsafe {
+                        let window = gdk_quartz_window_get_nsview(gdk_window.as_ptr() as *mut _);
+                        video_overlay.set_window_handle(window as usize);
+                    }
+                } else {
+                    error!("Unsupported display type {}.", display_type);
+                }
+            }
+        });
+
+        // Return the video area
+        video_area.upcast()
+    }
+
+    // A helper function to get the widget which displays the video from a
+    // video sink (for displays without native windows, such as Wayland)
+    //
+    fn sink_widget(video_sink: &gst::Element) -> Option<gtk::Widget> {
+        // Check that the sink provides a widget
+        if video_sink.find_property("widget").is_none() {
+            error!("Video sink does not support an overlay or provide a widget.");
+            return None;
+        }
+
+        // Get the widget (created on the first request)
+        let widget = video_sink.property::<gtk::Widget>("widget");
+        widget.show();
+        Some(widget)
+    }
+
+    // A helper function to remove any video area with the name from the overlay
+    // (ignoring the background)
+    //
+    fn remove_named(overlay: &gtk::Overlay, name: &str) {
+        let background = overlay.child();
+        for widget in overlay.children() {
+            if Some(&widget) != background.as_ref() && widget.widget_name().as_str() == name {
+                overlay.remove(&widget);
+            }
+        }
+    }
+
+    // A helper function to find a monitor by index or connector name
+    //
+    fn find_monitor(target: &MonitorTarget) -> Option<(i32, gdk::Monitor)> {
+        // Get the default display
+        let display = gdk::Display::default()?;
+
+        // Look for the matching monitor
+        let monitor = match target {
+            MonitorTarget::Index(index) => display.monitor(*index).map(|monitor| (*index, monitor)),
+            MonitorTarget::Name(name) => {
+                // Find every monitor with a matching model (which is the
+                // connector name on X11)
+                let find_model = |model: &str| -> Vec<(i32, gdk::Monitor)> {
+                    (0..display.n_monitors())
+                        .filter_map(|index| display.monitor(index).map(|monitor| (index, monitor)))
+                        .filter(|(_, monitor)| {
+                            monitor.model().is_some_and(|other| other.as_str() == model)
+                        })
+                        .collect()
+                };
+                let mut matches = find_model(name);
+
+                // Otherwise, look up the model name of the monitor on the
+                // connector (for displays which report the model name instead,
+                // such as Wayland)
+                if matches.is_empty()
+                    && let Some(model) = VideoWindow::connector_model(name)
+                {
+                    matches = find_model(&model);
+                }
+
+                // Warn the user if the monitors can't be told apart
+                if matches.len() > 1 {
+                    error!(
+                        "Unable to tell monitors apart: {} monitors match {}. Select the monitor by index instead.",
+                        matches.len(),
+                        name
+                    );
+                }
+                matches.into_iter().next()
+            }
+        };
+
+        // Warn the user if the monitor wasn't found
+        if monitor.is_none() {
+            error!("Unable to find monitor {:?}.", target);
+        }
+        monitor
+    }
+
+    // A helper function to read the model name of the monitor on a connector
+    // (e.g. HDMI-1 or HDMI-A-1) from the kernel, if the monitor is connected
+    //
+    fn connector_model(name: &str) -> Option<String> {
+        // Look through the connectors of every graphics card (named like card0-HDMI-A-1)
+        for entry in fs::read_dir(DRM_DIRECTORY).ok()?.flatten() {
+            // Check the connector name, with or without the HDMI type
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some((card, connector)) = file_name.split_once('-') else {
+                continue;
+            };
+            if !card.starts_with("card")
+                || (connector != name && connector.replacen("HDMI-A-", "HDMI-", 1) != name)
+            {
+                continue;
+            }
+
+            // Read the model name from the monitor description
+            return fs::read(entry.path().join("edid"))
+                .ok()
+                .and_then(|edid| VideoWindow::edid_model(&edid));
+        }
+
+        // Otherwise, the connector wasn't found
+        None
+    }
+
+    // A helper function to extract the model name from a monitor description
+    // (the EDID), if the name is included
+    //
+    fn edid_model(edid: &[u8]) -> Option<String> {
+        // Check each of the four descriptors in the base block
+        (0..4).find_map(|descriptor| {
+            // Look for the display name descriptor
+            let start = 54 + descriptor * 18;
+            let bytes = edid.get(start..start + 18)?;
+            if bytes[0..3] != [0, 0, 0] || bytes[3] != 0xFC {
+                return None;
+            }
+
+            // Extract the name (which ends at a newline and is padded with spaces)
+            let name = bytes[5..].split(|byte| *byte == b'\n').next()?;
+            let name = String::from_utf8_lossy(name).trim().to_string();
+            (!name.is_empty()).then_some(name)
+        })
+    }
+
+    // A helper function to load a background so that it is ready to draw
+    //
+    fn load_background(background: Option<Background>) -> Option<LoadedBackground> {
+        match background? {
+            // Convert the color to the range for drawing
+            Background::Color {
+                red,
+                green,
+                blue,
+                alpha,
+            } => Some(LoadedBackground::Color(
+                red as f64 / 255.0,
+                green as f64 / 255.0,
+                blue as f64 / 255.0,
+                alpha as f64 / 255.0,
+            )),
+
+            // Try to load the image
+            Background::Image { path } => match Pixbuf::from_file(&path) {
+                Ok(pixbuf) => Some(LoadedBackground::Image(pixbuf)),
+                Err(error) => {
+                    error!("Unable to load background image {}: {}.", path, error);
+                    None
+                }
+            },
+        }
+    }
+
+    // A helper function to draw a window background, on top of black
+    //
+    fn draw_background(area: &gtk::DrawingArea, cr: &cairo::Context, shared: &SharedBackground) {
+        // Draw the background black
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        cr.paint().unwrap_or(());
+
+        // Draw the color or image, if specified
+        VideoWindow::paint_background(area, cr, shared);
+    }
+
+    // A helper function to draw a background over anything below it (so that
+    // any alpha in the background blends with the rest of the window)
+    //
+    fn paint_background(area: &gtk::DrawingArea, cr: &cairo::Context, shared: &SharedBackground) {
+        // Try to get the current background
+        let current = match shared.try_borrow() {
+            Ok(current) => current,
+            _ => return,
+        };
+
+        // Draw the color or image, if specified
+        match current.as_ref() {
+            // Fill the area with the color
+            Some(LoadedBackground::Color(red, green, blue, alpha)) => {
+                cr.set_source_rgba(*red, *green, *blue, *alpha);
+                cr.paint().unwrap_or(());
+            }
+
+            // Scale the image to fit in the center of the area
+            Some(LoadedBackground::Image(pixbuf)) => {
+                let width = area.allocated_width() as f64;
+                let height = area.allocated_height() as f64;
+                let scale = (width / pixbuf.width() as f64).min(height / pixbuf.height() as f64);
+                cr.translate(
+                    (width - pixbuf.width() as f64 * scale) / 2.0,
+                    (height - pixbuf.height() as f64 * scale) / 2.0,
+                );
+                cr.scale(scale, scale);
+                cr.set_source_pixbuf(pixbuf, 0.0, 0.0);
+                cr.paint().unwrap_or(());
+            }
+
+            // Otherwise, leave the area unchanged
+            None => (),
+        }
+    }
+
     // A helper method to create a new video window and return the window and overlay
     //
-    fn new_window(&self, definition: Option<WindowDefinition>) -> (gtk::Window, gtk::Overlay) {
+    fn new_window(
+        &mut self,
+        window_number: u32,
+        definition: Option<WindowDefinition>,
+    ) -> (gtk::Window, gtk::Overlay) {
         // Create the new window
         let window = gtk::Window::new(gtk::WindowType::Toplevel);
 
@@ -376,31 +1067,19 @@ impl VideoWindow {
             }
         });
 
-        // Create black background TODO allow other colors for the background
+        // Create the background (black, unless another background is specified)
+        let shared: SharedBackground = Rc::new(RefCell::new(None));
         let background = gtk::DrawingArea::new();
-        background.connect_draw(|_, cr| {
-            // Draw the background black
-            cr.set_source_rgb(0.0, 0.0, 0.0);
-            cr.paint().unwrap_or(());
+        let draw_shared = shared.clone();
+        background.connect_draw(move |area, cr| {
+            // Draw the current background
+            VideoWindow::draw_background(area, cr, &draw_shared);
             glib::Propagation::Stop
         });
 
-        // If there is a definition
-        if let Some(detail) = definition {
-            // And it is set to fullscreen, change the window setting
-            if detail.fullscreen {
-                window.fullscreen();
-            }
-
-            // Set the minimum window dimensions, if specified
-            if let Some((height, width)) = detail.dimensions {
-                background.set_size_request(height, width);
-            }
-
-        // Otherwise, default to fullscreen
-        } else {
-            window.fullscreen();
-        }
+        // Apply the window settings and save the background
+        VideoWindow::apply_definition(&window, &background, &shared, definition);
+        self.background_map.insert(window_number, shared);
 
         // Create the overlay and add the background
         let overlay = gtk::Overlay::new();
@@ -429,3 +1108,29 @@ impl VideoWindow {
         (window, overlay)
     }
 }
+
+// Tests of the video window module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test reading the model name from a monitor description
+    #[test]
+    fn edid_model() {
+        // Create a base block with a serial number and a display name
+        let mut edid = vec![0u8; 128];
+        edid[72..77].copy_from_slice(&[0, 0, 0, 0xFF, 0]);
+        edid[77..90].copy_from_slice(b"12345678\n    ");
+        edid[90..95].copy_from_slice(&[0, 0, 0, 0xFC, 0]);
+        edid[95..108].copy_from_slice(b"PROJECTOR\n   ");
+        assert_eq!(
+            VideoWindow::edid_model(&edid),
+            Some("PROJECTOR".to_string())
+        );
+
+        // A description without a name (or one that is too short) has no model
+        edid[93] = 0xFE;
+        assert_eq!(VideoWindow::edid_model(&edid), None);
+        assert_eq!(VideoWindow::edid_model(&edid[..60]), None);
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to replace the user interface when running without a display.
+//! This module runs the main loop for the media playback and discards any
+//! updates for the video windows.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import standard library features
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Import GLib library
+use glib;
+
+// Define user interface constants
+const REFRESH_RATE: u64 = 10; // the update check rate in milliseconds
+
+/// A structure to stand in for the user interface when there is no display
+///
+pub struct HeadlessInterface;
+
+// Implement key HeadlessInterface functionality
+impl HeadlessInterface {
+    /// A function to run the main loop until the program is closed. This
+    /// function blocks until an update to close is received.
+    ///
+    pub fn run(interface_receive: mpsc::Receiver<InterfaceUpdate>) {
+        // Create the main loop (required for the media playback signals)
+        let main_loop = glib::MainLoop::new(None, false);
+
+        // Check for updates periodically
+        let loop_clone = main_loop.clone();
+        glib::timeout_add_local(Duration::from_millis(REFRESH_RATE), move || {
+            // Look for any updates
+            while let Ok(update) = interface_receive.try_recv() {
+                // Quit when the program closes, and ignore all other updates
+                if let InterfaceUpdate::Close = update {
+                    loop_clone.quit();
+                    return glib::ControlFlow::Break;
+                }
+            }
+
+            // Continue looking for updates
+            glib::ControlFlow::Continue
+        });
+
+        // Run the main loop until closed
+        main_loop.run();
+    }
+}
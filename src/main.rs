@@ -27,7 +27,10 @@ extern crate serde;
 
 // Define program modules
 mod definitions;
+#[cfg(feature = "display")]
 mod gtk_interface;
+mod headless_interface;
+mod osc_interface;
 mod system_interface;
 mod web_interface;
 
@@ -35,12 +38,15 @@ mod web_interface;
 use crate::definitions::*;
 
 // Import other structures into this module
+#[cfg(feature = "display")]
 use self::gtk_interface::GtkInterface;
+use self::headless_interface::HeadlessInterface;
+use self::osc_interface::OscInterface;
 use self::system_interface::SystemInterface;
 use self::web_interface::WebInterface;
 
 // Import standard library features
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 
 // Import tracing features
@@ -65,20 +71,54 @@ struct Apollo;
 impl Apollo {
     /// A function to build the main program and the user interface
     ///
-    fn build_program(
-        application: &gtk::Application,
-        address: Arc<Mutex<String>>,
-        server_location: Arc<Mutex<Option<String>>>,
-    ) {
+    #[cfg(feature = "display")]
+    fn build_program(application: &gtk::Application, settings: UserSettings) {
+        // Launch the background thread
+        let (gtk_interface_recv, monitors) =
+            Apollo::launch_system(settings, GtkInterface::video_output());
+
+        // Create the gtk interface structure to handle video and media playback
+        GtkInterface::spawn_interface(application, gtk_interface_recv, monitors);
+    }
+
+    /// A function to build the main program without a display and run the
+    /// program until it is closed
+    ///
+    fn run_headless(settings: UserSettings) {
+        // Launch the background thread (there are no monitors without a display)
+        let (interface_recv, _) = Apollo::launch_system(settings, VideoOutput::Headless);
+
+        // Run the stand-in interface until the program is closed
+        HeadlessInterface::run(interface_recv);
+    }
+
+    /// A function to launch the system, web, and OSC interfaces on a
+    /// background thread and return the line for interface updates (and the
+    /// shared list of monitors for the interface to keep up to date)
+    ///
+    fn launch_system(
+        mut settings: UserSettings,
+        video_output: VideoOutput,
+    ) -> (mpsc::Receiver<InterfaceUpdate>, Arc<Mutex<MonitorList>>) {
         // Create the tokio runtime
         let runtime = Runtime::new().expect("Unable To Create Tokio Runtime.");
 
-        // Create the interface send
+        // Create the interface send and share the list of monitors
         let (interface_send, gtk_interface_recv) = InterfaceSend::new();
+        let monitors = interface_send.monitor_share();
+
+        // Create the event send
+        let event_send = EventSend::new();
 
         // Launch the system interface to monitor and handle events
         let (system_interface, web_send) = match runtime.block_on(async {
-            SystemInterface::new(interface_send.clone(), address.clone(), server_location).await
+            SystemInterface::new(
+                interface_send.clone(),
+                event_send.clone(),
+                &settings,
+                video_output,
+            )
+            .await
         }) {
             Ok(result) => result,
             Err(error) => {
@@ -90,8 +130,14 @@ impl Apollo {
             }
         };
 
+        // Add the API keys from the show file to those from the command line
+        settings.api_keys.extend(system_interface.api_keys());
+
         // Create a new web interface
-        let mut web_interface = WebInterface::new(web_send, address);
+        let mut web_interface = WebInterface::new(web_send.clone(), event_send, &settings);
+
+        // Create a new OSC interface
+        let mut osc_interface = OscInterface::new(web_send, &settings);
 
         // Spin the runtime into a native thread
         thread::spawn(move || {
@@ -100,14 +146,19 @@ impl Apollo {
                 system_interface.run().await;
             });
 
+            // Run the OSC interface in a new thread (if specified)
+            runtime.spawn(async move {
+                osc_interface.run().await;
+            });
+
             // Block on the web interface
             runtime.block_on(async move {
                 web_interface.run().await;
             });
         });
 
-        // Create the gtk interface structure to handle video and media playback
-        GtkInterface::spawn_interface(application, gtk_interface_recv);
+        // Return the line for interface updates and the list of monitors
+        (gtk_interface_recv, monitors)
     }
 }
 
@@ -116,21 +167,48 @@ impl Apollo {
 ///
 fn main() {
     // Create the gtk application window. Failure results in immediate panic!
+    #[cfg(feature = "display")]
     let application = gtk::Application::new(None, gio::ApplicationFlags::empty());
 
-    // Create the default address and backup server location
-    let address = Arc::new(Mutex::new(String::from(DEFAULT_ADDRESS)));
-    let server_location = Arc::new(Mutex::new(None));
+    // Without a display, create a plain application to parse the options
+    #[cfg(not(feature = "display"))]
+    let application = gio::Application::new(None, gio::ApplicationFlags::empty());
+
+    // Create the default settings
+    let settings = Arc::new(Mutex::new(UserSettings::default()));
 
     // Register command line options
-    let addr_clone = address.clone();
-    let server_clone = server_location.clone();
+    let settings_clone = settings.clone();
     application.add_main_option(
         "address",
         glib::Char::from(b'a'),
         glib::OptionFlags::NONE,
+        glib::OptionArg::StringArray,
+        "Optional listening address for the webserver, default is 127.0.0.1:27655. May be repeated to listen on several addresses.",
+        None,
+    );
+    application.add_main_option(
+        "tlsAddress",
+        glib::Char::from(b't'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::StringArray,
+        "Optional listening address for encrypted (HTTPS) connections to the webserver. May be repeated. Requires 'tlsCert' and 'tlsKey'. Without 'address', the webserver listens only for encrypted connections.",
+        None,
+    );
+    application.add_main_option(
+        "tlsCert",
+        glib::Char::from(b'e'),
+        glib::OptionFlags::NONE,
         glib::OptionArg::String,
-        "Optional listening address for the webserver, default is 127.0.0.1:27655",
+        "Optional location of the TLS certificate (in PEM format) for encrypted connections.",
+        None,
+    );
+    application.add_main_option(
+        "tlsKey",
+        glib::Char::from(b'y'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional location of the TLS private key (in PEM format) for encrypted connections.",
         None,
     );
     application.add_main_option(
@@ -141,6 +219,102 @@ fn main() {
         "Optional backup server location. If none specified, no live backup performed.",
         None,
     );
+    application.add_main_option(
+        "show",
+        glib::Char::from(b's'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional show file (YAML or JSON) to load at startup. A backup, if found, takes priority for windows, channels, and media.",
+        None,
+    );
+    application.add_main_option(
+        "showDirectory",
+        glib::Char::from(b'd'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional directory for saving show files. Defaults to the directory of the show file. If neither is specified, shows cannot be saved.",
+        None,
+    );
+    application.add_main_option(
+        "standby",
+        glib::Char::from(b'S'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional listening address of a primary instance to follow on standby. Requires the same backup location as the primary.",
+        None,
+    );
+    application.add_main_option(
+        "clockServer",
+        glib::Char::from(b'c'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional address (ip:port) to publish the media clock for other instances to follow.",
+        None,
+    );
+    application.add_main_option(
+        "clockClient",
+        glib::Char::from(b'C'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional address (ip:port) of a published media clock to follow.",
+        None,
+    );
+    application.add_main_option(
+        "operatorKey",
+        glib::Char::from(b'k'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::StringArray,
+        "Optional API key which grants full control of the web interface. May be repeated. If no keys are specified, access is unrestricted.",
+        None,
+    );
+    application.add_main_option(
+        "observerKey",
+        glib::Char::from(b'K'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::StringArray,
+        "Optional API key which grants read-only access to the status and events of the web interface. May be repeated.",
+        None,
+    );
+    application.add_main_option(
+        "osc",
+        glib::Char::from(b'o'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional listening address for Open Sound Control messages. If none specified, OSC is disabled.",
+        None,
+    );
+    application.add_main_option(
+        "oscReply",
+        glib::Char::from(b'r'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Optional address to send replies to Open Sound Control messages.",
+        None,
+    );
+    application.add_main_option(
+        "oscAllow",
+        glib::Char::from(b'O'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::StringArray,
+        "Optional IP address of a sender allowed to send Open Sound Control messages. May be repeated. If API keys are specified, OSC requires 'oscAllow' or 'oscUnrestricted'.",
+        None,
+    );
+    application.add_main_option(
+        "oscUnrestricted",
+        glib::Char::from(b'U'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Optional flag to accept Open Sound Control messages from any sender, even if API keys are specified.",
+        None,
+    );
+    application.add_main_option(
+        "headless",
+        glib::Char::from(b'H'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Optional flag to run without a display. Video is discarded and only audio is played.",
+        None,
+    );
     application.add_main_option(
         "logLevel",
         glib::Char::from(b'l'),
@@ -159,15 +333,100 @@ fn main() {
                 .lookup_value("address", None)
                 .expect("Invalid parameter for option 'address'.");
 
-            // Try to convert it to a string
-            let new_address: String = variant
+            // Try to convert it to a list of strings
+            let mut new_addresses: Vec<String> = variant
                 .get()
                 .expect("Invalid parameter for option 'address'.");
 
             // Get a lock on the address
-            if let Ok(mut lock) = addr_clone.try_lock() {
-                // Save the new address (may still be an invalid string)
-                *lock = new_address;
+            if !new_addresses.is_empty()
+                && let Ok(mut lock) = settings_clone.try_lock()
+            {
+                // Save the first address as the main address (may still be an invalid string)
+                lock.address = new_addresses.remove(0);
+            }
+
+            // Get a lock on the other addresses
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the other addresses
+                lock.listen.addresses = new_addresses;
+            }
+        }
+
+        // Check to see if any encrypted addresses were specified
+        if dict.contains("tlsAddress") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("tlsAddress", None)
+                .expect("Invalid parameter for option 'tlsAddress'.");
+
+            // Try to convert it to a list of strings
+            let new_addresses: Vec<String> = variant
+                .get()
+                .expect("Invalid parameter for option 'tlsAddress'.");
+
+            // Make sure the certificate and key are also specified
+            if !dict.contains("tlsCert") || !dict.contains("tlsKey") {
+                panic!("Unable to parse options: 'tlsAddress' requires 'tlsCert' and 'tlsKey'.");
+            }
+
+            // If no plaintext address was specified, listen only for encrypted connections
+            if !dict.contains("address") {
+                // Use the first encrypted address as the main address
+                if let Some(first) = new_addresses.first()
+                    && let Ok(mut lock) = settings_clone.try_lock()
+                {
+                    lock.address = first.clone();
+                }
+
+                // Don't listen for plaintext connections on the main address
+                if let Ok(mut lock) = settings_clone.try_lock() {
+                    lock.listen.is_encrypted_only = true;
+                }
+            }
+
+            // Get a lock on the listen settings
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new addresses (may still be invalid strings)
+                lock.listen.tls_addresses = new_addresses;
+            }
+        }
+
+        // Check to see if the TLS certificate was specified
+        if dict.contains("tlsCert") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("tlsCert", None)
+                .expect("Invalid parameter for option 'tlsCert'.");
+
+            // Try to convert it to a string
+            let new_cert: String = variant
+                .get()
+                .expect("Invalid parameter for option 'tlsCert'.");
+
+            // Get a lock on the listen settings
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new location (may still be an invalid path)
+                lock.listen.tls_cert = Some(new_cert);
+            }
+        }
+
+        // Check to see if the TLS private key was specified
+        if dict.contains("tlsKey") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("tlsKey", None)
+                .expect("Invalid parameter for option 'tlsKey'.");
+
+            // Try to convert it to a string
+            let new_key: String = variant
+                .get()
+                .expect("Invalid parameter for option 'tlsKey'.");
+
+            // Get a lock on the listen settings
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new location (may still be an invalid path)
+                lock.listen.tls_key = Some(new_key);
             }
         }
 
@@ -184,9 +443,218 @@ fn main() {
                 .expect("Invalid parameter for option 'backup'.");
 
             // Get a lock on the backup
-            if let Ok(mut lock) = server_clone.try_lock() {
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new address (may still be an invalid string)
+                lock.server_location = Some(new_backup);
+            }
+        }
+
+        // Check to see if the show file was specified
+        if dict.contains("show") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("show", None)
+                .expect("Invalid parameter for option 'show'.");
+
+            // Try to convert it to a string
+            let new_show: String = variant.get().expect("Invalid parameter for option 'show'.");
+
+            // Get a lock on the show file
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new location (may still be an invalid path)
+                lock.show_file = Some(new_show);
+            }
+        }
+
+        // Check to see if the show directory was specified
+        if dict.contains("showDirectory") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("showDirectory", None)
+                .expect("Invalid parameter for option 'showDirectory'.");
+
+            // Try to convert it to a string
+            let new_directory: String = variant
+                .get()
+                .expect("Invalid parameter for option 'showDirectory'.");
+
+            // Get a lock on the show directory
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new location (may still be an invalid path)
+                lock.show_directory = Some(new_directory);
+            }
+        }
+
+        // Check to see if a primary instance was specified
+        if dict.contains("standby") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("standby", None)
+                .expect("Invalid parameter for option 'standby'.");
+
+            // Try to convert it to a string
+            let new_primary: String = variant
+                .get()
+                .expect("Invalid parameter for option 'standby'.");
+
+            // Get a lock on the primary address
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new address (may still be an invalid string)
+                lock.standby = Some(new_primary);
+            }
+        }
+
+        // Check to see if the clock should be published
+        if dict.contains("clockServer") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("clockServer", None)
+                .expect("Invalid parameter for option 'clockServer'.");
+
+            // Try to convert it to a string
+            let address: String = variant
+                .get()
+                .expect("Invalid parameter for option 'clockServer'.");
+
+            // Get a lock on the clock source
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new source (may still be an invalid address)
+                lock.clock = Some(ClockSource::Publish { address });
+            }
+        }
+
+        // Check to see if a published clock should be followed
+        if dict.contains("clockClient") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("clockClient", None)
+                .expect("Invalid parameter for option 'clockClient'.");
+
+            // Try to convert it to a string
+            let address: String = variant
+                .get()
+                .expect("Invalid parameter for option 'clockClient'.");
+
+            // Make sure the clock isn't also published
+            if dict.contains("clockServer") {
+                panic!("Unable to parse options: Specify either 'clockServer' or 'clockClient'.");
+            }
+
+            // Get a lock on the clock source
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new source (may still be an invalid address)
+                lock.clock = Some(ClockSource::Follow { address });
+            }
+        }
+
+        // Check to see if any operator keys were specified
+        if dict.contains("operatorKey") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("operatorKey", None)
+                .expect("Invalid parameter for option 'operatorKey'.");
+
+            // Try to convert it to a list of strings
+            let keys: Vec<String> = variant
+                .get()
+                .expect("Invalid parameter for option 'operatorKey'.");
+
+            // Get a lock on the API keys
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new keys
+                lock.api_keys.operator.extend(keys);
+            }
+        }
+
+        // Check to see if any observer keys were specified
+        if dict.contains("observerKey") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("observerKey", None)
+                .expect("Invalid parameter for option 'observerKey'.");
+
+            // Try to convert it to a list of strings
+            let keys: Vec<String> = variant
+                .get()
+                .expect("Invalid parameter for option 'observerKey'.");
+
+            // Get a lock on the API keys
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new keys
+                lock.api_keys.observer.extend(keys);
+            }
+        }
+
+        // Check to see if the OSC address was specified
+        if dict.contains("osc") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("osc", None)
+                .expect("Invalid parameter for option 'osc'.");
+
+            // Try to convert it to a string
+            let new_osc: String = variant.get().expect("Invalid parameter for option 'osc'.");
+
+            // Get a lock on the OSC address
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new address (may still be an invalid string)
+                lock.osc_address = Some(new_osc);
+            }
+        }
+
+        // Check to see if the OSC reply address was specified
+        if dict.contains("oscReply") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("oscReply", None)
+                .expect("Invalid parameter for option 'oscReply'.");
+
+            // Try to convert it to a string
+            let new_reply: String = variant
+                .get()
+                .expect("Invalid parameter for option 'oscReply'.");
+
+            // Get a lock on the OSC reply address
+            if let Ok(mut lock) = settings_clone.try_lock() {
                 // Save the new address (may still be an invalid string)
-                *lock = Some(new_backup);
+                lock.osc_reply_address = Some(new_reply);
+            }
+        }
+
+        // Check to see if any OSC senders were specified
+        if dict.contains("oscAllow") {
+            // Try to get the value
+            let variant = dict
+                .lookup_value("oscAllow", None)
+                .expect("Invalid parameter for option 'oscAllow'.");
+
+            // Try to convert it to a list of strings
+            let senders: Vec<String> = variant
+                .get()
+                .expect("Invalid parameter for option 'oscAllow'.");
+
+            // Get a lock on the OSC senders
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new senders (may still be invalid strings)
+                lock.osc_allow.extend(senders);
+            }
+        }
+
+        // Check to see if unrestricted OSC was specified
+        if dict.contains("oscUnrestricted") {
+            // Get a lock on the OSC setting
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new setting
+                lock.is_osc_unrestricted = true;
+            }
+        }
+
+        // Check to see if headless mode was specified
+        if dict.contains("headless") {
+            // Get a lock on the headless setting
+            if let Ok(mut lock) = settings_clone.try_lock() {
+                // Save the new setting
+                lock.is_headless = true;
             }
         }
 
@@ -227,13 +695,31 @@ fn main() {
                 .init();
         }
 
+        // If running without a display, run the program without the application
+        let settings = settings_clone
+            .lock()
+            .expect("Unable to read the program options.")
+            .clone();
+        if settings.is_headless {
+            // Run the program until it is closed, then exit
+            Apollo::run_headless(settings);
+            return 0;
+        }
+
         // Don't continue the application
         return -1;
     });
 
     // Create the program and launch the background thread
+    #[cfg(feature = "display")]
     application.connect_startup(move |gtk_app| {
-        Apollo::build_program(gtk_app, address.clone(), server_location.clone());
+        Apollo::build_program(
+            gtk_app,
+            settings
+                .lock()
+                .expect("Unable to read the program options.")
+                .clone(),
+        );
     });
 
     // Connect the activate-specific function (as compared with open-specific function)
@@ -0,0 +1,525 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to create the Open Sound Control (OSC) interface to connect
+//! lighting and sound consoles to the program. Each OSC message is converted
+//! to the matching request and handled exactly like a web request.
+//!
+//! OSC messages can't carry an API key. If API keys are required, the OSC
+//! interface only starts with a list of allowed senders (or if any sender is
+//! explicitly allowed).
+
+// Import crate definitions
+use crate::definitions::*;
+
+//  Import standard library features
+use std::net::{IpAddr, SocketAddr};
+
+// Import Tokio features
+use tokio::net::UdpSocket;
+use tokio::sync::oneshot;
+
+// Import OSC features
+use rosc::{OscMessage, OscPacket, OscType, decoder, encoder};
+
+// Import tracing features
+use tracing::{error, warn};
+
+// Import anyhow features
+use anyhow::Result;
+
+// Define module constants
+const ADDRESS_PREFIX: &str = "apollo"; // the first element of every apollo address
+const REPLY_ADDRESS: &str = "/apollo/reply"; // the address for replies to the feedback location
+
+/// A structure to contain the OSC interface and forward all messages to the
+/// system interface.
+///
+pub struct OscInterface {
+    web_send: WebSend,                    // send line to the system interface
+    address: Option<String>,              // user-defined listening address
+    reply_address: Option<String>,        // user-defined feedback address
+    senders: Result<Option<Vec<IpAddr>>>, // the allowed senders (any sender if none), or the reason to refuse
+}
+
+// Implement key OSC Interface functionality
+impl OscInterface {
+    /// A function to create a new OSC interface. The send channel should
+    /// connect directly to the system interface.
+    ///
+    pub fn new(web_send: WebSend, settings: &UserSettings) -> Self {
+        // Return the new OSC interface
+        OscInterface {
+            web_send,
+            address: settings.osc_address.clone(),
+            reply_address: settings.osc_reply_address.clone(),
+            senders: OscInterface::check_senders(settings),
+        }
+    }
+
+    /// A method to listen for OSC messages. This method returns immediately
+    /// if no listening address was specified.
+    ///
+    pub async fn run(&mut self) {
+        // Quit if no address was specified
+        let Some(address) = self.address.clone() else {
+            return;
+        };
+
+        // Refuse to listen if the allowed senders are missing or invalid
+        let senders = match self.senders.as_ref() {
+            Ok(senders) => senders.clone(),
+            Err(error) => {
+                error!("Unable to start the OSC interface: {}", error);
+                return;
+            }
+        };
+
+        // Try to parse the user defined reply address
+        let mut reply_address = None;
+        if let Some(address) = self.reply_address.as_ref() {
+            match address.parse::<SocketAddr>() {
+                Ok(address) => reply_address = Some(address),
+                Err(_) => error!("Unable to parse OSC reply address: {}.", address),
+            }
+        }
+
+        // Try to listen at the requested address
+        let socket = match UdpSocket::bind(&address).await {
+            Ok(socket) => socket,
+            Err(error) => {
+                error!("Unable to listen for OSC at {}: {}.", address, error);
+                return;
+            }
+        };
+
+        // Listen for messages indefinitely
+        let mut buffer = [0u8; decoder::MTU];
+        loop {
+            // Wait for the next message
+            let size = match socket.recv_from(&mut buffer).await {
+                Ok((size, sender)) => {
+                    // Ignore any message from a sender which is not allowed
+                    if let Some(senders) = senders.as_ref()
+                        && !senders.contains(&sender.ip())
+                    {
+                        warn!("Ignored OSC message from {}.", sender);
+                        continue;
+                    }
+                    size
+                }
+                Err(error) => {
+                    warn!("Unable to receive OSC message: {}.", error);
+                    continue;
+                }
+            };
+
+            // Try to decode the message
+            let packet = match decoder::decode_udp(&buffer[..size]) {
+                Ok((_, packet)) => packet,
+                Err(error) => {
+                    warn!("Unable to decode OSC message: {:?}.", error);
+                    continue;
+                }
+            };
+
+            // Handle every message in the packet, in order
+            for message in OscInterface::unpack_packet(packet) {
+                // Try to convert the message to a request
+                let reply = match OscInterface::parse_message(&message) {
+                    // Forward the request and wait for the reply
+                    Ok(request) => self.handle_request(request).await,
+
+                    // Otherwise, note the error
+                    Err(error) => {
+                        warn!("{}", error);
+                        WebReply::failure(format!("{}", error))
+                    }
+                };
+
+                // If a reply address was specified, send the reply
+                if let Some(reply_address) = reply_address {
+                    OscInterface::send_reply(&socket, reply_address, &message.addr, reply).await;
+                }
+            }
+        }
+    }
+
+    /// A method to forward a request to the system interface and wait for the reply
+    ///
+    async fn handle_request(&self, request: Request) -> WebReply {
+        // Send the message and wait for the reply
+        let (reply_to, rx) = oneshot::channel();
+        self.web_send.send(reply_to, request).await;
+
+        // Return the reply or note the error
+        rx.await
+            .unwrap_or(WebReply::failure("Unable to process request."))
+    }
+
+    /// A helper function to check which senders are allowed to send messages.
+    /// Returns None if any sender is allowed, or an error if API keys are
+    /// required without a list of allowed senders or the unrestricted flag.
+    ///
+    fn check_senders(settings: &UserSettings) -> Result<Option<Vec<IpAddr>>> {
+        // Parse every allowed sender
+        let senders = settings
+            .osc_allow
+            .iter()
+            .map(|sender| {
+                sender
+                    .parse::<IpAddr>()
+                    .map_err(|_| anyhow!("Invalid OSC sender address: {}.", sender))
+            })
+            .collect::<Result<Vec<IpAddr>>>()?;
+
+        // Restrict the senders, if specified
+        if !senders.is_empty() {
+            return Ok(Some(senders));
+        }
+
+        // Refuse any sender if API keys are required (unless explicitly allowed)
+        if !settings.api_keys.is_empty() && !settings.is_osc_unrestricted {
+            return Err(anyhow!(
+                "OSC can't check API keys. Specify the allowed senders or allow any sender."
+            ));
+        }
+
+        // Otherwise, allow any sender
+        Ok(None)
+    }
+
+    /// A helper function to send a reply to the feedback address. The reply
+    /// contains the original address, the result of the request, and the
+    /// complete reply as a JSON string.
+    ///
+    async fn send_reply(
+        socket: &UdpSocket,
+        reply_address: SocketAddr,
+        original_address: &str,
+        reply: WebReply,
+    ) {
+        // Try to serialize the reply
+        let reply_string = match serde_json::to_string(&reply) {
+            Ok(string) => string,
+            Err(error) => {
+                warn!("Unable to parse OSC reply: {}.", error);
+                return;
+            }
+        };
+
+        // Compose the reply message
+        let packet = OscPacket::Message(OscMessage {
+            addr: REPLY_ADDRESS.to_string(),
+            args: vec![
+                OscType::String(original_address.to_string()),
+                OscType::Bool(reply.is_success()),
+                OscType::String(reply_string),
+            ],
+        });
+
+        // Try to encode and send the reply
+        match encoder::encode(&packet) {
+            Ok(bytes) => {
+                if let Err(error) = socket.send_to(&bytes, reply_address).await {
+                    warn!("Unable to send OSC reply: {}.", error);
+                }
+            }
+            Err(error) => warn!("Unable to encode OSC reply: {:?}.", error),
+        }
+    }
+
+    /// A helper function to unpack all the messages from a packet, in order
+    ///
+    fn unpack_packet(packet: OscPacket) -> Vec<OscMessage> {
+        match packet {
+            // Return a single message
+            OscPacket::Message(message) => vec![message],
+
+            // Unpack every packet in the bundle
+            OscPacket::Bundle(bundle) => bundle
+                .content
+                .into_iter()
+                .flat_map(|packet| OscInterface::unpack_packet(packet))
+                .collect(),
+        }
+    }
+
+    /// A helper function to convert an OSC message into a request
+    ///
+    /// # Note
+    ///
+    /// The supported addresses are
+    /// * /apollo/cue/{channel} uri [loopUri]
+    /// * /apollo/preload/{channel} uri [loopUri]
+    /// * /apollo/state/{channel} play|pause
+    /// * /apollo/seek/{channel} milliseconds
+    /// * /apollo/volume/{channel} level [fadeMilliseconds]
+    /// * /apollo/opacity/{channel} level [fadeMilliseconds]
+    /// * /apollo/allStop [fadeMilliseconds]
+    /// * /apollo/go
+    /// * /apollo/back
+    /// * /apollo/goTo cueNumber
+    /// * /apollo/preset name
+    ///
+    fn parse_message(message: &OscMessage) -> Result<Request> {
+        // Split the address into its parts
+        let parts: Vec<&str> = message.addr.trim_start_matches('/').split('/').collect();
+
+        // Check the prefix and match the command
+        match parts.as_slice() {
+            // Cue new media
+            [ADDRESS_PREFIX, "cue", channel] => Ok(Request::CueMedia {
+                media_cue: MediaCue {
+                    uri: OscInterface::string_argument(message, 0)?,
+                    channel: OscInterface::parse_channel(channel)?,
+                    loop_media: OscInterface::string_argument(message, 1).ok(),
+                    fade_in: None,
+                    fade_out: None,
+                    transition: None,
+                    start_at: None,
+                },
+            }),
+
+            // Preload new media
+            [ADDRESS_PREFIX, "preload", channel] => Ok(Request::PreloadMedia {
+                media_cue: MediaCue {
+                    uri: OscInterface::string_argument(message, 0)?,
+                    channel: OscInterface::parse_channel(channel)?,
+                    loop_media: OscInterface::string_argument(message, 1).ok(),
+                    fade_in: None,
+                    fade_out: None,
+                    transition: None,
+                    start_at: None,
+                },
+            }),
+
+            // Change the playback state
+            [ADDRESS_PREFIX, "state", channel] => {
+                // Convert the state
+                let state = match OscInterface::string_argument(message, 0)?.as_str() {
+                    "play" => PlaybackState::Playing,
+                    "pause" => PlaybackState::Paused,
+                    other => return Err(anyhow!("Invalid OSC playback state: {}.", other)),
+                };
+
+                // Return the request
+                Ok(Request::ChangeState {
+                    channel_state: ChannelState {
+                        channel: OscInterface::parse_channel(channel)?,
+                        state,
+                    },
+                })
+            }
+
+            // Seek within the media
+            [ADDRESS_PREFIX, "seek", channel] => Ok(Request::Seek {
+                channel_seek: ChannelSeek {
+                    channel: OscInterface::parse_channel(channel)?,
+                    position: OscInterface::number_argument(message, 0)?,
+                },
+            }),
+
+            // Change the volume
+            [ADDRESS_PREFIX, "volume", channel] => Ok(Request::ChangeVolume {
+                channel_volume: ChannelVolume {
+                    channel: OscInterface::parse_channel(channel)?,
+                    volume: OscInterface::level_argument(message, 0)?,
+                    mute: None, // keep the current mute setting
+                    fade: OscInterface::fade_argument(message, 1),
+                },
+            }),
+
+            // Change the opacity
+            [ADDRESS_PREFIX, "opacity", channel] => Ok(Request::ChangeOpacity {
+                channel_opacity: ChannelOpacity {
+                    channel: OscInterface::parse_channel(channel)?,
+                    opacity: OscInterface::level_argument(message, 0)?,
+                    fade: OscInterface::fade_argument(message, 1),
+                },
+            }),
+
+            // Stop all the media
+            [ADDRESS_PREFIX, "allStop"] => Ok(Request::AllStop {
+                fade_out: OscInterface::fade_argument(message, 0),
+            }),
+
+            // Play the standby cue
+            [ADDRESS_PREFIX, "go"] => Ok(Request::Go),
+
+            // Play the previous cue
+            [ADDRESS_PREFIX, "back"] => Ok(Request::Back),
+
+            // Play a specific cue
+            [ADDRESS_PREFIX, "goTo"] => Ok(Request::GoTo {
+                cue: OscInterface::number_argument(message, 0)? as usize,
+            }),
+
+            // Recall a preset from the show file
+            [ADDRESS_PREFIX, "preset"] => Ok(Request::RecallPreset {
+                name: OscInterface::string_argument(message, 0)?,
+            }),
+
+            // Otherwise, note the error
+            _ => Err(anyhow!("Unknown OSC address: {}.", message.addr)),
+        }
+    }
+
+    // A helper function to parse the channel number from an address
+    fn parse_channel(channel: &str) -> Result<u32> {
+        channel
+            .parse::<u32>()
+            .map_err(|_| anyhow!("Invalid OSC channel number: {}.", channel))
+    }
+
+    // A helper function to extract a string argument from a message
+    fn string_argument(message: &OscMessage, index: usize) -> Result<String> {
+        match message.args.get(index) {
+            Some(OscType::String(string)) => Ok(string.clone()),
+            _ => Err(anyhow!("Missing OSC string argument for {}.", message.addr)),
+        }
+    }
+
+    // A helper function to extract a volume (or opacity) level argument from a message
+    fn level_argument(message: &OscMessage, index: usize) -> Result<f64> {
+        match message.args.get(index) {
+            Some(OscType::Float(level)) => Ok(*level as f64),
+            Some(OscType::Double(level)) => Ok(*level),
+            Some(OscType::Int(level)) => Ok(*level as f64),
+            _ => Err(anyhow!("Missing OSC level argument for {}.", message.addr)),
+        }
+    }
+
+    // A helper function to extract an optional fade argument from a message
+    fn fade_argument(message: &OscMessage, index: usize) -> Option<AudioFade> {
+        OscInterface::number_argument(message, index)
+            .ok()
+            .map(|duration| AudioFade {
+                duration,
+                curve: FadeCurve::default(),
+            })
+    }
+
+    // A helper function to extract a positive number argument from a message
+    fn number_argument(message: &OscMessage, index: usize) -> Result<u64> {
+        match message.args.get(index) {
+            Some(OscType::Int(number)) if *number >= 0 => Ok(*number as u64),
+            Some(OscType::Long(number)) if *number >= 0 => Ok(*number as u64),
+            Some(OscType::Float(number)) if *number >= 0.0 => Ok(*number as u64),
+            Some(OscType::Double(number)) if *number >= 0.0 => Ok(*number as u64),
+            Some(OscType::String(string)) => string
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Invalid OSC number argument for {}.", message.addr)),
+            _ => Err(anyhow!("Missing OSC number argument for {}.", message.addr)),
+        }
+    }
+}
+
+// Tests of the OSC module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the conversion of OSC messages
+    #[test]
+    fn parse_message() {
+        // Convert a media cue with loop media
+        let request = OscInterface::parse_message(&OscMessage {
+            addr: "/apollo/cue/3".to_string(),
+            args: vec![
+                OscType::String("file:///video.mp4".to_string()),
+                OscType::String("file:///loop.mp4".to_string()),
+            ],
+        })
+        .unwrap();
+        if let Request::CueMedia { media_cue } = request {
+            assert_eq!(
+                MediaCue {
+                    uri: "file:///video.mp4".to_string(),
+                    channel: 3,
+                    loop_media: Some("file:///loop.mp4".to_string()),
+                    fade_in: None,
+                    fade_out: None,
+                    transition: None,
+                    start_at: None,
+                },
+                media_cue
+            );
+        } else {
+            panic!("Media cue was not converted.");
+        }
+
+        // Convert a seek with a float argument
+        let request = OscInterface::parse_message(&OscMessage {
+            addr: "/apollo/seek/2".to_string(),
+            args: vec![OscType::Float(1500.0)],
+        })
+        .unwrap();
+        if let Request::Seek { channel_seek } = request {
+            assert_eq!(
+                ChannelSeek {
+                    channel: 2,
+                    position: 1500,
+                },
+                channel_seek
+            );
+        } else {
+            panic!("Seek was not converted.");
+        }
+
+        // Reject an invalid state and an unknown address
+        assert!(
+            OscInterface::parse_message(&OscMessage {
+                addr: "/apollo/state/1".to_string(),
+                args: vec![OscType::String("rewind".to_string())],
+            })
+            .is_err()
+        );
+        assert!(
+            OscInterface::parse_message(&OscMessage {
+                addr: "/other/allStop".to_string(),
+                args: Vec::new(),
+            })
+            .is_err()
+        );
+    }
+    // Test the refusal of any sender when API keys are required
+    #[test]
+    fn check_senders() {
+        // Allow any sender without API keys
+        let mut settings = UserSettings::default();
+        assert_eq!(None, OscInterface::check_senders(&settings).unwrap());
+
+        // Refuse to start with API keys but no allowed senders
+        settings.api_keys.operator.push("secret".to_string());
+        assert!(OscInterface::check_senders(&settings).is_err());
+
+        // Allow any sender if explicitly allowed
+        settings.is_osc_unrestricted = true;
+        assert_eq!(None, OscInterface::check_senders(&settings).unwrap());
+
+        // Restrict the senders if specified (and refuse invalid addresses)
+        settings.is_osc_unrestricted = false;
+        settings.osc_allow.push("192.168.1.20".to_string());
+        assert_eq!(
+            Some(vec!["192.168.1.20".parse::<IpAddr>().unwrap()]),
+            OscInterface::check_senders(&settings).unwrap()
+        );
+        settings.osc_allow.push("console".to_string());
+        assert!(OscInterface::check_senders(&settings).is_err());
+    }
+}
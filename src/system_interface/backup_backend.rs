@@ -0,0 +1,195 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements the storage for the backup handler. The backup can
+//! be stored on a Redis server or in a local directory.
+
+// Import standard library features
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Import tracing features
+use tracing::error;
+
+// Imprt redis client library
+use redis::{Commands, ConnectionLike, RedisResult};
+
+// Import anyhow features
+use anyhow::{Context, Result};
+
+// Define module constants
+const FILE_PREFIX: &str = "file://"; // the prefix which selects the file backend
+const REDIS_TIMEOUT: u64 = 2000; // the time to wait for the Redis server before giving up, in milliseconds
+
+/// A trait to store and retrieve backup data by key
+///
+pub trait BackupBackend: Send {
+    /// A method to retrieve the data for a key, if it exists
+    ///
+    fn get(&mut self, key: &str) -> Option<String>;
+
+    /// A method to store the data for a key, replacing any existing data
+    ///
+    fn set(&mut self, key: &str, data: &str) -> Result<()>;
+
+    /// A method to remove the data for a key, if it exists
+    ///
+    fn delete(&mut self, key: &str);
+}
+
+/// A function to open the backup backend for the provided location. Locations
+/// starting with file:// are stored in a local directory, and all other
+/// locations are treated as a Redis server.
+///
+pub fn open_backend(location: &str) -> Result<Box<dyn BackupBackend>> {
+    // If the location is a local directory
+    if let Some(directory) = location.strip_prefix(FILE_PREFIX) {
+        Ok(Box::new(FileBackend::open(directory)?))
+
+    // Otherwise, connect to the Redis server
+    } else {
+        Ok(Box::new(RedisBackend::connect(location)?))
+    }
+}
+
+/// A structure to store the backup on a Redis server
+///
+pub struct RedisBackend {
+    connection: redis::Connection, // the connection to the Redis server
+}
+
+// Implement key features of the Redis backend
+impl RedisBackend {
+    /// A function to connect to the Redis server and update the snapshot
+    /// settings so that every change is written to the disk. If the server
+    /// stops responding, reads and writes fail after a short timeout.
+    ///
+    pub fn connect(location: &str) -> Result<RedisBackend> {
+        // Try to connect to the Redis server
+        let timeout = Duration::from_millis(REDIS_TIMEOUT);
+        let client = redis::Client::open(location).context("Invalid Redis server location.")?;
+        let mut connection = client
+            .get_connection_with_timeout(timeout)
+            .context("Unable to connect to Redis server.")?;
+        connection
+            .set_read_timeout(Some(timeout))
+            .context("Unable to connect to Redis server.")?;
+        connection
+            .set_write_timeout(Some(timeout))
+            .context("Unable to connect to Redis server.")?;
+
+        // Set the snapshot settings
+        let result: RedisResult<redis::Value> = connection.req_command(
+            redis::Cmd::new()
+                .arg("CONFIG")
+                .arg("SET")
+                .arg("save")
+                .arg("60 1"),
+        );
+
+        // Unpack the result from the operation
+        if result.is_err() {
+            // Warn that it wasn't possible to update the settings
+            error!("Unable to set Redis snapshot settings.");
+        }
+
+        // Return the new backend
+        Ok(RedisBackend { connection })
+    }
+}
+
+// Implement the backup backend for Redis
+impl BackupBackend for RedisBackend {
+    fn get(&mut self, key: &str) -> Option<String> {
+        self.connection.get(key).ok()
+    }
+
+    fn set(&mut self, key: &str, data: &str) -> Result<()> {
+        let _: bool = self
+            .connection
+            .set(key, data)
+            .context("Unable to write to Redis server.")?;
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) {
+        let _: RedisResult<bool> = self.connection.del(key);
+    }
+}
+
+/// A structure to store the backup as YAML files in a local directory
+///
+/// # Note
+///
+/// Each key is stored in a separate file. Files are written to a temporary
+/// file and then renamed (and the directory is synced), so a crash during a
+/// write never leaves a partial file behind.
+///
+pub struct FileBackend {
+    directory: PathBuf, // the directory which holds the backup files
+}
+
+// Implement key features of the file backend
+impl FileBackend {
+    /// A function to open the backup directory, creating it if necessary
+    ///
+    pub fn open(directory: &str) -> Result<FileBackend> {
+        // Create the directory (if it doesn't already exist)
+        let directory = PathBuf::from(directory);
+        fs::create_dir_all(&directory).context("Unable to create backup directory.")?;
+
+        // Return the new backend
+        Ok(FileBackend { directory })
+    }
+
+    // A helper function to compose the file location for a key
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory
+            .join(format!("{}.yaml", key.replace(':', "_")))
+    }
+}
+
+// Implement the backup backend for local files
+impl BackupBackend for FileBackend {
+    fn get(&mut self, key: &str) -> Option<String> {
+        fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn set(&mut self, key: &str, data: &str) -> Result<()> {
+        // Write the data to a temporary file and make sure it reaches the disk
+        let path = self.path(key);
+        let temporary = path.with_extension("yaml.tmp");
+        let mut file = fs::File::create(&temporary).context("Unable to create backup file.")?;
+        file.write_all(data.as_bytes())
+            .context("Unable to write backup file.")?;
+        file.sync_all().context("Unable to write backup file.")?;
+
+        // Replace the previous file
+        fs::rename(&temporary, &path).context("Unable to replace backup file.")?;
+
+        // Make sure the rename reaches the disk
+        fs::File::open(&self.directory)
+            .and_then(|directory| directory.sync_all())
+            .context("Unable to replace backup file.")
+    }
+
+    fn delete(&mut self, key: &str) {
+        fs::remove_file(self.path(key)).unwrap_or(());
+    }
+}
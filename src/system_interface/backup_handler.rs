@@ -15,43 +15,72 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-//! This module implements the connection to a Redis backup server to maintain
-//! a backup of the program state. This handler syncs the current media playlist
-//!  to the server. This module does nothing if a Redis server is not connected.
+//! This module implements the connection to a backup server (or a local
+//! backup directory) to maintain a backup of the program state. This handler
+//! syncs the current media playlist to the backup. This module does nothing
+//! if a backup location is not specified.
 //!
 //! WARNING: This module assumes no authorized systems/operators are compromised.
 
 // Import crate definitions
 use crate::definitions::*;
 
+// Import submodule definitions
+use super::backup_writer::{BackupReader, BackupWriter};
+
 // Import standard library features
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Import Tokio features
+use tokio::sync::mpsc;
+use tokio::time::{MissedTickBehavior, interval};
 
 // Import tracing features
 use tracing::{error, warn};
 
-// Imprt redis client library
-use redis::{Commands, ConnectionLike, RedisResult};
-
 // Import YAML processing library
 use serde_yaml;
 
-/// A structure which holds a reference to the Redis server (if it exists) and
-/// syncronizes local data to and from the server.
+// Define module constants
+const MAX_DRIFT: u64 = 250; // the largest difference from the expected media position before the playlist is copied again, in milliseconds
+
+/// A type to hold the backup of an instance (the windows, channels, media,
+/// and volumes)
+///
+pub type InstanceBackup = (WindowList, ChannelList, MediaPlaylist, VolumeList);
+
+/// A structure to hold a check of another instance of Apollo
+///
+#[derive(Debug)]
+pub struct InstanceUpdate {
+    pub heartbeat: Option<u64>, // the most recent heartbeat of the instance, if any
+    pub owner: Option<String>, // the address of the instance which took over from the instance, if any
+    pub backup: Option<InstanceBackup>, // the backup of the instance, if it changed since the last check
+}
+
+/// A structure which holds a reference to the backup storage (if it exists)
+/// and syncronizes local data to and from the storage.
 ///
 /// # Notes
 ///
 /// When created, the status handler will attempt to connect to the requested
-/// redis server. If the status handler cannot make the connection, the status
-/// handler will raise an error and return none.
+/// backup location. Locations starting with file:// are stored as files in a
+/// local directory, and all other locations are treated as a Redis server. If
+/// the status handler cannot make the connection, the status handler will
+/// raise an error and continue without a backup.
 ///
 pub struct BackupHandler {
     address: String, // the listening address for this instance of the controller for unique identification
-    connection: Option<redis::Connection>, // the Redis connection, if it exists
+    writer: Option<BackupWriter>, // the background writer for the backup, if it exists
+    is_paused: bool, // a flag to hold all writes to the backup (while on standby)
+    claimed: Option<String>, // the address of the instance this instance took over from, if any
+    last_group: u64, // the identifier of the most recent group of channels
     last_media_update: Instant, // the time of the last update for the media backup
     window_list: WindowList, // the list of all currently defined windows, in the order defined
     channel_list: ChannelList, // the list of all currently  defined channels, in the order defined
     media_playlist: MediaPlaylist, // the current media playback for each channel
+    last_playlist: MediaPlaylist, // the media playback for each channel, as last copied to the backup
+    volume_list: VolumeList,      // the current volume for each channel
     interface_send: InterfaceSend, // a sending line to pass the signal to close after cleanup is complete
 }
 
@@ -62,104 +91,235 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server provided.
+    /// backup location provided.
     ///
     pub async fn new(
         address: String,
         server_location: Option<String>,
         interface_send: InterfaceSend,
     ) -> Self {
-        // If a server location was specified
-        if let Some(location) = server_location {
-            // Try to connect to the Redis server
-            if let Ok(client) = redis::Client::open(location.as_str()) {
-                // Try to get a copy of the Redis connection
-                if let Ok(mut connection) = client.get_connection() {
-                    // Set the snapshot settings
-                    let result: RedisResult<redis::Value> = connection.req_command(
-                        redis::Cmd::new()
-                            .arg("CONFIG")
-                            .arg("SET")
-                            .arg("save")
-                            .arg("60 1"),
-                    );
-
-                    // Unpack the result from the operation
-                    if let Err(..) = result {
-                        // Warn that it wasn't possible to update the current scene
-                        error!("Unable to set Redis snapshot settings.");
-                    }
-
-                    // Return the new backup handler
-                    return Self {
-                        address,
-                        connection: Some(connection),
-                        last_media_update: Instant::now(),
-                        window_list: Vec::new(),
-                        channel_list: Vec::new(),
-                        media_playlist: MediaPlaylist::default(),
-                        interface_send,
-                    };
-
-                // Indicate that there was a failure to connect to the server
-                } else {
-                    error!("Unable to connect to backup server: {}.", location);
-                }
+        // If a server location was specified, start the background writer
+        // (which keeps trying to connect if the connection fails)
+        let writer = server_location.map(BackupWriter::new);
 
-            // Indicate that there was a failure to connect to the server
-            } else {
-                error!("Unable to connect to backup server: {}.", location);
-            }
-        }
-
-        // If a location was not specified or the connection failed, return without a redis connection
+        // Return the new backup handler
         Self {
             address,
-            connection: None,
+            writer,
+            is_paused: false,
+            claimed: None,
+            last_group: 0,
             last_media_update: Instant::now(),
             window_list: Vec::new(),
             channel_list: Vec::new(),
             media_playlist: MediaPlaylist::default(),
+            last_playlist: MediaPlaylist::default(),
+            volume_list: VolumeList::default(),
             interface_send,
         }
     }
 
-    /// A method to backup a new window definition to the backup server.
+    /// A method to return the listening address which identifies this
+    /// instance in the backup
+    ///
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// A method to hold all writes to the backup (while this instance is on
+    /// standby). Changes are still recorded and are written once the backup
+    /// is resumed.
+    ///
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    /// A method to resume writes to the backup and copy the windows,
+    /// channels, media, and volumes recorded while the backup was paused.
+    ///
+    pub async fn resume(&mut self) {
+        // If the backup exists
+        self.is_paused = false;
+        if let Some(writer) = self.writer.take() {
+            // Copy everything to the server
+            self.write_backup(&writer, "windows", &self.window_list);
+            self.write_backup(&writer, "channels", &self.channel_list);
+            self.write_backup(&writer, "volumes", &self.volume_list);
+            self.update_media();
+            self.write_playlist(&writer);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to mark the instance with the provided address as replaced
+    /// by this instance. The old instance checks this mark before resuming
+    /// (and while running) so that two instances never play the same show.
     ///
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
-    pub async fn backup_window(&mut self, window_definition: WindowDefinition) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
-            // Add the cue to the window list
-            self.window_list.push(window_definition);
+    pub async fn claim_instance(&mut self, address: &str) {
+        // If the backup exists (and is not paused)
+        if !self.is_paused
+            && let Some(writer) = self.writer.take()
+        {
+            // Mark the old instance and this instance as owned by this instance
+            if let Ok(owner) = serde_yaml::to_string(&self.address) {
+                writer.write(format!("apollo:{}:owner", address), owner);
+            }
+            self.write_backup(&writer, "owner", &self.address);
+            self.claimed = Some(address.to_string());
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
 
-            // Try to serialize the window_list
-            let window_string = match serde_yaml::to_string(&self.window_list) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse window list: {}.", error);
+    /// A method to read the address of the instance which took over from
+    /// this instance, if another instance took over.
+    ///
+    pub async fn read_owner(&mut self) -> Option<String> {
+        // Try to read the owner of this instance
+        let address = self.address.clone();
+        let owner_string = self.read_backup(&address, "owner").await;
 
-                    // Put the connection back
-                    self.connection = Some(connection);
+        // Parse the owner (ignoring this instance)
+        BackupHandler::parse_owner(owner_string, &address)
+    }
+
+    /// A method to check the backup of another instance of Apollo in the
+    /// background, once each period. Each check is sent to the returned
+    /// receiver, and the windows, channels, media, and volumes are included
+    /// only when they change. The checks stop when the receiver is dropped.
+    ///
+    /// If there is no backup, this method returns None.
+    ///
+    pub fn watch_instance(
+        &self,
+        address: &str,
+        period: Duration,
+    ) -> Option<mpsc::Receiver<InstanceUpdate>> {
+        // Compose the keys to check
+        let reader = self.writer.as_ref()?.reader();
+        let keys: Vec<String> = [
+            "heartbeat",
+            "owner",
+            "media",
+            "windows",
+            "channels",
+            "volumes",
+        ]
+        .iter()
+        .map(|key| format!("apollo:{}:{}", address, key))
+        .collect();
+
+        // Check the backup in the background (without delaying the system interface)
+        let (update_send, update_receive) = mpsc::channel(1);
+        tokio::spawn(async move {
+            // Create the timer (skipping any missed checks)
+            let mut timer = interval(period);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            // Check the backup until the receiver is dropped
+            let mut last_backup = Vec::new();
+            loop {
+                timer.tick().await;
+                let mut data = reader.read_all(keys.clone()).await;
+
+                // Parse the heartbeat and owner
+                let backup_data = data.split_off(2);
+                let heartbeat = data[0]
+                    .as_ref()
+                    .and_then(|heartbeat| serde_yaml::from_str(heartbeat).ok());
+                let owner = data[1]
+                    .as_ref()
+                    .and_then(|owner| serde_yaml::from_str(owner).ok());
+
+                // Parse the rest of the backup only if it changed
+                let mut backup = None;
+                if backup_data != last_backup {
+                    backup = BackupHandler::parse_instance(&backup_data);
+                    last_backup = backup_data;
+                }
+
+                // Send the update (and stop if the receiver was dropped)
+                let update = InstanceUpdate {
+                    heartbeat,
+                    owner,
+                    backup,
+                };
+                if update_send.send(update).await.is_err() {
                     return;
                 }
-            };
+            }
+        });
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> =
-                connection.set(&format!("apollo:{}:windows", self.address), &window_string);
+        // Return the receiver
+        Some(update_receive)
+    }
 
-            // Alert that the window list was not set
-            if let Err(..) = result {
-                error!("Unable to backup window list onto backup server.");
+    /// A method to check in the background, once each period, if another
+    /// instance has taken over from this instance. The address of the other
+    /// instance is sent to the returned receiver, and the checks stop once
+    /// another instance is found or the receiver is dropped.
+    ///
+    /// If there is no backup, this method returns None.
+    ///
+    pub fn watch_owner(&self, period: Duration) -> Option<mpsc::Receiver<String>> {
+        // Compose the key to check
+        let reader = self.writer.as_ref()?.reader();
+        let address = self.address.clone();
+        let key = self.backup_key("owner");
+
+        // Check the backup in the background (without delaying the system interface)
+        let (owner_send, owner_receive) = mpsc::channel(1);
+        tokio::spawn(async move {
+            // Create the timer (skipping any missed checks)
+            let mut timer = interval(period);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            // Check the backup until another owner is found
+            loop {
+                // Stop if the receiver was dropped
+                timer.tick().await;
+                if owner_send.is_closed() {
+                    return;
+                }
+
+                // Check the owner of this instance
+                if let Some(owner) = BackupHandler::parse_owner(reader.read(&key).await, &address) {
+                    owner_send.send(owner).await.unwrap_or(());
+                    return;
+                }
             }
+        });
 
-            // Put the connection back
-            self.connection = Some(connection);
+        // Return the receiver
+        Some(owner_receive)
+    }
+
+    /// A method to backup a new window definition to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_window(&mut self, window_definition: WindowDefinition) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Add the cue to the window list
+            self.window_list.push(window_definition);
+
+            // Try to copy the window list to the server
+            self.write_backup(&writer, "windows", &self.window_list);
+
+            // Put the backup back
+            self.writer = Some(writer);
         }
     }
 
@@ -168,39 +328,133 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_channel(&mut self, media_channel: MediaChannel) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Add the channel to the channel list
             self.channel_list.push(media_channel);
 
-            // Try to serialize the channel list
-            let channel_string = match serde_yaml::to_string(&self.channel_list) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse channel list: {}.", error);
+            // Try to copy the channel list to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
 
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> = connection.set(
-                &format!("apollo:{}:channels", self.address),
-                &channel_string,
-            );
+    /// A method to replace a window definition and backup to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_window_redefine(&mut self, window_definition: WindowDefinition) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Replace the window in the window list (or add it if missing)
+            if let Some(window) = self
+                .window_list
+                .iter_mut()
+                .find(|window| window.window_number == window_definition.window_number)
+            {
+                *window = window_definition;
+            } else {
+                self.window_list.push(window_definition);
+            }
+
+            // Try to copy the window list to the server
+            self.write_backup(&writer, "windows", &self.window_list);
 
-            // Alert that the channel list was not set
-            if let Err(..) = result {
-                error!("Unable to backup channel list onto backup server.");
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to remove a window definition and backup to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_window_removal(&mut self, window_number: u32) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Remove the window from the window list
+            self.window_list
+                .retain(|window| window.window_number != window_number);
+
+            // Try to copy the window list to the server
+            self.write_backup(&writer, "windows", &self.window_list);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to replace a channel definition and backup to the backup
+    /// server. Any media on the channel is removed from the backup.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_channel_redefine(&mut self, media_channel: MediaChannel) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Remove any media on the channel
+            self.update_media();
+            self.media_playlist.remove(&media_channel.channel);
+
+            // Replace the channel in the channel list (or add it if missing)
+            if let Some(channel) = self
+                .channel_list
+                .iter_mut()
+                .find(|channel| channel.channel == media_channel.channel)
+            {
+                *channel = media_channel;
+            } else {
+                self.channel_list.push(media_channel);
             }
 
-            // Put the connection back
-            self.connection = Some(connection);
+            // Try to copy the channel list and media playlist to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
+            self.write_playlist(&writer);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to remove a channel definition (and any media and volume for
+    /// the channel) and backup to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_channel_removal(&mut self, channel_number: u32) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Remove the channel, media, and volume
+            self.update_media();
+            self.channel_list
+                .retain(|channel| channel.channel != channel_number);
+            self.media_playlist.remove(&channel_number);
+            self.volume_list.remove(&channel_number);
+
+            // Try to copy the lists to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
+            self.write_playlist(&writer);
+            self.write_backup(&writer, "volumes", &self.volume_list);
+
+            // Put the backup back
+            self.writer = Some(writer);
         }
     }
 
@@ -209,11 +463,11 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_channel_align(&mut self, new_alignment: ChannelRealignment) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Find the channel in the channel list
             for channel in self.channel_list.iter_mut() {
                 // If we found the correct channel (checked elsewhere for uniqueness)
@@ -227,8 +481,8 @@ impl BackupHandler {
                                 new_alignment.channel
                             );
 
-                            // Put the connection back
-                            self.connection = Some(connection);
+                            // Put the backup back
+                            self.writer = Some(writer);
                             return;
                         }
                     };
@@ -246,31 +500,11 @@ impl BackupHandler {
                 }
             }
 
-            // Try to serialize the channel list
-            let channel_string = match serde_yaml::to_string(&self.channel_list) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse channel list: {}.", error);
-
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
+            // Try to copy the channel list to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> = connection.set(
-                &format!("apollo:{}:channels", self.address),
-                &channel_string,
-            );
-
-            // Alert that the channel list was not set
-            if let Err(..) = result {
-                error!("Unable to backup channel list onto backup server.");
-            }
-
-            // Put the connection back
-            self.connection = Some(connection);
+            // Put the backup back
+            self.writer = Some(writer);
         }
     }
 
@@ -279,11 +513,11 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_channel_resize(&mut self, new_size: ChannelAllocation) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Find the channel in the channel list
             for channel in self.channel_list.iter_mut() {
                 // If we found the correct channel (checked elsewhere for uniqueness)
@@ -297,8 +531,8 @@ impl BackupHandler {
                                 new_size.channel
                             );
 
-                            // Put the connection back
-                            self.connection = Some(connection);
+                            // Put the backup back
+                            self.writer = Some(writer);
                             return;
                         }
                     };
@@ -317,31 +551,66 @@ impl BackupHandler {
                 }
             }
 
-            // Try to serialize the channel list
-            let channel_string = match serde_yaml::to_string(&self.channel_list) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse channel list: {}.", error);
+            // Try to copy the channel list to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
 
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> = connection.set(
-                &format!("apollo:{}:channels", self.address),
-                &channel_string,
-            );
+    /// A method to update the idle background of a channel and backup to
+    /// the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_channel_background(
+        &mut self,
+        channel_number: u32,
+        background: Option<Background>,
+    ) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Update the channel in the channel list (checked elsewhere for uniqueness)
+            if let Some(channel) = self
+                .channel_list
+                .iter_mut()
+                .find(|channel| channel.channel == channel_number)
+            {
+                channel.idle_background = background;
+            }
 
-            // Alert that the channel list was not set
-            if let Err(..) = result {
-                error!("Unable to backup channel list onto backup server.");
+            // Try to copy the channel list to the server
+            self.write_backup(&writer, "channels", &self.channel_list);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to update the definitions of existing channels (such as the
+    /// layer, opacity, or visibility of each channel) without changing the
+    /// media, and backup to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_channel_update(&mut self, channel_list: ChannelList) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Replace each existing channel in the channel list
+            if self.update_channels(channel_list) {
+                // Try to copy the channel list to the server
+                self.write_backup(&writer, "channels", &self.channel_list);
             }
 
-            // Put the connection back
-            self.connection = Some(connection);
+            // Put the backup back
+            self.writer = Some(writer);
         }
     }
 
@@ -354,57 +623,172 @@ impl BackupHandler {
     /// method does not verify the validity of the media cue values in any way.
     /// It is expected that the calling module will perform this check.
     ///
-    /// The media interface only waits half a second for media to load before
-    /// seeking to the corrent position of the media. This delay may not be
-    /// sufficient for network-loaded media which can take several seconds
-    /// to load. If the media takes too long to load, the media with resume
-    /// playback from the start rather than its correct position.
+    /// The media interface waits up to ten seconds for media to load before
+    /// seeking to the corrent position of the media. Network-loaded media may
+    /// take longer to load. If the media takes too long to load, the media
+    /// will resume playback from the start rather than its correct position.
     ///
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_media(&mut self, media_cue: MediaCue) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Update the media seek positions
             self.update_media();
 
-            // Add the cue to the media playlist
+            // Add the cue to the media playlist (without any start time, which has passed)
             self.media_playlist.insert(
                 media_cue.channel,
                 MediaPlayback {
-                    media_cue,
+                    media_cue: MediaCue {
+                        start_at: None,
+                        ..media_cue
+                    },
                     seek_to: Duration::from_secs(0),
                     state: PlaybackState::Playing,
+                    updated: 0,
+                    group: None,
                 },
             ); // replaces an existing media playback, if it exists
 
-            // Try to serialize the media playlist
-            let media_string = match serde_yaml::to_string(&self.media_playlist) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse media playlist: {}.", error);
+            // Try to copy the media playlist to the server
+            self.write_playlist(&writer);
 
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to backup media taken over from another instance to the
+    /// backup server. Any group of the media is kept, so that the group is
+    /// restarted together after a crash.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_playback(&mut self, playback: MediaPlayback) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Update the media seek positions
+            self.update_media();
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> =
-                connection.set(&format!("apollo:{}:media", self.address), &media_string);
+            // Add the media to the media playlist
+            self.media_playlist.insert(
+                playback.media_cue.channel,
+                MediaPlayback {
+                    seek_to: Duration::from_secs(0),
+                    state: PlaybackState::Playing,
+                    updated: 0,
+                    ..playback
+                },
+            ); // replaces an existing media playback, if it exists
 
-            // Alert that the media playlist was not set
-            if let Err(..) = result {
-                error!("Unable to backup media onto backup server.");
+            // Try to copy the media playlist to the server
+            self.write_playlist(&writer);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to backup new media on several channels which play together
+    /// to the backup server. The channels are marked as one group, so that
+    /// the group is restarted together after a crash.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_group(&mut self, cue_group: CueGroup) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Update the media seek positions
+            self.update_media();
+
+            // Create a new, unique group
+            self.last_group = BackupHandler::now().max(self.last_group + 1);
+            let group = PlaybackGroup {
+                id: self.last_group,
+                loop_group: cue_group.loop_group,
+            };
+
+            // Add each cue to the media playlist (without any start time, which has passed)
+            for media_cue in cue_group.media_cues {
+                self.media_playlist.insert(
+                    media_cue.channel,
+                    MediaPlayback {
+                        media_cue: MediaCue {
+                            start_at: None,
+                            ..media_cue
+                        },
+                        seek_to: Duration::from_secs(0),
+                        state: PlaybackState::Playing,
+                        updated: 0,
+                        group: Some(group.clone()),
+                    },
+                ); // replaces an existing media playback, if it exists
             }
 
-            // Put the connection back
-            self.connection = Some(connection);
+            // Try to copy the media playlist to the server
+            self.write_playlist(&writer);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A function to remove the channels which play together in a group
+    /// from a media playlist. Each group is returned as a cue group, with the
+    /// position and state of the first channel in the group (every channel
+    /// in a group shares the same position and state).
+    ///
+    pub fn take_groups(playlist: &mut MediaPlaylist) -> Vec<(CueGroup, Duration, PlaybackState)> {
+        // Sort the grouped channels by group, in channel order
+        let mut channels: Vec<u32> = playlist
+            .iter()
+            .filter(|(_, playback)| playback.group.is_some())
+            .map(|(channel, _)| *channel)
+            .collect();
+        channels.sort();
+
+        // Combine the channels in each group
+        let mut groups: Vec<(u64, CueGroup, Duration, PlaybackState)> = Vec::new();
+        for channel in channels {
+            // Remove the channel from the playlist
+            let Some(playback) = playlist.remove(&channel) else {
+                continue;
+            };
+            let Some(group) = playback.group else {
+                continue;
+            };
+
+            // Add the media to an existing group, or start a new group
+            match groups.iter_mut().find(|(id, ..)| *id == group.id) {
+                Some((_, cue_group, ..)) => cue_group.media_cues.push(playback.media_cue),
+                None => groups.push((
+                    group.id,
+                    CueGroup {
+                        media_cues: vec![playback.media_cue],
+                        loop_group: group.loop_group,
+                        start_at: None,
+                    },
+                    playback.seek_to,
+                    playback.state,
+                )),
+            }
         }
+
+        // Return the groups
+        groups
+            .into_iter()
+            .map(|(_, cue_group, seek_to, state)| (cue_group, seek_to, state))
+            .collect()
     }
 
     /// A method to backup the state of media to the backup server.
@@ -412,11 +796,11 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_media_state(&mut self, new_state: ChannelState) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Update the media seek positions
             self.update_media();
 
@@ -425,41 +809,19 @@ impl BackupHandler {
                 // Upate the media
                 media.state = new_state.state;
 
+                // Try to copy the media playlist to the server
+                self.write_playlist(&writer);
+
             // Otherwise, warn the media wasn't found
             } else {
                 error!(
                     "Unable to backup media state: channel {} not defined.",
                     new_state.channel
                 );
-
-                // Put the connection back
-                self.connection = Some(connection);
-                return;
             }
 
-            // Try to serialize the media playlist
-            let media_string = match serde_yaml::to_string(&self.media_playlist) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse media playlist: {}.", error);
-
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
-
-            // Try to copy the data to the server
-            let result: RedisResult<bool> =
-                connection.set(&format!("apollo:{}:media", self.address), &media_string);
-
-            // Alert that the media playlist was not set
-            if let Err(..) = result {
-                error!("Unable to backup media onto backup server.");
-            }
-
-            // Put the connection back
-            self.connection = Some(connection);
+            // Put the backup back
+            self.writer = Some(writer);
         }
     }
 
@@ -468,11 +830,11 @@ impl BackupHandler {
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
+    /// backup server.
     ///
     pub async fn backup_media_seek(&mut self, new_seek: ChannelSeek) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
             // Update the media seek positions
             self.update_media();
 
@@ -481,118 +843,465 @@ impl BackupHandler {
                 // Upate the media seek location
                 media.seek_to = Duration::from_millis(new_seek.position);
 
+                // Try to copy the media playlist to the server
+                self.write_playlist(&writer);
+
             // Otherwise, warn the media wasn't found
             } else {
                 error!(
                     "Unable to backup media state: channel {} not defined.",
                     new_seek.channel
                 );
+            }
 
-                // Put the connection back
-                self.connection = Some(connection);
-                return;
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to backup the volume of a channel to the backup server. Any
+    /// fade is ignored and the final volume is saved.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_volume(&mut self, mut channel_volume: ChannelVolume) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Add the volume to the volume list, without the fade
+            channel_volume.fade = None;
+
+            // If the mute setting was omitted, keep the previous setting
+            if channel_volume.mute.is_none() {
+                channel_volume.mute = self
+                    .volume_list
+                    .get(&channel_volume.channel)
+                    .and_then(|previous| previous.mute);
             }
+            self.volume_list
+                .insert(channel_volume.channel, channel_volume); // replaces an existing volume, if it exists
 
-            // Try to serialize the media playlist
-            let media_string = match serde_yaml::to_string(&self.media_playlist) {
-                Ok(string) => string,
-                Err(error) => {
-                    error!("Unable to parse media playlist: {}.", error);
+            // Try to copy the volume list to the server
+            self.write_backup(&writer, "volumes", &self.volume_list);
 
-                    // Put the connection back
-                    self.connection = Some(connection);
-                    return;
-                }
-            };
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to record the real position of the media on every channel
+    /// and backup to the backup server. This method also updates the
+    /// heartbeat which a standby instance uses to check this instance.
+    ///
+    /// # Note
+    ///
+    /// If the channel has moved on to its loop media, the loop media replaces
+    /// the original media in the backup. Channels which are still loading
+    /// media are left unchanged.
+    ///
+    /// The media playlist is only copied to the server if the media, state,
+    /// or position has changed from what the last copy expects. The heartbeat
+    /// is updated every time.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_checkpoint(&mut self, status_list: ChannelStatusList) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Copy any change to the channel definitions (such as the layers
+            // renumbered when another channel is added or removed)
+            if self.update_channels(
+                status_list
+                    .iter()
+                    .map(|status| status.media_channel.clone())
+                    .collect(),
+            ) {
+                self.write_backup(&writer, "channels", &self.channel_list);
+            }
+
+            // Update the media seek positions (for any channel not reported)
+            self.update_media();
+
+            // Check each channel with loaded media
+            for status in status_list {
+                // Make sure the media is loaded
+                let (Some(uri), Some(state), Some(position)) =
+                    (status.current_uri, status.state, status.position)
+                else {
+                    continue;
+                };
+
+                // Find the media in the playlist
+                let channel = status.media_channel.channel;
+                if let Some(media) = self.media_playlist.get_mut(&channel) {
+                    // If the channel switched to the loop media, replace the media
+                    if media.media_cue.uri != uri {
+                        // Ignore the channel if the media is unexpected (likely still loading)
+                        if status.loop_media.as_ref() != Some(&uri) {
+                            continue;
+                        }
+
+                        // Replace the media with the loop media
+                        media.media_cue = MediaCue {
+                            uri: uri.clone(),
+                            channel,
+                            loop_media: Some(uri),
+                            fade_in: None,
+                            fade_out: None,
+                            transition: None,
+                            start_at: None,
+                        };
+                    }
 
-            // Try to copy the data to the server
-            let result: RedisResult<bool> =
-                connection.set(&format!("apollo:{}:media", self.address), &media_string);
+                    // Record the real position and state
+                    media.seek_to = Duration::from_millis(position);
+                    media.state = state;
+                }
+            }
 
-            // Alert that the media playlist was not set
-            if let Err(..) = result {
-                error!("Unable to backup media onto backup server.");
+            // Copy the media playlist to the server, unless the media is
+            // still where the last copy expects it to be
+            let heartbeat = BackupHandler::now();
+            if !BackupHandler::is_unchanged(&self.last_playlist, &self.media_playlist, heartbeat) {
+                self.write_playlist(&writer);
             }
 
-            // Put the connection back
-            self.connection = Some(connection);
+            // Update the heartbeat to show this instance is still running
+            self.write_backup(&writer, "heartbeat", &heartbeat);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to backup that all the media was stopped to the backup
+    /// server (so that the media is not restarted by a restore or standby).
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_all_stop(&mut self) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Clear the media playlist
+            self.media_playlist.clear();
+
+            // Try to copy the media playlist to the server
+            self.write_playlist(&writer);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to backup the requests waiting to run to the backup server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn backup_schedule(&mut self, schedule_list: ScheduleList) {
+        // If the backup exists
+        if let Some(writer) = self.writer.take() {
+            // Try to copy the schedule list to the server
+            self.write_backup(&writer, "schedule", &schedule_list);
+
+            // Put the backup back
+            self.writer = Some(writer);
+        }
+    }
+
+    /// A method to delete the backup for this instance and disconnect from
+    /// the backup server. This method should be called before the handler
+    /// is dropped, so that the backup is removed before the program closes.
+    ///
+    pub async fn close(&mut self) {
+        // If the backup exists, delete each part of the backup
+        if let Some(writer) = self.writer.take() {
+            writer.close(self.instance_keys()).await;
         }
     }
 
+    /// A method to reload the requests waiting to run from the backup server.
+    /// If the data exists, this function returns the existing schedule list.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn reload_schedule(&mut self) -> Option<ScheduleList> {
+        // If the backup exists, check to see if there is a schedule list
+        let schedule_string = self.read_backup(&self.address.clone(), "schedule").await?;
+
+        // If something was received, try to parse the data
+        serde_yaml::from_str(schedule_string.as_str()).ok()
+    }
+
     /// A method to reload an existing backup from the backup server. If the
     /// data exists, this function returns the existing backup data.
     ///
     /// # Errors
     ///
     /// This function will raise an error if it is unable to connect to the
-    /// Redis server.
-    ///
-    pub fn reload_backup(&mut self) -> Option<(WindowList, ChannelList, MediaPlaylist)> {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
-            // Check to see if there is a media playlist
-            let result: RedisResult<String> =
-                connection.get(&format!("apollo:{}:media", self.address));
-
-            // If something was received
-            if let Ok(media_string) = result {
-                // Warn that existing data was found
-                warn!("Apollo detected lingering backup data. Reloading ...");
-
-                // Try to parse the data
-                let mut media_playlist = MediaPlaylist::default();
-                if let Ok(playlist) = serde_yaml::from_str(media_string.as_str()) {
-                    media_playlist = playlist;
-                }
+    /// backup server.
+    ///
+    pub async fn reload_backup(&mut self) -> Option<InstanceBackup> {
+        // If the backup exists, check to see if there is existing data
+        let (window_list, channel_list, media_playlist, volume_list) =
+            self.read_instance(&self.address.clone()).await?;
+
+        // Warn that existing data was found
+        warn!("Apollo detected lingering backup data. Reloading ...");
+
+        // Save the existing data
+        self.window_list = window_list.clone();
+        self.channel_list = channel_list.clone();
+        self.media_playlist = media_playlist.clone();
+        self.volume_list = volume_list.clone();
+
+        // Return all the media information
+        Some((window_list, channel_list, media_playlist, volume_list))
+    }
 
-                // Save the media playlist
-                self.media_playlist = media_playlist.clone();
+    /// A method to read the backup of another instance of Apollo (without
+    /// changing the backup for this instance). If the data exists, this
+    /// function returns the windows, channels, media, and volumes for the
+    /// instance with the provided address.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// backup server.
+    ///
+    pub async fn read_instance(&mut self, address: &str) -> Option<InstanceBackup> {
+        // If the backup exists, read each part of the backup (together)
+        let keys = ["media", "windows", "channels", "volumes", "heartbeat"]
+            .iter()
+            .map(|key| format!("apollo:{}:{}", address, key))
+            .collect();
+        let mut data = self.writer.as_ref()?.reader().read_all(keys).await;
+        let heartbeat_string = data.pop().flatten();
+
+        // Try to parse the data (nothing to return if there is no media playlist)
+        let (window_list, channel_list, mut media_playlist, volume_list) =
+            BackupHandler::parse_instance(&data)?;
+
+        // Advance any playing media to the time of the last heartbeat (the
+        // playlist is only copied when the media changes)
+        if let Some(heartbeat) =
+            heartbeat_string.and_then(|heartbeat| serde_yaml::from_str(heartbeat.as_str()).ok())
+        {
+            BackupHandler::advance_playlist(&mut media_playlist, heartbeat);
+        }
 
-                // Try to read the existing window list
-                let mut window_list = WindowList::new();
-                let result: RedisResult<String> =
-                    connection.get(&format!("apollo:{}:windows", self.address));
+        // Return all the media information
+        Some((window_list, channel_list, media_playlist, volume_list))
+    }
 
-                // If something was received
-                if let Ok(window_string) = result {
-                    // Try to parse the data
-                    if let Ok(windows) = serde_yaml::from_str(window_string.as_str()) {
-                        window_list = windows;
-                    }
-                }
+    /// A helper function to read the data stored under the provided key for
+    /// the instance with the provided address, if the backup and the data
+    /// exist.
+    ///
+    async fn read_backup(&mut self, address: &str, key: &str) -> Option<String> {
+        // Compose the full key
+        let key = format!("apollo:{}:{}", address, key);
 
-                // Save the window list
-                self.window_list = window_list.clone();
+        // Try to read the data (if the backup exists)
+        self.writer.as_ref()?.read(&key).await
+    }
 
-                // Try to read the existing channel list
-                let mut channel_list = ChannelList::new();
-                let result: RedisResult<String> =
-                    connection.get(&format!("apollo:{}:channels", self.address));
+    /// A helper function to serialize data and queue it to copy to the backup
+    /// server under the provided key.
+    ///
+    fn write_backup<T: serde::Serialize>(&self, writer: &BackupWriter, key: &str, data: &T) {
+        // Hold the data while the backup is paused
+        if self.is_paused {
+            return;
+        }
 
-                // If something was received
-                if let Ok(channel_string) = result {
-                    // Try to parse the data
-                    if let Ok(channels) = serde_yaml::from_str(channel_string.as_str()) {
-                        channel_list = channels;
-                    }
-                }
+        // Try to serialize the data
+        let data_string = match serde_yaml::to_string(data) {
+            Ok(string) => string,
+            Err(error) => {
+                error!("Unable to parse {} backup: {}.", key, error);
+                return;
+            }
+        };
+
+        // Queue the data to copy to the server (without waiting for the server)
+        writer.write(self.backup_key(key), data_string);
+    }
+
+    /// A helper function to parse the media, windows, channels, and volumes
+    /// of an instance (in that order). If there is no media playlist, the
+    /// backup does not exist and this function returns None.
+    ///
+    fn parse_instance(data: &[Option<String>]) -> Option<InstanceBackup> {
+        // Make sure there is a media playlist
+        let media_string = data.first()?.as_ref()?;
+
+        // Try to parse the media playlist
+        let mut media_playlist = MediaPlaylist::default();
+        if let Ok(playlist) = serde_yaml::from_str(media_string.as_str()) {
+            media_playlist = playlist;
+        }
+
+        // Try to parse the existing window list
+        let mut window_list = WindowList::new();
+        if let Some(Some(window_string)) = data.get(1)
+            && let Ok(windows) = serde_yaml::from_str(window_string.as_str())
+        {
+            window_list = windows;
+        }
+
+        // Try to parse the existing channel list
+        let mut channel_list = ChannelList::new();
+        if let Some(Some(channel_string)) = data.get(2)
+            && let Ok(channels) = serde_yaml::from_str(channel_string.as_str())
+        {
+            channel_list = channels;
+        }
+
+        // Try to parse the existing volume list
+        let mut volume_list = VolumeList::default();
+        if let Some(Some(volume_string)) = data.get(3)
+            && let Ok(volumes) = serde_yaml::from_str(volume_string.as_str())
+        {
+            volume_list = volumes;
+        }
+
+        // Return all the media information
+        Some((window_list, channel_list, media_playlist, volume_list))
+    }
+
+    /// A helper function to parse the owner of an instance, ignoring the
+    /// instance itself
+    ///
+    fn parse_owner(owner_string: Option<String>, address: &str) -> Option<String> {
+        serde_yaml::from_str::<String>(owner_string?.as_str())
+            .ok()
+            .filter(|owner| owner != address)
+    }
+
+    /// A helper function to record the time of each media position and
+    /// queue the media playlist to copy to the backup server.
+    ///
+    fn write_playlist(&mut self, writer: &BackupWriter) {
+        // Note the time of each position
+        let now = BackupHandler::now();
+        for media in self.media_playlist.values_mut() {
+            media.updated = now;
+        }
+
+        // Queue the playlist and save a copy for the next checkpoint
+        self.write_backup(writer, "media", &self.media_playlist);
+        self.last_playlist = self.media_playlist.clone();
+    }
 
-                // Save the channel list
-                self.channel_list = channel_list.clone();
+    /// A helper function to check if the media playlist matches the last copy
+    /// of the playlist, allowing for the time since the copy was made.
+    ///
+    fn is_unchanged(previous: &MediaPlaylist, current: &MediaPlaylist, now: u64) -> bool {
+        // Make sure the same channels have media
+        if previous.len() != current.len() {
+            return false;
+        }
 
-                // Put the connection back
-                self.connection = Some(connection);
+        // Compare the media on each channel
+        current.iter().all(|(channel, media)| {
+            previous.get(channel).is_some_and(|last| {
+                // Calculate where the media should be now
+                let mut expected = last.seek_to.as_millis() as u64;
+                if last.state == PlaybackState::Playing {
+                    expected += now.saturating_sub(last.updated);
+                }
 
-                // Return all the media information
-                return Some((window_list, channel_list, media_playlist));
+                // Check the media, the state, and the position
+                last.media_cue == media.media_cue
+                    && last.state == media.state
+                    && expected.abs_diff(media.seek_to.as_millis() as u64) <= MAX_DRIFT
+            })
+        })
+    }
+
+    /// A helper function to advance the playing media in a playlist from the
+    /// time each position was recorded to the provided time (in milliseconds
+    /// since the epoch).
+    ///
+    fn advance_playlist(playlist: &mut MediaPlaylist, now: u64) {
+        for media in playlist.values_mut() {
+            // Skip any paused media or media without a recorded time
+            if media.state == PlaybackState::Playing && media.updated > 0 {
+                media.update(Duration::from_millis(now.saturating_sub(media.updated)));
+                media.updated = now;
             }
+        }
+    }
+
+    /// A helper function to return the current time, in milliseconds since
+    /// the epoch.
+    ///
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
 
-            // Put the connection back
-            self.connection = Some(connection);
+    /// A helper function to compose the unique key for this instance
+    ///
+    fn backup_key(&self, key: &str) -> String {
+        format!("apollo:{}:{}", self.address, key)
+    }
+
+    /// A helper function to compose every key in the backup for this instance
+    /// (including the mark on any instance this instance took over from)
+    ///
+    fn instance_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = [
+            "media",
+            "channels",
+            "windows",
+            "volumes",
+            "schedule",
+            "heartbeat",
+            "owner",
+        ]
+        .iter()
+        .map(|key| self.backup_key(key))
+        .collect();
+        if let Some(address) = self.claimed.as_ref() {
+            keys.push(format!("apollo:{}:owner", address));
         }
+        keys
+    }
 
-        // Silently return nothing if the connection does not exist or there was not any data
-        None
+    /// A helper function to replace the definitions of existing channels in
+    /// the channel list. Returns true if any definition changed.
+    ///
+    fn update_channels(&mut self, channel_list: ChannelList) -> bool {
+        let mut is_changed = false;
+        for media_channel in channel_list {
+            if let Some(channel) = self
+                .channel_list
+                .iter_mut()
+                .find(|channel| channel.channel == media_channel.channel)
+                && *channel != media_channel
+            {
+                *channel = media_channel;
+                is_changed = true;
+            }
+        }
+        is_changed
     }
 
     /// A helper function to advance the media seek positions.
@@ -602,7 +1311,10 @@ impl BackupHandler {
     fn update_media(&mut self) {
         // Advance the seek position of all the currently playing media
         for media in self.media_playlist.values_mut() {
-            media.update(self.last_media_update.elapsed());
+            // Skip any paused media
+            if media.state == PlaybackState::Playing {
+                media.update(self.last_media_update.elapsed());
+            }
         }
 
         // Save the new update time
@@ -620,16 +1332,9 @@ impl Drop for BackupHandler {
     /// connection is being closed.
     ///
     fn drop(&mut self) {
-        // If the redis connection exists
-        if let Some(mut connection) = self.connection.take() {
-            // Try to delete the media backup if it exists
-            let _: RedisResult<bool> = connection.del(&format!("apollo:{}:media", self.address));
-
-            // Try to delete the channel backup if it exists
-            let _: RedisResult<bool> = connection.del(&format!("apollo:{}:channels", self.address));
-
-            // Try to delete the window backup if it exists
-            let _: RedisResult<bool> = connection.del(&format!("apollo:{}:windows", self.address));
+        // If the backup wasn't already closed, delete each part of the backup in the background
+        if let Some(writer) = self.writer.take() {
+            writer.close_in_background(self.instance_keys());
         }
 
         // Close the GTK program and video windows
@@ -657,7 +1362,7 @@ mod tests {
         .await;
 
         // Make sure there is no existing backup
-        if backup_handler.reload_backup().is_some() {
+        if backup_handler.reload_backup().await.is_some() {
             panic!("Backup already existed before beginning of the test.");
         }
 
@@ -667,6 +1372,9 @@ mod tests {
                 window_number: 1,
                 fullscreen: true,
                 dimensions: None,
+                background: None,
+                monitor: None,
+                position: None,
             })
             .await;
         backup_handler
@@ -675,6 +1383,11 @@ mod tests {
                 video_frame: None,
                 audio_device: None,
                 loop_media: None,
+                idle_background: None,
+                transitions: false,
+                layer: None,
+                opacity: None,
+                hidden: false,
             })
             .await;
         backup_handler
@@ -682,6 +1395,10 @@ mod tests {
                 channel: 1,
                 uri: "video.mp4".to_string(),
                 loop_media: None,
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
             })
             .await;
         backup_handler
@@ -689,16 +1406,25 @@ mod tests {
                 channel: 1,
                 uri: "new_video.mp4".to_string(),
                 loop_media: None,
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
             })
             .await;
 
         // Reload the backup
-        if let Some((window_list, channel_list, media_playlist)) = backup_handler.reload_backup() {
+        if let Some((window_list, channel_list, media_playlist, _)) =
+            backup_handler.reload_backup().await
+        {
             assert_eq!(
                 WindowDefinition {
                     window_number: 1,
                     fullscreen: true,
                     dimensions: None,
+                    background: None,
+                    monitor: None,
+                    position: None,
                 },
                 window_list[0]
             );
@@ -708,6 +1434,11 @@ mod tests {
                     video_frame: None,
                     audio_device: None,
                     loop_media: None,
+                    idle_background: None,
+                    transitions: false,
+                    layer: None,
+                    opacity: None,
+                    hidden: false,
                 },
                 channel_list[0]
             );
@@ -715,7 +1446,11 @@ mod tests {
                 MediaCue {
                     channel: 1,
                     uri: "new_video.mp4".to_string(),
-                    loop_media: None
+                    loop_media: None,
+                    fade_in: None,
+                    fade_out: None,
+                    transition: None,
+                    start_at: None,
                 },
                 media_playlist.get(&1).unwrap().media_cue
             );
@@ -725,4 +1460,330 @@ mod tests {
             panic!("Backup was not reloaded.");
         }
     }
+
+    // Test the backup module with a local backup directory
+    #[tokio::test]
+    async fn backup_file() {
+        // Create the interface sender (empty)
+        let (interface_send, _rx) = InterfaceSend::new();
+
+        // Create the backup handler in a new temporary directory
+        let directory = std::env::temp_dir().join(format!(
+            "apollo-backup-test-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let mut backup_handler = BackupHandler::new(
+            String::from("127.0.0.1:27656"),
+            Some(format!("file://{}", directory.display())),
+            interface_send,
+        )
+        .await;
+
+        // Make sure there is no existing backup
+        if backup_handler.reload_backup().await.is_some() {
+            panic!("Backup already existed before beginning of the test.");
+        }
+
+        // Load a channel and a media cue
+        backup_handler
+            .backup_channel(MediaChannel {
+                channel: 2,
+                video_frame: None,
+                audio_device: None,
+                loop_media: None,
+                idle_background: None,
+                transitions: false,
+                layer: None,
+                opacity: None,
+                hidden: false,
+            })
+            .await;
+        backup_handler
+            .backup_media(MediaCue {
+                channel: 2,
+                uri: "audio.mp3".to_string(),
+                loop_media: None,
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
+            })
+            .await;
+
+        // Reload the backup
+        if let Some((window_list, channel_list, media_playlist, _)) =
+            backup_handler.reload_backup().await
+        {
+            assert!(window_list.is_empty());
+            assert_eq!(2, channel_list[0].channel);
+            assert_eq!(
+                "audio.mp3",
+                media_playlist.get(&2).unwrap().media_cue.uri.as_str()
+            );
+
+        // If the backup doesn't exist, throw the error
+        } else {
+            panic!("Backup was not reloaded.");
+        }
+
+        // Make sure the backup is removed when the handler is closed
+        backup_handler.close().await;
+        assert!(!directory.join("apollo_127.0.0.1_27656_media.yaml").exists());
+
+        // Remove the temporary directory
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    // A helper function to create media playback for the checkpoint tests
+    fn playback(uri: &str, seek_to: u64, state: PlaybackState, updated: u64) -> MediaPlayback {
+        MediaPlayback {
+            media_cue: MediaCue {
+                channel: 1,
+                uri: uri.to_string(),
+                loop_media: None,
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
+            },
+            seek_to: Duration::from_millis(seek_to),
+            state,
+            updated,
+            group: None,
+        }
+    }
+
+    // Test the comparison of the media playlist with the last copy
+    #[test]
+    fn checkpoint_unchanged() {
+        // Create the last copy of the playlist
+        let mut previous = MediaPlaylist::default();
+        previous.insert(
+            1,
+            playback("video.mp4", 1000, PlaybackState::Playing, 10_000),
+        );
+        previous.insert(
+            2,
+            playback("audio.mp3", 3000, PlaybackState::Paused, 10_000),
+        );
+
+        // Playing media which advanced with the clock is unchanged
+        let mut current = MediaPlaylist::default();
+        current.insert(
+            1,
+            playback("video.mp4", 2100, PlaybackState::Playing, 10_000),
+        );
+        current.insert(
+            2,
+            playback("audio.mp3", 3000, PlaybackState::Paused, 10_000),
+        );
+        assert!(BackupHandler::is_unchanged(&previous, &current, 11_000));
+
+        // Media which drifted or was seeked has changed
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 12_000));
+
+        // Paused media which moved has changed
+        current.insert(
+            2,
+            playback("audio.mp3", 4000, PlaybackState::Paused, 10_000),
+        );
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 11_000));
+
+        // A change of state has changed
+        current.insert(
+            2,
+            playback("audio.mp3", 3000, PlaybackState::Playing, 10_000),
+        );
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 11_000));
+
+        // New media has changed
+        current.insert(
+            2,
+            playback("other.mp3", 3000, PlaybackState::Paused, 10_000),
+        );
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 11_000));
+
+        // A new or removed channel has changed
+        current.insert(
+            2,
+            playback("audio.mp3", 3000, PlaybackState::Paused, 10_000),
+        );
+        current.insert(3, playback("audio.mp3", 0, PlaybackState::Paused, 10_000));
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 11_000));
+        current.remove(&3);
+        current.remove(&2);
+        assert!(!BackupHandler::is_unchanged(&previous, &current, 11_000));
+    }
+
+    // Test advancing the playing media to the heartbeat
+    #[test]
+    fn checkpoint_advance() {
+        // Create a playlist with playing, paused, and untimed media
+        let mut playlist = MediaPlaylist::default();
+        playlist.insert(
+            1,
+            playback("video.mp4", 1000, PlaybackState::Playing, 10_000),
+        );
+        playlist.insert(
+            2,
+            playback("audio.mp3", 3000, PlaybackState::Paused, 10_000),
+        );
+        playlist.insert(3, playback("image.png", 500, PlaybackState::Playing, 0));
+
+        // Advance the playlist and check the positions
+        BackupHandler::advance_playlist(&mut playlist, 12_500);
+        assert_eq!(Duration::from_millis(3500), playlist[&1].seek_to);
+        assert_eq!(12_500, playlist[&1].updated);
+        assert_eq!(Duration::from_millis(3000), playlist[&2].seek_to);
+        assert_eq!(Duration::from_millis(500), playlist[&3].seek_to);
+    }
+
+    // Test separating the groups of channels from the playlist
+    #[test]
+    fn take_groups() {
+        // Create a playlist with two groups and a single channel
+        let mut playlist = MediaPlaylist::default();
+        for (channel, group) in [(1, Some(7)), (2, None), (3, Some(7)), (4, Some(9))] {
+            let mut media = playback(
+                "video.mp4",
+                1000 * channel as u64,
+                PlaybackState::Playing,
+                0,
+            );
+            media.media_cue.channel = channel;
+            media.group = group.map(|id| PlaybackGroup {
+                id,
+                loop_group: id == 9,
+            });
+            playlist.insert(channel, media);
+        }
+        playlist.get_mut(&1).unwrap().state = PlaybackState::Paused;
+
+        // Only the single channel remains in the playlist
+        let groups = BackupHandler::take_groups(&mut playlist);
+        assert_eq!(vec![2], playlist.keys().copied().collect::<Vec<u32>>());
+
+        // Each group uses the position and state of its first channel
+        assert_eq!(2, groups.len());
+        let (first, seek_to, state) = &groups[0];
+        assert_eq!(
+            vec![1, 3],
+            first
+                .media_cues
+                .iter()
+                .map(|cue| cue.channel)
+                .collect::<Vec<u32>>()
+        );
+        assert!(!first.loop_group);
+        assert_eq!(Duration::from_millis(1000), *seek_to);
+        assert_eq!(PlaybackState::Paused, *state);
+        let (second, seek_to, _) = &groups[1];
+        assert_eq!(4, second.media_cues[0].channel);
+        assert!(second.loop_group);
+        assert_eq!(Duration::from_millis(4000), *seek_to);
+    }
+
+    // Test the checkpoint with a local backup directory
+    #[tokio::test]
+    async fn checkpoint_file() {
+        // Create the interface sender (empty)
+        let (interface_send, _rx) = InterfaceSend::new();
+
+        // Create the backup handler in a new temporary directory
+        let directory = std::env::temp_dir().join(format!(
+            "apollo-checkpoint-test-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let mut backup_handler = BackupHandler::new(
+            String::from("127.0.0.1:27657"),
+            Some(format!("file://{}", directory.display())),
+            interface_send,
+        )
+        .await;
+
+        // Load a media cue with loop media
+        let media_channel = MediaChannel {
+            channel: 1,
+            video_frame: None,
+            audio_device: None,
+            loop_media: None,
+            idle_background: None,
+            transitions: false,
+            layer: None,
+            opacity: None,
+            hidden: false,
+        };
+        backup_handler
+            .backup_media(MediaCue {
+                channel: 1,
+                uri: "video.mp4".to_string(),
+                loop_media: Some("loop.mp4".to_string()),
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
+            })
+            .await;
+        let written = backup_handler.last_playlist.clone();
+
+        // A channel where it is expected is not copied again
+        let status = |uri: &str, position: u64| ChannelStatus {
+            media_channel: media_channel.clone(),
+            current_uri: Some(uri.to_string()),
+            loop_media: Some("loop.mp4".to_string()),
+            state: Some(PlaybackState::Playing),
+            position: Some(position),
+            duration: None,
+        };
+        backup_handler
+            .backup_checkpoint(vec![status("video.mp4", 0)])
+            .await;
+        assert_eq!(written, backup_handler.last_playlist);
+
+        // A channel which jumped is copied again
+        backup_handler
+            .backup_checkpoint(vec![status("video.mp4", 5000)])
+            .await;
+        assert_eq!(
+            Duration::from_millis(5000),
+            backup_handler.last_playlist[&1].seek_to
+        );
+
+        // A channel which moved on to the loop media is replaced
+        backup_handler
+            .backup_checkpoint(vec![status("loop.mp4", 100)])
+            .await;
+        assert_eq!(
+            "loop.mp4",
+            backup_handler.last_playlist[&1].media_cue.uri.as_str()
+        );
+
+        // A channel definition which changed is copied again
+        backup_handler.backup_channel(media_channel.clone()).await;
+        let mut changed = status("loop.mp4", 200);
+        changed.media_channel.layer = Some(0);
+        changed.media_channel.opacity = Some(0.5);
+        backup_handler.backup_checkpoint(vec![changed]).await;
+
+        // Make sure the reloaded backup matches the last copy
+        let (_, channel_list, media_playlist, _) = backup_handler
+            .read_instance("127.0.0.1:27657")
+            .await
+            .unwrap();
+        assert_eq!("loop.mp4", media_playlist[&1].media_cue.uri.as_str());
+        assert_eq!(Some(0), channel_list[0].layer);
+        assert_eq!(Some(0.5), channel_list[0].opacity);
+
+        // Remove the backup and the temporary directory
+        backup_handler.close().await;
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
 }
@@ -0,0 +1,509 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements a background writer for the backup handler. Writes
+//! are queued without waiting for the backup, and a background task copies
+//! them to the backup (in the order they were queued) and reconnects if the
+//! backup goes away. Reads and deletes also wait for the backup on a blocking
+//! thread, so a slow backup never stalls the other tasks.
+
+// Import submodule definitions
+use super::backup_backend::{BackupBackend, open_backend};
+
+// Import standard library features
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Import Tokio features
+use tokio::runtime::Handle;
+use tokio::sync::Notify;
+use tokio::task;
+use tokio::time::sleep;
+
+// Import tracing features
+use tracing::{error, info, warn};
+
+// Import FNV HashSet
+use fnv::FnvHashSet;
+
+// Define module constants
+const RECONNECT_INTERVAL: u64 = 5000; // the time between attempts to reconnect to the backup, in milliseconds
+const MAX_PENDING_WRITES: usize = 10000; // the most writes to keep while waiting for the backup
+
+/// A structure to hold the writes waiting to be copied to the backup, in the
+/// order they were queued
+///
+/// # Note
+///
+/// The writes are copied in order, so the backup never holds newer data for
+/// one key alongside older data for a key which was written before it. A
+/// write replaces the previous write only if both are to the same key (as no
+/// other write came between them).
+///
+/// If too many writes are waiting (while the backup is not available), only
+/// the newest write for each key is kept, in the order of those writes.
+///
+#[derive(Clone, Debug, Default)]
+struct PendingWrites {
+    writes: VecDeque<(String, String)>, // the key and data of each write, from the oldest to the newest
+}
+
+// Implement key features of the pending writes
+impl PendingWrites {
+    /// A method to add a write to the end of the queue
+    ///
+    fn push(&mut self, key: String, data: String) {
+        // Replace the previous write if it was to the same key
+        match self.writes.back_mut() {
+            Some((last_key, last_data)) if *last_key == key => *last_data = data,
+            _ => self.writes.push_back((key, data)),
+        }
+
+        // Keep only the newest data for each key if the queue is too long
+        if self.writes.len() > MAX_PENDING_WRITES {
+            warn!("Too many writes waiting for backup server. Keeping only the newest data.");
+            self.compact();
+        }
+    }
+
+    /// A method to return the newest data for a key, if any
+    ///
+    fn get(&self, key: &str) -> Option<String> {
+        self.writes
+            .iter()
+            .rev()
+            .find(|(write_key, _)| write_key == key)
+            .map(|(_, data)| data.clone())
+    }
+
+    /// A method to return unwritten data to the front of the queue (ahead of
+    /// any newer writes)
+    ///
+    fn requeue(&mut self, mut unwritten: PendingWrites) {
+        unwritten.writes.append(&mut self.writes);
+        *self = unwritten;
+        if self.writes.len() > MAX_PENDING_WRITES {
+            self.compact();
+        }
+    }
+
+    /// A method to remove every write
+    ///
+    fn clear(&mut self) {
+        self.writes.clear();
+    }
+
+    // A helper method to keep only the newest write for each key
+    fn compact(&mut self) {
+        let mut keys = FnvHashSet::default();
+        let mut writes: Vec<(String, String)> = self
+            .writes
+            .drain(..)
+            .rev()
+            .filter(|(key, _)| keys.insert(key.clone()))
+            .collect();
+        writes.reverse();
+        self.writes = writes.into();
+    }
+}
+
+/// A type to share the backup between the writer and the background task
+///
+type SharedBackend = Arc<Mutex<Option<Box<dyn BackupBackend>>>>;
+
+/// A structure to read from the backup on any task, including data which is
+/// waiting to be written
+///
+#[derive(Clone)]
+pub struct BackupReader {
+    pending: Arc<Mutex<PendingWrites>>, // the data waiting to be written
+    writing: Arc<Mutex<PendingWrites>>, // the data currently being written
+    backend: SharedBackend,             // the backup, if currently connected
+}
+
+// Implement key features of the backup reader
+impl BackupReader {
+    /// A method to read the data for several keys, including any data which
+    /// has not been written yet. Otherwise, this method waits for the backup
+    /// (without blocking other tasks). The data is returned in the same order
+    /// as the keys.
+    ///
+    pub async fn read_all(&self, keys: Vec<String>) -> Vec<Option<String>> {
+        // Check for data waiting to be written (or currently being written)
+        let mut data: Vec<Option<String>> = keys
+            .iter()
+            .map(|key| {
+                [&self.pending, &self.writing]
+                    .iter()
+                    .find_map(|queue| queue.lock().ok().and_then(|queue| queue.get(key)))
+            })
+            .collect();
+
+        // If every key was found, return the data
+        if data.iter().all(Option::is_some) {
+            return data;
+        }
+
+        // Otherwise, read the remaining keys from the backup (together on one blocking thread)
+        let backend = self.backend.clone();
+        let missing: Vec<(usize, String)> = keys
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| data[*index].is_none())
+            .collect();
+        let found = task::spawn_blocking(move || match backend.lock() {
+            Ok(mut backend) => missing
+                .into_iter()
+                .map(|(index, key)| {
+                    (
+                        index,
+                        backend.as_mut().and_then(|backend| backend.get(&key)),
+                    )
+                })
+                .collect(),
+            _ => Vec::new(),
+        })
+        .await
+        .unwrap_or_default();
+
+        // Combine the data and return it
+        for (index, value) in found {
+            data[index] = value;
+        }
+        data
+    }
+
+    /// A method to read the data for a key, including any data which has not
+    /// been written yet. Otherwise, this method waits for the backup (without
+    /// blocking other tasks).
+    ///
+    pub async fn read(&self, key: &str) -> Option<String> {
+        self.read_all(vec![key.to_string()]).await.pop().flatten()
+    }
+}
+
+/// A structure to queue writes to the backup and copy them in the background
+///
+pub struct BackupWriter {
+    pending: Arc<Mutex<PendingWrites>>, // the data waiting to be written
+    writing: Arc<Mutex<PendingWrites>>, // the data currently being written
+    notify: Arc<Notify>,                // the signal that new data is waiting
+    backend: SharedBackend,             // the backup, if currently connected
+    is_closed: Arc<AtomicBool>,         // a flag to indicate that the writer is closed
+}
+
+// Implement key features of the backup writer
+impl BackupWriter {
+    /// A function to connect to the backup location and start the background
+    /// task. If the connection fails, the background task keeps trying to
+    /// connect.
+    ///
+    pub fn new(location: String) -> BackupWriter {
+        // Try to connect to the backup
+        let backend = match open_backend(location.as_str()) {
+            Ok(backend) => Some(backend),
+            Err(error) => {
+                error!(
+                    "Unable to connect to backup server: {}: {}. Retrying in the background.",
+                    location, error
+                );
+                None
+            }
+        };
+
+        // Create the writer
+        let writer = BackupWriter {
+            pending: Arc::new(Mutex::new(PendingWrites::default())),
+            writing: Arc::new(Mutex::new(PendingWrites::default())),
+            notify: Arc::new(Notify::new()),
+            backend: Arc::new(Mutex::new(backend)),
+            is_closed: Arc::new(AtomicBool::new(false)),
+        };
+
+        // Start the background task
+        tokio::spawn(BackupWriter::run(
+            location,
+            writer.pending.clone(),
+            writer.writing.clone(),
+            writer.notify.clone(),
+            writer.backend.clone(),
+            writer.is_closed.clone(),
+        ));
+
+        // Return the writer
+        writer
+    }
+
+    /// A method to queue data to write to the backup. This method does not
+    /// wait for the data to be written.
+    ///
+    pub fn write(&self, key: String, data: String) {
+        // Queue the data
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.push(key, data);
+        }
+
+        // Wake the background task
+        self.notify.notify_one();
+    }
+
+    /// A method to read the data for a key, including any data which has not
+    /// been written yet. Otherwise, this method waits for the backup (without
+    /// blocking other tasks).
+    ///
+    pub async fn read(&self, key: &str) -> Option<String> {
+        self.reader().read(key).await
+    }
+
+    /// A method to return a reader for the backup which can be moved to
+    /// another task
+    ///
+    pub fn reader(&self) -> BackupReader {
+        BackupReader {
+            pending: self.pending.clone(),
+            writing: self.writing.clone(),
+            backend: self.backend.clone(),
+        }
+    }
+
+    /// A method to stop the background task and delete the provided keys from
+    /// the backup (without blocking other tasks). Any data which has not been
+    /// written is discarded.
+    ///
+    pub async fn close(&self, keys: Vec<String>) {
+        // Stop the background task
+        self.stop();
+
+        // Delete the keys from the backup and disconnect
+        let backend = self.backend.clone();
+        task::spawn_blocking(move || BackupWriter::delete_all(backend, keys))
+            .await
+            .unwrap_or(());
+    }
+
+    /// A method to stop the background task and delete the provided keys from
+    /// the backup without waiting for the backup. Any data which has not been
+    /// written is discarded.
+    ///
+    pub fn close_in_background(&self, keys: Vec<String>) {
+        // Stop the background task
+        self.stop();
+
+        // Delete the keys on a blocking thread, if possible (otherwise, delete them now)
+        let backend = self.backend.clone();
+        match Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || BackupWriter::delete_all(backend, keys));
+            }
+            Err(_) => BackupWriter::delete_all(backend, keys),
+        }
+    }
+
+    // A helper method to stop the background task and discard any waiting data
+    fn stop(&self) {
+        self.is_closed.store(true, Ordering::SeqCst);
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+        self.notify.notify_one();
+    }
+
+    // A helper function to delete the provided keys from the backup and disconnect
+    fn delete_all(backend: SharedBackend, keys: Vec<String>) {
+        if let Ok(mut backend) = backend.lock()
+            && let Some(mut backend) = backend.take()
+        {
+            for key in keys {
+                backend.delete(&key);
+            }
+        }
+    }
+
+    // A helper function to copy waiting data to the backup until closed
+    async fn run(
+        location: String,
+        pending: Arc<Mutex<PendingWrites>>,
+        writing: Arc<Mutex<PendingWrites>>,
+        notify: Arc<Notify>,
+        backend: SharedBackend,
+        is_closed: Arc<AtomicBool>,
+    ) {
+        // Note if the backup is currently connected
+        let mut is_connected = backend.lock().is_ok_and(|backend| backend.is_some());
+
+        // Loop until the writer is closed
+        loop {
+            // Wait for new data (or wait to reconnect)
+            if is_connected {
+                notify.notified().await;
+            } else {
+                sleep(Duration::from_millis(RECONNECT_INTERVAL)).await;
+            }
+
+            // Stop if the writer is closed
+            if is_closed.load(Ordering::SeqCst) {
+                return;
+            }
+
+            // Take all the waiting data (still available to read while it is written)
+            let writes = match pending.lock() {
+                Ok(mut pending) => mem::take(&mut *pending),
+                _ => PendingWrites::default(),
+            };
+            if let Ok(mut writing) = writing.lock() {
+                *writing = writes.clone();
+            }
+
+            // Copy the data to the backup without blocking other tasks
+            let location_clone = location.clone();
+            let backend_clone = backend.clone();
+            let is_closed_clone = is_closed.clone();
+            let result = task::spawn_blocking(move || {
+                BackupWriter::write_all(location_clone, backend_clone, is_closed_clone, writes)
+            })
+            .await;
+
+            // Check the result
+            match result {
+                // If successful, note the change in connection
+                Ok(Ok(())) => {
+                    if !is_connected {
+                        info!("Reconnected to backup server.");
+                        is_connected = true;
+                    }
+                }
+
+                // If the backup failed, requeue the data ahead of any newer data
+                Ok(Err(unwritten)) => {
+                    if let Ok(mut pending) = pending.lock() {
+                        pending.requeue(unwritten);
+                    }
+
+                    // Alert the user only when the connection is first lost
+                    if is_connected {
+                        error!("Lost connection to backup server. Retrying in the background.");
+                        is_connected = false;
+                    }
+                }
+
+                // If the task failed, trace the error
+                Err(error) => error!("Unable to write to backup server: {}.", error),
+            }
+
+            // Clear the data which was being written (any unwritten data was requeued)
+            if let Ok(mut writing) = writing.lock() {
+                writing.clear();
+            }
+        }
+    }
+
+    // A helper function to write data to the backup (reconnecting if
+    // necessary) and return any data that was not written
+    fn write_all(
+        location: String,
+        backend: SharedBackend,
+        is_closed: Arc<AtomicBool>,
+        mut writes: PendingWrites,
+    ) -> Result<(), PendingWrites> {
+        // Get a lock on the backup
+        let mut backend = match backend.lock() {
+            Ok(backend) => backend,
+            _ => return Err(writes),
+        };
+
+        // Discard the data if the writer was closed
+        if is_closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        // If not connected, try to reconnect
+        if backend.is_none() {
+            match open_backend(location.as_str()) {
+                Ok(new_backend) => *backend = Some(new_backend),
+                _ => return Err(writes),
+            }
+        }
+
+        // Write the data in the order it was queued
+        while let Some((key, data)) = writes.writes.front() {
+            // Try to write the data
+            if let Some(current) = backend.as_mut()
+                && current.set(key, data).is_err()
+            {
+                // Disconnect and return the remaining data
+                *backend = None;
+                return Err(writes);
+            }
+
+            // Remove the written data
+            writes.writes.pop_front();
+        }
+
+        // Indicate success
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A helper function to list the waiting writes as key=data
+    fn list(pending: &PendingWrites) -> Vec<String> {
+        pending
+            .writes
+            .iter()
+            .map(|(key, data)| format!("{}={}", key, data))
+            .collect()
+    }
+
+    // Test the order of the writes waiting for the backup
+    #[test]
+    fn pending_writes() {
+        // Writes stay in order, and only replace a write to the same key just before
+        let mut pending = PendingWrites::default();
+        for (key, data) in [("a", "1"), ("a", "2"), ("b", "1"), ("a", "3")] {
+            pending.push(key.to_string(), data.to_string());
+        }
+        assert_eq!(list(&pending), vec!["a=2", "b=1", "a=3"]);
+        assert_eq!(pending.get("a"), Some("3".to_string()));
+        assert_eq!(pending.get("c"), None);
+
+        // Unwritten data returns ahead of any newer writes
+        let mut unwritten = PendingWrites::default();
+        unwritten.push("c".to_string(), "1".to_string());
+        pending.requeue(unwritten);
+        assert_eq!(list(&pending), vec!["c=1", "a=2", "b=1", "a=3"]);
+
+        // Compacting keeps the newest write for each key, in order
+        pending.compact();
+        assert_eq!(list(&pending), vec!["c=1", "b=1", "a=3"]);
+
+        // Too many writes are compacted automatically
+        for index in 0..MAX_PENDING_WRITES {
+            let key = if index % 2 == 0 { "a" } else { "b" };
+            pending.push(key.to_string(), index.to_string());
+        }
+        assert!(pending.writes.len() <= MAX_PENDING_WRITES);
+        assert_eq!(pending.get("a"), Some((MAX_PENDING_WRITES - 2).to_string()));
+        assert_eq!(pending.get("b"), Some((MAX_PENDING_WRITES - 1).to_string()));
+        assert_eq!(pending.get("c"), Some("1".to_string()));
+    }
+}
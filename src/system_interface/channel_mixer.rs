@@ -0,0 +1,875 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to mix the decks of a media channel into a single output. Each
+//! deck is a separate playbin which passes its audio and video to the mixer,
+//! allowing the channel to blend the outgoing and incoming media during a
+//! transition. Channels without transitions have a single deck which plays
+//! directly to the outputs instead.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import other structures into this module
+use super::network_clock::OutputClock;
+
+// Import standard library features
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Import Gstreamer Library
+use gst::prelude::*;
+use gstreamer as gst;
+
+// Import the tracing features
+use tracing::error;
+
+// Import anyhow features
+use anyhow::{Context, Result};
+
+// Define module constants
+pub const DECK_COUNT: usize = 2; // the number of decks for each channel
+const VIDEO_SINKS: [&str; 3] = ["glimagesink", "xvimagesink", "ximagesink"]; // the video sinks which support an overlay, in order of preference
+const WIDGET_SINK: &str = "gtksink"; // the video sink which draws into a widget (for displays without an overlay)
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(0); // the counter to give the outputs of every channel definition a unique name
+
+/// A helper structure to hold the video portion of the mixer
+///
+#[derive(Debug)]
+struct VideoMixer {
+    bin: gst::Bin,              // the bin which mixes the video from each deck
+    caps_filter: gst::Element,  // the filter which sets the size of the mixed video
+    pads: Vec<gst::Pad>,        // the compositor pads for each deck
+    sink: Option<gst::Element>, // the video sink which displays the mixed video, unless sent to the window compositor
+}
+
+/// A helper structure to hold the source of the idle background and the
+/// compositor pad it is connected to
+///
+#[derive(Debug)]
+struct IdleSource {
+    bin: gst::Bin, // the bin which creates the background
+    pad: gst::Pad, // the compositor pad for the background
+}
+
+/// A helper structure to hold the current state of the idle layer
+///
+#[derive(Debug)]
+struct IdleState {
+    source: Option<IdleSource>, // the source of the background, if specified
+    is_idle: bool,              // a flag to indicate that no media is playing
+    level: f64,  // the opacity of the layer and the video it replaces, from 0.0 to 1.0
+    alpha: f64,  // the opacity of the background itself (from the alpha of a color)
+    zorder: u32, // the stacking order of the layer in the compositor
+    left: i32,   // the horizontal position of the video frame
+    top: i32,    // the vertical position of the video frame
+    width: i32,  // the width of the video frame
+    height: i32, // the height of the video frame
+}
+
+/// A structure to show a color or image in a window compositor in place of
+/// a channel when no media is playing (or behind every channel). While the
+/// background is shown, the video of the channel is hidden, so any alpha in
+/// the background blends with the rest of the window. The layer can be
+/// shared with the playback callbacks.
+///
+#[derive(Clone, Debug)]
+pub struct IdleLayer {
+    bin: gst::Bin,                // the output pipeline (or bin) for the compositor
+    compositor: gst::Element,     // the compositor which mixes the video
+    video_pad: Option<gst::Pad>, // the compositor pad for the video which the layer replaces, if any
+    state: Arc<Mutex<IdleState>>, // the current state of the layer
+}
+
+// Implement key functionality for the idle layer
+impl IdleLayer {
+    /// A function to create a new idle layer for the compositor, without
+    /// a background
+    ///
+    pub fn new(
+        bin: &gst::Bin,
+        compositor: &gst::Element,
+        video_pad: Option<gst::Pad>,
+        zorder: u32,
+        is_idle: bool,
+    ) -> IdleLayer {
+        IdleLayer {
+            bin: bin.clone(),
+            compositor: compositor.clone(),
+            video_pad,
+            state: Arc::new(Mutex::new(IdleState {
+                source: None,
+                is_idle,
+                level: 1.0,
+                alpha: 1.0,
+                zorder,
+                left: 0,
+                top: 0,
+                width: 0,
+                height: 0,
+            })),
+        }
+    }
+
+    /// A method to show or hide the idle background
+    ///
+    pub fn set_idle(&self, is_idle: bool) {
+        // Save the new setting and update the layer
+        if let Ok(mut state) = self.state.lock() {
+            state.is_idle = is_idle;
+            self.apply_levels(&state);
+        }
+    }
+
+    /// A method to change the opacity of the layer (and the video it
+    /// replaces), from 0.0 (hidden) to 1.0 (opaque)
+    ///
+    pub fn set_level(&self, level: f64) {
+        // Save the new level and update the layer
+        if let Ok(mut state) = self.state.lock() {
+            state.level = level;
+            self.apply_levels(&state);
+        }
+    }
+
+    /// A method to return the current opacity of the layer
+    ///
+    pub fn level(&self) -> f64 {
+        match self.state.lock() {
+            Ok(state) => state.level,
+            _ => 0.0,
+        }
+    }
+
+    /// A method to change the stacking order of the idle background
+    ///
+    pub fn set_zorder(&self, zorder: u32) {
+        // Save the new order and update the background, if any
+        if let Ok(mut state) = self.state.lock() {
+            state.zorder = zorder;
+            if let Some(source) = state.source.as_ref() {
+                source.pad.set_property("zorder", zorder);
+            }
+        }
+    }
+
+    /// A method to replace the idle background. If no background is
+    /// specified, the video is always shown instead (holding the last frame
+    /// of the media).
+    ///
+    pub fn set_background(&self, background: Option<Background>) -> Result<()> {
+        // Get access to the current state
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("Unable to change idle background."))?;
+
+        // Remove the previous background, if any
+        if let Some(source) = state.source.take() {
+            if let Some(src_pad) = source.bin.static_pad("src") {
+                src_pad.unlink(&source.pad).unwrap_or(());
+            }
+            source
+                .bin
+                .set_state(gst::State::Null)
+                .unwrap_or(gst::StateChangeSuccess::Success);
+            self.bin.remove(&source.bin).unwrap_or(());
+            self.compositor.release_request_pad(&source.pad);
+        }
+
+        // If there is no new background, show the video and stop here
+        let background = match background {
+            Some(background) => background,
+            None => {
+                self.apply_levels(&state);
+                return Ok(());
+            }
+        };
+
+        // Create the new background (scaled to fit, with any extra space in black)
+        let (bin, alpha) = match background {
+            // A solid color (with the alpha applied to the compositor pad)
+            Background::Color {
+                red,
+                green,
+                blue,
+                alpha,
+            } => {
+                let color = u32::from_be_bytes([u8::MAX, red, green, blue]);
+                let bin = gst::parse_bin_from_description(
+                    &format!("videotestsrc is-live=true pattern=solid-color foreground-color={} ! videoconvert ! videoscale ! capsfilter name=size", color),
+                    true,
+                )
+                .context("Unable to create idle background.")?;
+                (bin, alpha as f64 / u8::MAX as f64)
+            }
+
+            // A still image
+            Background::Image { path } => {
+                // Make sure the image exists
+                if !Path::new(&path).is_file() {
+                    return Err(anyhow!(
+                        "Unable to create idle background: Image not found."
+                    ));
+                }
+
+                // Create the bin and set the image location
+                let bin = gst::parse_bin_from_description(
+                    "filesrc name=image ! decodebin ! imagefreeze is-live=true ! videoconvert ! videoscale ! capsfilter name=size",
+                    true,
+                )
+                .context("Unable to create idle background.")?;
+                if let Some(image) = bin.by_name("image") {
+                    image.set_property("location", &path);
+                }
+                (bin, 1.0)
+            }
+        };
+
+        // Set the size of the background
+        IdleLayer::apply_size(&bin, state.width, state.height);
+
+        // Connect the background to the compositor at the layer position
+        self.bin
+            .add(&bin)
+            .context("Unable to create idle background.")?;
+        let pad = ChannelMixer::link_to_mixer(bin.upcast_ref(), &self.compositor)?;
+        pad.set_property("zorder", state.zorder);
+        pad.set_property("xpos", state.left);
+        pad.set_property("ypos", state.top);
+        bin.sync_state_with_parent()
+            .context("Unable to start idle background.")?;
+
+        // Save the new background and show it, if idle
+        state.alpha = alpha;
+        state.source = Some(IdleSource { bin, pad });
+        self.apply_levels(&state);
+        Ok(())
+    }
+
+    /// A method to change the location and size of the idle background
+    ///
+    pub fn place(&self, left: i32, top: i32, width: i32, height: i32) {
+        // Save the new location and update the background, if any
+        if let Ok(mut state) = self.state.lock() {
+            state.left = left;
+            state.top = top;
+            state.width = width;
+            state.height = height;
+            if let Some(source) = state.source.as_ref() {
+                source.pad.set_property("xpos", left);
+                source.pad.set_property("ypos", top);
+                IdleLayer::apply_size(&source.bin, width, height);
+            }
+        }
+    }
+
+    // A helper method to show the background (in place of the video) or the
+    // video, based on the current state
+    fn apply_levels(&self, state: &IdleState) {
+        // Show the background, if idle
+        let is_shown = state.is_idle && state.source.is_some();
+        if let Some(source) = state.source.as_ref() {
+            source.pad.set_property(
+                "alpha",
+                if state.is_idle {
+                    state.level * state.alpha
+                } else {
+                    0.0
+                },
+            );
+        }
+
+        // Hide the video while the background is shown
+        if let Some(pad) = self.video_pad.as_ref() {
+            pad.set_property("alpha", if is_shown { 0.0 } else { state.level });
+        }
+    }
+
+    // A helper function to set the size of a background bin (keeping any
+    // transparency in the image)
+    fn apply_size(bin: &gst::Bin, width: i32, height: i32) {
+        if let Some(size) = bin.by_name("size") {
+            let caps = gst::Caps::builder("video/x-raw")
+                .field("format", "AYUV")
+                .field("width", width.max(1))
+                .field("height", height.max(1))
+                .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+                .build();
+            size.set_property("caps", &caps);
+        }
+    }
+}
+
+/// A structure to hold the output pipeline for the decks of a single channel
+///
+#[derive(Debug)]
+pub struct ChannelMixer {
+    name: String,                    // the unique name of this mixer (shared by the decks)
+    pipeline: gst::Pipeline,         // the output pipeline for this channel
+    video_mixer: Option<VideoMixer>, // the video mixer, if the channel has a video frame
+    audio_pads: Vec<gst::Pad>,       // the audio mixer pads for each deck
+    video_output: VideoOutput,       // the way video is shown on the display
+    _watch_guard: gst::bus::BusWatchGuard, // the guard for the watch function on the output bus
+}
+
+// Implement key functionality for the channel mixer
+impl ChannelMixer {
+    /// A function to create a new channel mixer. The output is not started
+    /// until start() is called.
+    ///
+    /// # Note
+    ///
+    /// The mixer is only created for channels which allow transitions. The
+    /// mixer output plays continuously, so other channels play directly to
+    /// the outputs instead.
+    ///
+    /// The video is only mixed if the channel has a video frame. Otherwise,
+    /// each deck displays video in a separate window generated by gstreamer.
+    /// If the video is composited, the mixed video is not part of the output
+    /// pipeline and must be added to the window compositor with video_bin().
+    ///
+    /// If the mixer is headless, the video is still mixed (so that transitions
+    /// behave the same) but is discarded instead of displayed.
+    ///
+    /// The name should come from unique_name() so that the decks of this
+    /// mixer never connect to the outputs of an earlier definition of the
+    /// channel.
+    ///
+    /// The output plays on the clock and base time shared by every output on
+    /// this instance, so that channels which play together stay together.
+    ///
+    /// If the video is shown in the user interface, the output should only be
+    /// started once the video sink is embedded in the window. Otherwise, the
+    /// sink opens a separate window of its own.
+    ///
+    pub fn new(
+        media_channel: &MediaChannel,
+        video_output: VideoOutput,
+        name: String,
+        output_clock: &OutputClock,
+    ) -> Result<ChannelMixer> {
+        // Create the output pipeline on the shared clock
+        let pipeline = gst::Pipeline::with_name(&name);
+        output_clock.apply(&pipeline);
+
+        // Create the audio mixer and the audio output
+        let audio_mixer = ChannelMixer::make_element("audiomixer")?;
+        let audio_convert = ChannelMixer::make_element("audioconvert")?;
+        let audio_resample = ChannelMixer::make_element("audioresample")?;
+        let audio_sink = match ChannelMixer::make_audio_sink(&media_channel.audio_device)? {
+            Some(sink) => sink,
+            None => ChannelMixer::make_element("autoaudiosink")?,
+        };
+        pipeline
+            .add_many([&audio_mixer, &audio_convert, &audio_resample, &audio_sink])
+            .context("Unable to create audio mixer.")?;
+        gst::Element::link_many([&audio_mixer, &audio_convert, &audio_resample, &audio_sink])
+            .context("Unable to create audio mixer.")?;
+
+        // Connect the audio from each deck
+        let mut audio_pads = Vec::new();
+        for deck in 0..DECK_COUNT {
+            // Create the audio source for the deck
+            let source = ChannelMixer::make_element("interaudiosrc")?;
+            source.set_property("channel", ChannelMixer::deck_name(&name, deck));
+            let convert = ChannelMixer::make_element("audioconvert")?;
+            pipeline
+                .add_many([&source, &convert])
+                .context("Unable to create audio mixer.")?;
+            source
+                .link(&convert)
+                .context("Unable to create audio mixer.")?;
+
+            // Connect the source to the mixer
+            audio_pads.push(ChannelMixer::link_to_mixer(&convert, &audio_mixer)?);
+        }
+
+        // If a video frame was specified, create the video mixer
+        let mut video_mixer = None;
+        if media_channel.video_frame.is_some() {
+            // Create the bin for the compositor (with the mixed video as the output)
+            let bin = gst::Bin::with_name(&format!("{}-video", name));
+            let compositor = ChannelMixer::make_element("compositor")?;
+            compositor.set_property_from_str("background", "black");
+            let caps_filter = ChannelMixer::make_element("capsfilter")?;
+            bin.add_many([&compositor, &caps_filter])
+                .context("Unable to create video mixer.")?;
+            compositor
+                .link(&caps_filter)
+                .context("Unable to create video mixer.")?;
+            let output = caps_filter
+                .static_pad("src")
+                .ok_or(anyhow!("Unable to create video mixer."))?;
+            let ghost_pad =
+                gst::GhostPad::with_target(&output).context("Unable to create video mixer.")?;
+            bin.add_pad(&ghost_pad)
+                .context("Unable to create video mixer.")?;
+
+            // Connect the video from each deck
+            let mut pads = Vec::new();
+            for deck in 0..DECK_COUNT {
+                // Create the video source for the deck (hold the last frame when paused)
+                let source = ChannelMixer::make_element("intervideosrc")?;
+                source.set_property("channel", ChannelMixer::deck_name(&name, deck));
+                source.set_property("timeout", u64::MAX);
+                let convert = ChannelMixer::make_element("videoconvert")?;
+                bin.add_many([&source, &convert])
+                    .context("Unable to create video mixer.")?;
+                source
+                    .link(&convert)
+                    .context("Unable to create video mixer.")?;
+
+                // Connect the source to the compositor, preserving the aspect ratio
+                let pad = ChannelMixer::link_to_mixer(&convert, &compositor)?;
+                pad.set_property_from_str("sizing-policy", "keep-aspect-ratio");
+                pads.push(pad);
+            }
+
+            // Show the mixed video from the output pipeline (unless the
+            // window compositor mixes it directly into the window pipeline)
+            let sink = match video_output {
+                VideoOutput::Composited => None,
+                _ => {
+                    let video_convert = ChannelMixer::make_element("videoconvert")?;
+                    let sink = ChannelMixer::make_video_sink(video_output, &name)?;
+                    pipeline
+                        .add_many([bin.upcast_ref::<gst::Element>(), &video_convert, &sink])
+                        .context("Unable to create video mixer.")?;
+                    gst::Element::link_many([
+                        bin.upcast_ref::<gst::Element>(),
+                        &video_convert,
+                        &sink,
+                    ])
+                    .context("Unable to create video mixer.")?;
+                    Some(sink)
+                }
+            };
+
+            // Save the video mixer
+            video_mixer = Some(VideoMixer {
+                bin,
+                caps_filter,
+                pads,
+                sink,
+            });
+        }
+
+        // Create the watch to report any errors
+        let _watch_guard = ChannelMixer::create_error_callback(
+            format!("channel {} output", media_channel.channel),
+            &pipeline,
+        )?;
+
+        // Compose the mixer
+        let mixer = ChannelMixer {
+            name,
+            pipeline,
+            video_mixer,
+            audio_pads,
+            video_output,
+            _watch_guard,
+        };
+
+        // Set the size of the video and silence every deck
+        if let Some(video_frame) = media_channel.video_frame.as_ref() {
+            mixer.resize(video_frame.width, video_frame.height);
+        }
+        for deck in 0..DECK_COUNT {
+            mixer.set_level(deck, 0.0);
+        }
+
+        // Return the completed mixer
+        Ok(mixer)
+    }
+
+    /// A method to start the output of this mixer
+    ///
+    pub fn start(&self) -> Result<()> {
+        self.pipeline
+            .set_state(gst::State::Playing)
+            .context("Unable to start channel output.")?;
+        Ok(())
+    }
+
+    /// A method to connect a deck to this mixer
+    ///
+    pub fn connect_deck(&self, playbin: &gst::Element, deck: usize) -> Result<()> {
+        // Send the audio to the mixer
+        let audio_sink = ChannelMixer::make_element("interaudiosink")?;
+        audio_sink.set_property("channel", ChannelMixer::deck_name(&self.name, deck));
+        playbin.set_property("audio-sink", &audio_sink);
+
+        // If there is a video mixer, send the video to the mixer
+        if self.video_mixer.is_some() {
+            let video_sink = ChannelMixer::make_element("intervideosink")?;
+            video_sink.set_property("channel", ChannelMixer::deck_name(&self.name, deck));
+            playbin.set_property("video-sink", &video_sink);
+
+        // Otherwise, if headless, discard the video from each deck
+        } else if self.video_output == VideoOutput::Headless {
+            let video_sink = ChannelMixer::make_element("fakesink")?;
+            video_sink.set_property("sync", true);
+            playbin.set_property("video-sink", &video_sink);
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to connect the only deck of a channel without a mixer
+    /// directly to the outputs. If no audio device or video sink is
+    /// specified, the playbin chooses the default.
+    ///
+    pub fn connect_direct(
+        playbin: &gst::Element,
+        audio_device: &Option<AudioDevice>,
+        video_sink: Option<&gst::Element>,
+    ) -> Result<()> {
+        // Send the audio to the audio device, if specified
+        if let Some(audio_sink) = ChannelMixer::make_audio_sink(audio_device)? {
+            playbin.set_property("audio-sink", &audio_sink);
+        }
+
+        // Send the video to the video sink, if specified
+        if let Some(video_sink) = video_sink {
+            playbin.set_property("video-sink", video_sink);
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to create the video sink for a channel without a mixer.
+    /// If the channel has no video frame, any window creation is left to
+    /// gstreamer (or the video is discarded, if headless).
+    ///
+    pub fn make_direct_sink(
+        media_channel: &MediaChannel,
+        video_output: VideoOutput,
+        name: &str,
+    ) -> Result<Option<gst::Element>> {
+        // Match the video frame and the video output
+        match (media_channel.video_frame.as_ref(), video_output) {
+            // Create the sink for the video frame (or discard the video if headless)
+            (Some(_), _) | (None, VideoOutput::Headless) => {
+                Ok(Some(ChannelMixer::make_video_sink(video_output, name)?))
+            }
+
+            // Otherwise, leave the window to gstreamer
+            (None, _) => Ok(None),
+        }
+    }
+
+    /// A method to return the video sink for the mixed video, if it exists
+    ///
+    pub fn video_sink(&self) -> Option<gst::Element> {
+        self.video_mixer
+            .as_ref()
+            .and_then(|mixer| mixer.sink.clone())
+    }
+
+    /// A method to return the bin which mixes the video, if the mixed video
+    /// is sent to the window compositor (instead of shown by this mixer)
+    ///
+    pub fn video_bin(&self) -> Option<gst::Bin> {
+        self.video_mixer
+            .as_ref()
+            .filter(|mixer| mixer.sink.is_none())
+            .map(|mixer| mixer.bin.clone())
+    }
+
+    /// A method to change the size of the mixed video
+    ///
+    pub fn resize(&self, width: i32, height: i32) {
+        // If there is a video mixer
+        if let Some(mixer) = self.video_mixer.as_ref() {
+            // Scale every deck to the new size
+            for pad in mixer.pads.iter() {
+                pad.set_property("width", width);
+                pad.set_property("height", height);
+            }
+
+            // Set the size of the mixed video
+            let caps = gst::Caps::builder("video/x-raw")
+                .field("width", width)
+                .field("height", height)
+                .build();
+            mixer.caps_filter.set_property("caps", &caps);
+        }
+    }
+
+    /// A method to change the level (both opacity and volume) of a deck,
+    /// from 0.0 (hidden and silent) to 1.0 (fully visible and audible)
+    ///
+    pub fn set_level(&self, deck: usize, level: f64) {
+        // Change the opacity of the deck
+        if let Some(pad) = self
+            .video_mixer
+            .as_ref()
+            .and_then(|mixer| mixer.pads.get(deck))
+        {
+            pad.set_property("alpha", level);
+        }
+
+        // Change the volume of the deck
+        if let Some(pad) = self.audio_pads.get(deck) {
+            pad.set_property("volume", level);
+        }
+    }
+
+    /// A method to return the current level of a deck
+    ///
+    pub fn level(&self, deck: usize) -> f64 {
+        match self.audio_pads.get(deck) {
+            Some(pad) => pad.property::<f64>("volume"),
+            None => 0.0,
+        }
+    }
+
+    /// A function to compose a new unique name for the outputs of a channel.
+    /// Each definition of the channel receives a different name, so that
+    /// background tasks which still hold an earlier mixer never share its
+    /// connections with the new one.
+    ///
+    pub fn unique_name(channel: u32) -> String {
+        format!(
+            "apollo-{}-{}",
+            channel,
+            NEXT_INSTANCE.fetch_add(1, Ordering::SeqCst)
+        )
+    }
+
+    /// A function to compose the name which connects the mixed video of a
+    /// channel (with the provided unique name) to the compositor for the window
+    ///
+    pub fn output_name(name: &str) -> String {
+        format!("{}-output", name)
+    }
+
+    // A helper function to compose the name which connects a deck to the mixer
+    fn deck_name(name: &str, deck: usize) -> String {
+        format!("{}-{}", name, deck)
+    }
+
+    /// A helper function to create a new element
+    ///
+    pub fn make_element(factory: &str) -> Result<gst::Element> {
+        gst::ElementFactory::make_with_name(factory, None)
+            .context(format!("Unable to create {}.", factory))
+    }
+
+    /// A helper function to create the audio sink for the audio device, if
+    /// specified
+    ///
+    pub fn make_audio_sink(audio_device: &Option<AudioDevice>) -> Result<Option<gst::Element>> {
+        match audio_device {
+            // An ALSA device
+            Some(AudioDevice::Alsa { device_name }) => {
+                let sink = ChannelMixer::make_element("alsasink")?;
+                sink.set_property("device", device_name);
+                Ok(Some(sink))
+            }
+
+            // A Pulse Audio device
+            Some(AudioDevice::Pulse { device_name }) => {
+                let sink = ChannelMixer::make_element("pulsesink")?;
+                sink.set_property("device", device_name);
+                Ok(Some(sink))
+            }
+
+            // Otherwise, use the system default
+            None => Ok(None),
+        }
+    }
+
+    /// A helper function to create the first available video sink with an
+    /// overlay, a sink which draws into a widget, a sink which sends the video
+    /// to the window compositor, or a sink which discards the video (if headless)
+    ///
+    pub fn make_video_sink(video_output: VideoOutput, name: &str) -> Result<gst::Element> {
+        match video_output {
+            // Find the first available sink with an overlay
+            VideoOutput::Overlay => VIDEO_SINKS
+                .iter()
+                .find_map(|factory| gst::ElementFactory::make_with_name(factory, None).ok())
+                .ok_or(anyhow!("Unable to create video sink.")),
+
+            // Create the sink which draws into a widget
+            VideoOutput::Widget => ChannelMixer::make_element(WIDGET_SINK),
+
+            // Send the video to the window compositor
+            VideoOutput::Composited => {
+                let sink = ChannelMixer::make_element("intervideosink")?;
+                sink.set_property("channel", ChannelMixer::output_name(name));
+                Ok(sink)
+            }
+
+            // Discard the video at the normal playback rate
+            VideoOutput::Headless => {
+                let sink = ChannelMixer::make_element("fakesink")?;
+                sink.set_property("sync", true);
+                Ok(sink)
+            }
+        }
+    }
+
+    /// A helper function to link an element to a new pad on the mixer
+    ///
+    pub fn link_to_mixer(element: &gst::Element, mixer: &gst::Element) -> Result<gst::Pad> {
+        // Request a new pad from the mixer
+        let pad = mixer
+            .request_pad_simple("sink_%u")
+            .ok_or(anyhow!("Unable to connect deck to mixer."))?;
+
+        // Link the element to the pad
+        element
+            .static_pad("src")
+            .ok_or(anyhow!("Unable to connect deck to mixer."))?
+            .link(&pad)
+            .context("Unable to connect deck to mixer.")?;
+
+        // Return the pad
+        Ok(pad)
+    }
+
+    /// A helper function to create a signal watch to report errors on the
+    /// output (described by the name)
+    ///
+    pub fn create_error_callback(
+        name: String,
+        pipeline: &gst::Pipeline,
+    ) -> Result<gst::bus::BusWatchGuard> {
+        // Try to access the pipeline bus
+        let bus = match pipeline.bus() {
+            Some(bus) => bus,
+            None => return Err(anyhow!("Unable to create {}: Invalid bus.", name)),
+        };
+
+        // Connect the signal handler for any errors
+        let context = format!("Unable to create {}: Duplicate watch.", name);
+        bus.add_watch(move |_, msg| {
+            // If an error was received, share the error
+            if let gst::MessageView::Error(err) = msg.view() {
+                error!("Error on {}: {}", name, err.error());
+            }
+
+            // Continue with other signal handlers
+            glib::ControlFlow::Continue
+        })
+        .context(context)
+    }
+}
+
+// Implement the drop trait for ChannelMixer
+impl Drop for ChannelMixer {
+    /// This method sets the output pipeline to NULL
+    ///
+    fn drop(&mut self) {
+        self.pipeline
+            .set_state(gst::State::Null)
+            .unwrap_or(gst::StateChangeSuccess::Success);
+    }
+}
+
+// Tests of the channel mixer module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the mixer without a display, using test sources for the first deck
+    #[test]
+    fn headless_test_sources() {
+        // Initialize gstreamer
+        gst::init().expect("Unable to initialize gstreamer.");
+
+        // Create a headless mixer for a channel with a video frame
+        let name = ChannelMixer::unique_name(1);
+        let output_clock = OutputClock::new(gst::SystemClock::obtain());
+        let mixer = ChannelMixer::new(
+            &MediaChannel {
+                channel: 1,
+                video_frame: Some(VideoFrameWithWindow {
+                    window_number: 1,
+                    top: 0,
+                    left: 0,
+                    height: 240,
+                    width: 320,
+                }),
+                audio_device: None,
+                loop_media: None,
+                idle_background: None,
+                transitions: true,
+                layer: None,
+                opacity: None,
+                hidden: false,
+            },
+            VideoOutput::Headless,
+            name.clone(),
+            &output_clock,
+        )
+        .expect("Unable to create mixer.");
+        mixer.start().expect("Unable to start mixer.");
+
+        // Make sure the output plays on the shared clock and base time
+        let reference = gst::Pipeline::new();
+        output_clock.apply(&reference);
+        assert_eq!(mixer.pipeline.clock().as_ref(), Some(output_clock.clock()));
+        assert_eq!(mixer.pipeline.base_time(), reference.base_time());
+        mixer.set_level(0, 1.0);
+        assert_eq!(mixer.level(0), 1.0);
+        assert_eq!(mixer.level(1), 0.0);
+
+        // Play a short test pattern and tone into the first deck
+        let deck = gst::parse::launch(&format!(
+            "videotestsrc num-buffers=30 ! intervideosink channel={0} \
+             audiotestsrc num-buffers=30 ! interaudiosink channel={0}",
+            ChannelMixer::deck_name(&name, 0)
+        ))
+        .expect("Unable to create test sources.");
+        deck.set_state(gst::State::Playing)
+            .expect("Unable to play test sources.");
+
+        // Wait for the test sources to finish
+        let message = deck
+            .bus()
+            .expect("Invalid bus.")
+            .timed_pop_filtered(
+                gst::ClockTime::from_seconds(10),
+                &[gst::MessageType::Eos, gst::MessageType::Error],
+            )
+            .expect("Test sources did not finish.");
+        assert_eq!(message.type_(), gst::MessageType::Eos);
+        deck.set_state(gst::State::Null)
+            .expect("Unable to stop test sources.");
+
+        // Make sure the mixer is still playing without any errors
+        assert_eq!(
+            mixer.pipeline.state(gst::ClockTime::from_seconds(5)).1,
+            gst::State::Playing
+        );
+        assert!(
+            mixer
+                .pipeline
+                .bus()
+                .expect("Invalid bus.")
+                .pop_filtered(&[gst::MessageType::Error])
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to step through a list of cues. The module tracks the current
+//! and standby cues, and handles the wait and follow timing for each cue.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import standard library features
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Import Tokio features
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::sleep;
+
+// Import anyhow features
+use anyhow::Result;
+
+/// A helper type to trigger a cue after any wait or follow time
+///
+#[derive(Debug)]
+struct CueTrigger {
+    generation: u64, // the generation of the cue list when the trigger was created
+    index: usize,    // the index of the cue to fire
+}
+
+/// A structure to hold the cue list and the current position in the list
+///
+#[derive(Debug)]
+pub struct CueListHandler {
+    cue_list: Option<CueList>,  // the current cue list, if loaded
+    current: Option<usize>,     // the index of the most recent cue
+    pending: Option<usize>,     // the index of the cue waiting to fire after its wait time, if any
+    generation: Arc<AtomicU64>, // the counter to identify the most recent go (and cancel older waits and follows)
+    event_send: EventSend,      // the line to watch for the end of media
+    trigger_send: mpsc::UnboundedSender<CueTrigger>, // the sending line for cue triggers
+    trigger_receive: mpsc::UnboundedReceiver<CueTrigger>, // the receiving line for cue triggers
+}
+
+// Implement key functionality for the cue list handler
+impl CueListHandler {
+    /// A function to create a new, empty cue list handler
+    ///
+    pub fn new(event_send: EventSend) -> CueListHandler {
+        // Create the line for cue triggers
+        let (trigger_send, trigger_receive) = mpsc::unbounded_channel();
+
+        // Return the new handler
+        CueListHandler {
+            cue_list: None,
+            current: None,
+            pending: None,
+            generation: Arc::new(AtomicU64::new(0)),
+            event_send,
+            trigger_send,
+            trigger_receive,
+        }
+    }
+
+    /// A method to load a new cue list and place the first cue in standby.
+    /// Any pending waits or follows from the previous list are cancelled.
+    ///
+    pub fn load(&mut self, cue_list: CueList) -> CueListStatus {
+        // Cancel any pending cues
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        // Replace the cue list and reset the position
+        self.cue_list = Some(cue_list);
+        self.current = None;
+        self.pending = None;
+
+        // Return the new status
+        self.status()
+    }
+
+    /// A method to trigger the standby cue
+    ///
+    pub fn go(&mut self) -> Result<CueListStatus> {
+        // Make sure there is a standby cue
+        match self.standby() {
+            Some(index) => self.trigger(index),
+            None => Err(anyhow!("Unable to go: No cue in standby.")),
+        }
+    }
+
+    /// A method to trigger the cue before the most recent cue
+    ///
+    pub fn back(&mut self) -> Result<CueListStatus> {
+        // Make sure there is a previous cue
+        match self.current {
+            Some(current) if current > 0 => self.trigger(current - 1),
+            _ => Err(anyhow!("Unable to go back: No previous cue.")),
+        }
+    }
+
+    /// A method to trigger a specific cue in the list
+    ///
+    pub fn go_to(&mut self, index: usize) -> Result<CueListStatus> {
+        self.trigger(index)
+    }
+
+    /// A method to return a copy of the current cue list, if loaded
+    ///
+    pub fn cue_list(&self) -> Option<CueList> {
+        self.cue_list.clone()
+    }
+
+    /// A method to report the current position in the cue list
+    ///
+    pub fn status(&self) -> CueListStatus {
+        // Find the standby cue
+        let standby = self.standby();
+
+        // Return the completed status
+        CueListStatus {
+            name: self.cue_list.as_ref().map(|list| list.name.clone()),
+            current: self.current,
+            pending: self.pending,
+            standby,
+            standby_cue: standby.and_then(|index| self.cue(index)),
+        }
+    }
+
+    /// A method to wait for the next cue to fire. When the cue fires, the
+    /// position is updated and any follow for the cue is started.
+    ///
+    /// # Note
+    ///
+    /// This method is cancel safe and can be used in a select statement.
+    ///
+    pub async fn next_cue(&mut self) -> Option<Cue> {
+        // Wait for triggers from the current generation
+        loop {
+            // Wait for the next trigger
+            let trigger = self.trigger_receive.recv().await?;
+
+            // Ignore triggers from a previous generation
+            if trigger.generation != self.generation.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            // Ignore triggers for cues that no longer exist
+            let cue = match self.cue(trigger.index) {
+                Some(cue) => cue,
+                None => continue,
+            };
+
+            // Update the current position
+            self.current = Some(trigger.index);
+            self.pending = None;
+
+            // If there is a follow, start the follow for the next cue
+            if let Some(follow) = cue.follow.clone() {
+                self.start_follow(trigger.index + 1, follow, trigger.generation);
+            }
+
+            // Return the cue to fire
+            return Some(cue);
+        }
+    }
+
+    // A helper method to find the standby cue
+    fn standby(&self) -> Option<usize> {
+        // Find the cue after the cue waiting to fire (or the most recent cue)
+        let index = match self.pending.or(self.current) {
+            Some(current) => current + 1,
+            None => 0,
+        };
+
+        // Make sure the cue exists
+        self.cue(index).map(|_| index)
+    }
+
+    // A helper method to copy a cue from the list, if it exists
+    fn cue(&self, index: usize) -> Option<Cue> {
+        self.cue_list
+            .as_ref()
+            .and_then(|list| list.cues.get(index).cloned())
+    }
+
+    // A helper method to trigger a cue after its wait time. Any pending waits
+    // or follows are cancelled.
+    fn trigger(&mut self, index: usize) -> Result<CueListStatus> {
+        // Make sure the cue exists
+        let cue = match self.cue(index) {
+            Some(cue) => cue,
+            None => return Err(anyhow!("Unable to play cue: Cue not defined.")),
+        };
+
+        // Cancel any pending cues
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        // Note the cue as pending until it fires
+        self.pending = Some(index);
+
+        // If there is no wait, trigger the cue immediately
+        let trigger = CueTrigger { generation, index };
+        if cue.wait == 0 {
+            self.trigger_send.send(trigger).unwrap_or(());
+
+        // Otherwise, trigger the cue in the background
+        } else {
+            let trigger_send = self.trigger_send.clone();
+            tokio::spawn(async move {
+                sleep(Duration::from_millis(cue.wait)).await;
+                trigger_send.send(trigger).unwrap_or(());
+            });
+        }
+
+        // Return the new status
+        Ok(self.status())
+    }
+
+    // A helper method to trigger the next cue in the background, once the
+    // follow is complete
+    fn start_follow(&self, index: usize, follow: CueFollow, generation: u64) {
+        // Make sure the next cue exists
+        let wait = match self.cue(index) {
+            Some(cue) => cue.wait,
+            None => return,
+        };
+
+        // Copy the trigger line and the generation counter
+        let trigger_send = self.trigger_send.clone();
+        let current_generation = self.generation.clone();
+
+        // Subscribe to playback events before any media can end
+        let mut event_receive = self.event_send.subscribe();
+
+        // Spawn the follow in the background
+        tokio::spawn(async move {
+            // Match the type of follow
+            match follow {
+                // Wait for the delay
+                CueFollow::AutoContinue { delay } => {
+                    sleep(Duration::from_millis(delay)).await;
+                }
+
+                // Wait for the media on the channel to end, then wait for the delay
+                CueFollow::AutoFollow { channel, delay } => {
+                    loop {
+                        match event_receive.recv().await {
+                            // Continue when the media on the channel ends
+                            Ok(PlaybackEvent::MediaEnded { channel: ended, .. })
+                                if ended == channel =>
+                            {
+                                break;
+                            }
+
+                            // Stop waiting if a newer cue was triggered
+                            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                                if current_generation.load(Ordering::SeqCst) != generation {
+                                    return;
+                                }
+                            }
+
+                            // Stop waiting if the events have closed
+                            Err(broadcast::error::RecvError::Closed) => return,
+                        }
+                    }
+                    sleep(Duration::from_millis(delay)).await;
+                }
+            }
+
+            // Wait for the wait time of the next cue, then trigger the cue
+            sleep(Duration::from_millis(wait)).await;
+            trigger_send
+                .send(CueTrigger { generation, index })
+                .unwrap_or(());
+        });
+    }
+}
+
+// Tests of the cue list module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A helper function to create a cue with a wait and follow
+    fn cue(name: &str, wait: u64, follow: Option<CueFollow>) -> Cue {
+        Cue {
+            name: Some(name.to_string()),
+            media_cues: Vec::new(),
+            channel_states: Vec::new(),
+            channel_allocations: Vec::new(),
+            wait,
+            follow,
+        }
+    }
+
+    // A helper function to wait for the next cue (or fail after a second)
+    async fn next_name(handler: &mut CueListHandler) -> Option<String> {
+        tokio::time::timeout(Duration::from_secs(1), handler.next_cue())
+            .await
+            .expect("Cue did not fire.")
+            .and_then(|cue| cue.name)
+    }
+
+    // Test stepping through the cue list
+    #[tokio::test]
+    async fn step_through_list() {
+        // Load a cue list with three cues
+        let mut handler = CueListHandler::new(EventSend::new());
+        let status = handler.load(CueList {
+            name: "Show".to_string(),
+            cues: vec![cue("A", 0, None), cue("B", 0, None), cue("C", 0, None)],
+        });
+        assert_eq!(status.current, None);
+        assert_eq!(status.standby, Some(0));
+
+        // Go to the first cue
+        handler.go().unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("A".to_string()));
+        assert_eq!(handler.status().current, Some(0));
+        assert_eq!(handler.status().standby, Some(1));
+
+        // Jump to the last cue, which has no standby cue
+        handler.go_to(2).unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("C".to_string()));
+        assert_eq!(handler.status().standby, None);
+        assert!(handler.go().is_err());
+
+        // Go back to the middle cue
+        handler.back().unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("B".to_string()));
+        assert_eq!(handler.status().current, Some(1));
+
+        // Refuse a cue which does not exist
+        assert!(handler.go_to(5).is_err());
+    }
+
+    // Test that a cue is only current once its wait has elapsed
+    #[tokio::test]
+    async fn wait_before_firing() {
+        // Load a cue list with a waiting cue
+        let mut handler = CueListHandler::new(EventSend::new());
+        handler.load(CueList {
+            name: "Show".to_string(),
+            cues: vec![cue("A", 100, None), cue("B", 0, None)],
+        });
+
+        // Trigger the first cue and check that it is pending
+        let status = handler.go().unwrap();
+        assert_eq!(status.current, None);
+        assert_eq!(status.pending, Some(0));
+        assert_eq!(status.standby, Some(1));
+
+        // Once the cue fires, it becomes the current cue
+        assert_eq!(next_name(&mut handler).await, Some("A".to_string()));
+        assert_eq!(handler.status().current, Some(0));
+        assert_eq!(handler.status().pending, None);
+    }
+
+    // Test that a new cue cancels a pending wait or follow
+    #[tokio::test]
+    async fn cancel_pending_cues() {
+        // Load a cue list where the first cue continues to the second
+        let mut handler = CueListHandler::new(EventSend::new());
+        handler.load(CueList {
+            name: "Show".to_string(),
+            cues: vec![
+                cue("A", 0, Some(CueFollow::AutoContinue { delay: 50 })),
+                cue("B", 0, None),
+                cue("C", 0, None),
+            ],
+        });
+
+        // The first cue continues to the second cue
+        handler.go().unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("A".to_string()));
+        assert_eq!(next_name(&mut handler).await, Some("B".to_string()));
+
+        // Restart the list and jump ahead before the follow completes
+        handler.go_to(0).unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("A".to_string()));
+        handler.go_to(2).unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("C".to_string()));
+
+        // The cancelled follow never fires
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), handler.next_cue())
+                .await
+                .is_err()
+        );
+    }
+
+    // Test that an auto follow waits for the end of the media
+    #[tokio::test]
+    async fn follow_media_end() {
+        // Load a cue list where the first cue follows the media on channel 1
+        let event_send = EventSend::new();
+        let mut handler = CueListHandler::new(event_send.clone());
+        handler.load(CueList {
+            name: "Show".to_string(),
+            cues: vec![
+                cue(
+                    "A",
+                    0,
+                    Some(CueFollow::AutoFollow {
+                        channel: 1,
+                        delay: 0,
+                    }),
+                ),
+                cue("B", 0, None),
+            ],
+        });
+        handler.go().unwrap();
+        assert_eq!(next_name(&mut handler).await, Some("A".to_string()));
+
+        // Media ending on another channel is ignored
+        event_send.send(PlaybackEvent::MediaEnded {
+            channel: 2,
+            uri: None,
+        });
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), handler.next_cue())
+                .await
+                .is_err()
+        );
+
+        // Media ending on the channel fires the next cue
+        event_send.send(PlaybackEvent::MediaEnded {
+            channel: 1,
+            uri: None,
+        });
+        assert_eq!(next_name(&mut handler).await, Some("B".to_string()));
+    }
+}
@@ -20,36 +20,181 @@
 // Import crate definitions
 use crate::definitions::*;
 
+// Import other structures into this module
+use super::channel_mixer::{ChannelMixer, DECK_COUNT, IdleLayer};
+use super::network_clock::{NetworkClock, OutputClock};
+use super::window_compositor::WindowCompositor;
+
 // Import standard library features
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-// Import GTK Library
+// Import GLib Library
 use glib;
-use gtk;
-use gtk::prelude::*;
 
 // Import Gstreamer Library
 use gst::prelude::*;
 use gstreamer as gst;
-use gstreamer_video as gst_video;
+
+// Import Tokio features
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{interval, timeout};
 
 // Import FNV HashMap
 use fnv::FnvHashMap;
 
 // Import the tracing features
-use tracing::{error, instrument};
+use tracing::{error, instrument, warn};
 
 // Import anyhow features
 use anyhow::{Context, Result};
 
-/// A helper type to store the playbin and loop media uri
+// Define module constants
+pub const FADE_INTERVAL: u64 = 20; // the time between volume (or opacity) changes during a fade, in milliseconds
+const MAX_VOLUME: f64 = 10.0; // the maximum volume allowed by the playbin
+const PRELOAD_TIMEOUT: u64 = 10000; // the longest time to wait for new media before starting a transition, in milliseconds
+const GROUP_START_DELAY: u64 = 100; // the time between scheduling and starting a group, so every member starts together, in milliseconds
+const READY_TIMEOUT: u64 = 5000; // the longest time to wait for the user interface to embed a video sink, in milliseconds
+
+/// A helper type to store the settings which are shared with the playback
+/// callbacks for a channel
+///
+#[derive(Debug, Clone)]
+struct ChannelSettings {
+    active_deck: Arc<AtomicUsize>, // the index of the deck currently playing on this channel
+    loop_mutex: Arc<Mutex<Option<String>>>, // the current loop media handle for this channel
+    seek_flag: Arc<AtomicBool>,    // a flag to indicate a seek is in progress
+    loop_flag: Arc<AtomicBool>,    // a flag to indicate the loop media is about to start
+    end_flag: Arc<AtomicBool>,     // a flag to indicate the media on the active deck has ended
+    level_mutex: Arc<Mutex<f64>>,  // the current volume level for this channel
+    fade_generation: Arc<AtomicU64>, // the counter to identify the most recent fade out (and cancel older fades)
+    volume_generation: Arc<AtomicU64>, // the counter to identify the most recent volume fade (and cancel older volume fades)
+    transition_generation: Arc<AtomicU64>, // the counter to identify the most recent transition (and cancel older transitions)
+    ending_mutex: Arc<Mutex<Option<String>>>, // the uri of the media which is about to end, while the loop media is queued
+    is_idle: Arc<AtomicBool>, // a flag to indicate that no media is playing (and the idle background is shown)
+    idle_mutex: Arc<Mutex<Option<IdleLayer>>>, // the idle background in the window compositor, if the channel is composited
+    interface_send: Option<InterfaceSend>, // the line to show or hide the idle background in the user interface, if the channel is shown separately
+    channel: u32, // the channel number (to identify the channel in the user interface)
+}
+
+// Implement helper functionality for the channel settings
+impl ChannelSettings {
+    /// A method to show or hide the idle background of the channel, if any
+    ///
+    fn set_idle(&self, is_idle: bool) {
+        // Ignore the change if nothing changed
+        if self.is_idle.swap(is_idle, Ordering::SeqCst) == is_idle {
+            return;
+        }
+
+        // Update the idle background in the window compositor, if composited
+        if let Ok(idle_layer) = self.idle_mutex.lock()
+            && let Some(idle_layer) = idle_layer.as_ref()
+        {
+            idle_layer.set_idle(is_idle);
+        }
+
+        // Update the idle background in the user interface, if shown separately
+        if let Some(interface_send) = self.interface_send.as_ref() {
+            interface_send.send(InterfaceUpdate::Idle {
+                channel: self.channel,
+                is_idle,
+            });
+        }
+    }
+
+    /// A method to cancel every fade in progress or waiting on the channel
+    ///
+    fn cancel_fades(&self) {
+        self.fade_generation.fetch_add(1, Ordering::SeqCst);
+        self.volume_generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// An enum to identify an output which waits for the user interface to
+/// embed its video sink before starting
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReadyOutput {
+    /// A variant for the outputs of a channel
+    Channel(u32),
+
+    /// A variant for the output of a window compositor
+    Window(u32),
+}
+
+/// A helper type to store the decks, mixer, and loop media uri
 ///
 #[derive(Debug)]
 struct InternalChannel {
-    playbin: gst::Element,                  // the playbin for this channel
-    channel_loop: Option<String>,           // the default loop media for this channel
-    loop_mutex: Arc<Mutex<Option<String>>>, // the current loop media handle for this channel
-    watch_guard: gst::bus::BusWatchGuard,   // the guard for the watch funcions on the playback bus
+    media_channel: MediaChannel, // the current definition of this channel
+    decks: Vec<gst::Element>,    // the playbins for each deck of this channel
+    mixer: Option<Arc<ChannelMixer>>, // the mixer which combines the decks into the channel output, if the channel allows transitions
+    channel_loop: Option<String>,     // the default loop media for this channel
+    settings: ChannelSettings,        // the settings shared with the playback callbacks
+    preload_mutex: Mutex<Option<String>>, // the uri of the media preloaded on the idle deck, if any
+    cue_mutex: Mutex<Option<MediaCue>>, // the most recent media cue on this channel, if any
+    watch_guards: Vec<gst::bus::BusWatchGuard>, // the guards for the watch funcions on each playback bus
+    ready_mutex: Mutex<Option<oneshot::Receiver<()>>>, // the confirmation that the video sink is embedded, until the outputs wait to start
+}
+
+// Implement helper functionality for the internal channel
+impl InternalChannel {
+    /// A method to return the playbin of the deck currently playing
+    ///
+    fn active(&self) -> &gst::Element {
+        &self.decks[self.settings.active_deck.load(Ordering::SeqCst)]
+    }
+
+    /// A method to show only the provided deck (if the channel has a mixer)
+    ///
+    fn show_deck(&self, deck: usize) {
+        if let Some(mixer) = self.mixer.as_ref() {
+            for index in 0..self.decks.len() {
+                mixer.set_level(index, 0.0);
+            }
+            mixer.set_level(deck, 1.0);
+        }
+    }
+}
+
+/// A helper type to share a member of a group with the background task for
+/// the group
+///
+#[derive(Debug, Clone)]
+struct GroupMember {
+    channel: u32,                      // the channel number of the member
+    deck: glib::WeakRef<gst::Element>, // the deck playing the media for the group
+    settings: ChannelSettings,         // the settings shared with the playback callbacks
+    generation: u64, // the transition generation when the member joined (any change removes the member from the group)
+}
+
+// Implement helper functionality for the group member
+impl GroupMember {
+    /// A method to return the deck of the member, if the member is still
+    /// part of the group
+    ///
+    fn deck(&self) -> Option<gst::Element> {
+        // Check that no other media was cued on the channel
+        if self.settings.transition_generation.load(Ordering::SeqCst) != self.generation {
+            return None;
+        }
+
+        // Try to get a strong reference to the deck
+        self.deck.upgrade()
+    }
+}
+
+/// A helper type to store the channels which play together on a shared clock
+///
+#[derive(Debug)]
+struct InternalGroup {
+    members: Vec<GroupMember>,  // the channels which play together
+    clock: gst::Clock,          // the clock shared by every member
+    is_looping: bool, // a flag to restart every member together once all the media has ended
+    generation: Arc<AtomicU64>, // the counter to identify the most recent change to the group (and cancel older changes)
 }
 
 /// A structure to hold and manipulate the connection to the media backend
@@ -57,39 +202,105 @@ struct InternalChannel {
 #[derive(Debug)]
 pub struct MediaPlayback {
     channels: FnvHashMap<u32, InternalChannel>, // the map of channel numbers to internal channels
+    groups: Vec<InternalGroup>, // the groups of channels which play together on a shared clock
+    compositors: FnvHashMap<u32, WindowCompositor>, // the map of window numbers to compositors, for windows which mix every channel
+    network_clock: Option<NetworkClock>, // the clock shared with other instances, if specified
+    output_clock: OutputClock, // the clock and base time shared by every output and group on this instance
+    event_send: EventSend,     // the sending line to share playback events
+    interface_send: InterfaceSend, // the sending line to pass updates to the user interface
+    video_output: VideoOutput, // the way video is shown on the display
+    waiting: FnvHashMap<ReadyOutput, u64>, // the outputs waiting for the user interface, with the key of each wait
+    ready_key: u64,                        // the key of the most recent wait
+    ready_send: mpsc::Sender<(ReadyOutput, u64)>, // the sending line for outputs which are ready to start
+    ready_receive: mpsc::Receiver<(ReadyOutput, u64)>, // the receiving line for outputs which are ready to start
 }
 
 // Implement key functionality for the Media Out structure
 impl MediaPlayback {
     /// A function to create a new instance of the MediaPlayback
     ///
-    pub fn new() -> Result<MediaPlayback> {
+    /// # Note
+    ///
+    /// If headless, video is discarded and no video streams are sent to the
+    /// user interface. If a clock source is specified, every deck plays on
+    /// the clock shared with other instances.
+    ///
+    pub fn new(
+        event_send: EventSend,
+        interface_send: InterfaceSend,
+        video_output: VideoOutput,
+        clock_source: Option<ClockSource>,
+    ) -> Result<MediaPlayback> {
         // Try to initialize GStreamer
         gst::init().context("Unable to initialize Gstreamer.")?;
 
+        // Publish or follow the network clock, if specified
+        let network_clock = match clock_source {
+            Some(clock_source) => Some(NetworkClock::new(clock_source)?),
+            None => None,
+        };
+
+        // Share the network clock (or a realtime clock) with every output
+        let output_clock = OutputClock::new(match network_clock.as_ref() {
+            Some(network_clock) => network_clock.clock().clone(),
+            None => NetworkClock::realtime_clock(),
+        });
+
+        // Create the line for outputs which are ready to start
+        let (ready_send, ready_receive) = mpsc::channel(128);
+
         // Return the complete module
         Ok(MediaPlayback {
             channels: FnvHashMap::default(),
+            groups: Vec::new(),
+            compositors: FnvHashMap::default(),
+            network_clock,
+            output_clock,
+            event_send,
+            interface_send,
+            video_output,
+            waiting: FnvHashMap::default(),
+            ready_key: 0,
+            ready_send,
+            ready_receive,
         })
     }
 
-    /// A function to stop all playing media
+    /// A function to stop all playing media. If a fade out is specified, the
+    /// media is stopped once the fade is complete.
     ///
-    pub fn all_stop(&self) -> Result<()> {
+    pub fn all_stop(&self, fade_out: Option<AudioFade>) -> Result<()> {
         // Stop the playing media on every channel
         for (_, channel) in self.channels.iter() {
-            channel
-                .playbin
-                .set_state(gst::State::Null)
-                .context("Unable to stop media.")?;
+            MediaPlayback::stop_media(channel, fade_out.clone())?;
         }
 
         // Indicate success
         Ok(())
     }
 
+    /// A function to stop the media on an existing channel immediately and
+    /// show the idle background
+    ///
+    pub fn clear_media(&self, channel_number: u32) -> Result<()> {
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.get(&channel_number) {
+            MediaPlayback::stop_media(channel, None)
+
+        // Otherwise, throw an error
+        } else {
+            Err(anyhow!("Unable to stop media: Channel not defined."))
+        }
+    }
+
     /// A function a create a new video stream
     ///
+    /// # Note
+    ///
+    /// If a video stream is returned, the outputs of the channel do not start
+    /// until wait_for_channel() is called (once the video stream has been sent
+    /// to the user interface) and the channel is ready.
+    ///
     #[instrument]
     pub fn define_channel(&mut self, media_channel: MediaChannel) -> Result<Option<VideoStream>> {
         // Check to see if there is an existing channel
@@ -98,114 +309,364 @@ impl MediaPlayback {
             return Err(anyhow!("Channel is already defined."));
         }
 
-        // Create a new playbin
-        let playbin = gst::ElementFactory::make_with_name("playbin", None)
-            .context("Unable to create playbin.")?;
+        // Create the new channel and add it to the channels
+        let layer = media_channel.layer;
+        let (channel, video_stream) = self.create_channel(media_channel)?;
+        let channel_number = channel.media_channel.channel;
+        self.channels.insert(channel_number, channel);
+        self.waiting.remove(&ReadyOutput::Channel(channel_number));
+
+        // Place the channel in the stacking order of the window
+        self.restack(channel_number, layer);
+
+        // Return the video stream, if created
+        Ok(video_stream)
+    }
+
+    /// A function to stop and remove an existing channel
+    ///
+    pub fn remove_channel(&mut self, channel_number: u32) -> Result<()> {
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.remove(&channel_number) {
+            // Stop waiting to start the channel, if still waiting
+            self.waiting.remove(&ReadyOutput::Channel(channel_number));
 
-        // Match based on the audio device specified
-        match media_channel.audio_device {
-            // An ALSA device
-            Some(AudioDevice::Alsa { device_name }) => {
-                // Create and set the audio sink
-                let audio_sink = gst::ElementFactory::make_with_name("alsasink", None)
-                    .context("Unable to create alsasink.")?;
-                audio_sink.set_property("device", &device_name);
-                playbin.set_property("audio-sink", &audio_sink);
+            // Disconnect the channel from the window compositor, if any
+            if let Some(compositor) = channel
+                .media_channel
+                .video_frame
+                .as_ref()
+                .and_then(|frame| self.compositors.get_mut(&frame.window_number))
+            {
+                compositor.remove_channel(channel_number);
             }
 
-            // A Pulse Audio device
-            Some(AudioDevice::Pulse { device_name }) => {
-                // Create and set the audio sink
-                let audio_sink = gst::ElementFactory::make_with_name("pulsesink", None)
-                    .context("Unable to create pulsesink.")?;
-                audio_sink.set_property("device", &device_name);
-                playbin.set_property("audio-sink", &audio_sink);
+            // Renumber the layers of the remaining channels in the window
+            if let Some(window_number) = MediaPlayback::window_number(&channel) {
+                self.renumber(&self.window_order(window_number));
             }
 
-            // Ignore all others
-            _ => (),
+            // Stop the channel
+            MediaPlayback::stop_channel(&channel)?;
+
+        // Otherwise, throw an error
+        } else {
+            return Err(anyhow!("Unable to remove channel: Channel not defined."));
         }
 
-        // If a video window was specified
-        let mut video_stream = None;
-        if let Some(video_frame) = media_channel.video_frame {
-            // Compose the allocation
-            let allocation = gtk::Rectangle::new(
-                video_frame.left,
-                video_frame.top,
-                video_frame.width,
-                video_frame.height,
-            );
+        // Indicate success (the watch guards and mixer are dropped with the channel)
+        Ok(())
+    }
 
-            // Try to create the video overlay
-            let video_overlay = match playbin.clone().dynamic_cast::<gst_video::VideoOverlay>() {
-                Ok(overlay) => overlay,
-                _ => return Err(anyhow!("Unable to create video stream.")),
+    /// A function to replace the definition of an existing channel. Any media
+    /// on the channel is stopped, and the volume of the channel is preserved.
+    /// If the new definition fails, the existing channel is left unchanged.
+    ///
+    /// Unless a new layer is specified, the channel keeps its place in the
+    /// stacking order of the window (if the window is unchanged).
+    ///
+    /// As with define_channel(), if a video stream is returned, the outputs of
+    /// the channel do not start until wait_for_channel() is called and the
+    /// channel is ready.
+    ///
+    pub fn redefine_channel(&mut self, media_channel: MediaChannel) -> Result<Option<VideoStream>> {
+        // Make sure there is an existing channel and note the current volume
+        // and window
+        let (level, previous_window, previous_layer) =
+            match self.channels.get(&media_channel.channel) {
+                Some(channel) => (
+                    MediaPlayback::current_level(channel),
+                    MediaPlayback::window_number(channel),
+                    channel.media_channel.layer,
+                ),
+                None => return Err(anyhow!("Unable to redefine channel: Channel not defined.")),
             };
 
-            // Send the new video stream to the user interface
-            video_stream = Some(VideoStream {
-                window_number: video_frame.window_number,
-                channel: media_channel.channel,
-                allocation,
-                video_overlay,
-            });
-        } // Otherwise, any window creation (if needed) is left to gstreamer
+        // Create the new channel (alongside the existing channel)
+        let (channel, video_stream) = self.create_channel(media_channel)?;
 
-        // Create the loop media mutex
-        let loop_mutex = Arc::new(Mutex::new(media_channel.loop_media.clone()));
+        // Restore the volume of the channel
+        if let Ok(mut current) = channel.settings.level_mutex.lock() {
+            *current = level;
+        }
+        for deck in channel.decks.iter() {
+            deck.set_property("volume", level);
+        }
 
-        // Create the loop media callback
-        let watch_guard = MediaPlayback::create_loop_callback(&playbin, loop_mutex.clone())?;
+        // Keep the place of the channel in the stacking order, unless a new
+        // layer or window is specified
+        let window = MediaPlayback::window_number(&channel);
+        let layer = match (channel.media_channel.layer, window == previous_window) {
+            (None, true) => previous_layer,
+            (layer, _) => layer,
+        };
 
-        // If loop media was specified
-        if let Some(loop_uri) = media_channel.loop_media.clone() {
-            // Set the playbin to the loop uri
-            playbin.set_property("uri", &loop_uri);
+        // Replace the existing channel (and stop waiting to start the previous channel)
+        let channel_number = channel.media_channel.channel;
+        self.waiting.remove(&ReadyOutput::Channel(channel_number));
+        if let Some(previous) = self.channels.insert(channel_number, channel) {
+            // Disconnect the previous channel from a different window compositor, if any
+            // (a compositor in the same window has already replaced the video)
+            if previous_window != window
+                && let Some(compositor) =
+                    previous_window.and_then(|window| self.compositors.get_mut(&window))
+            {
+                compositor.remove_channel(channel_number);
+            }
 
-            // Start playing the media
-            playbin
-                .set_state(gst::State::Playing)
-                .context("Unable to start playing media.")?;
+            // Stop the previous channel (the new channel is already in place)
+            if let Err(error) = MediaPlayback::stop_channel(&previous) {
+                error!(
+                    "Unable to stop previous channel {}: {}",
+                    channel_number, error
+                );
+            }
         }
 
-        // Add the playbin to the channels
-        self.channels.insert(
-            media_channel.channel,
-            InternalChannel {
-                playbin,
-                channel_loop: media_channel.loop_media.clone(),
-                loop_mutex,
-                watch_guard,
-            },
-        );
+        // Place the channel in the stacking order of the window (and close
+        // the gap in a previous window, if different)
+        self.restack(channel_number, layer);
+        if previous_window != window
+            && let Some(previous_window) = previous_window
+        {
+            self.renumber(&self.window_order(previous_window));
+        }
 
         // Return the video stream, if created
         Ok(video_stream)
     }
 
+    /// A method to wait in the background for the user interface to embed
+    /// the video sink of a new channel. Once the user interface confirms (or
+    /// after the timeout), next_ready() returns the channel to start with
+    /// start_ready().
+    ///
+    /// Channels without a video stream are started when they are defined, so
+    /// this method has no effect on them (or on channels already waiting).
+    ///
+    pub fn wait_for_channel(&mut self, channel_number: u32) -> Result<()> {
+        // Take the confirmation line for the channel, if not yet waiting
+        let ready_receive = match self.channels.get(&channel_number) {
+            Some(channel) => match channel.ready_mutex.lock() {
+                Ok(mut ready_receive) => ready_receive.take(),
+                _ => return Err(anyhow!("Unable to start channel: Invalid lock.")),
+            },
+            None => return Err(anyhow!("Unable to start channel: Channel not defined.")),
+        };
+
+        // Wait for the user interface to embed the video sink, if needed
+        if let Some(ready_receive) = ready_receive {
+            self.wait_for_ready(ReadyOutput::Channel(channel_number), ready_receive);
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A method to wait for the next output which is ready to start. Outputs
+    /// which were removed or replaced while waiting are skipped.
+    ///
+    /// # Note
+    ///
+    /// This method is cancel safe and can be used in a select statement.
+    ///
+    pub async fn next_ready(&mut self) -> Option<ReadyOutput> {
+        // Wait for an output which is still waiting
+        loop {
+            // Wait for the next output
+            let (output, key) = self.ready_receive.recv().await?;
+
+            // Ignore outputs from a previous wait
+            if self.waiting.get(&output) != Some(&key) {
+                continue;
+            }
+
+            // Otherwise, return the output
+            self.waiting.remove(&output);
+            return Some(output);
+        }
+    }
+
+    /// A method to start an output which is ready (returned by next_ready())
+    ///
+    pub fn start_ready(&self, output: ReadyOutput) -> Result<()> {
+        match output {
+            // Start the outputs of the channel
+            ReadyOutput::Channel(channel_number) => match self.channels.get(&channel_number) {
+                Some(channel) => MediaPlayback::start_outputs(channel).map_err(|error| {
+                    anyhow!("Unable to start channel {}: {}", channel_number, error)
+                }),
+                None => Err(anyhow!("Unable to start channel: Channel not defined.")),
+            },
+
+            // Start the output of the window compositor
+            ReadyOutput::Window(window_number) => match self.compositors.get(&window_number) {
+                Some(compositor) => compositor.start().map_err(|error| {
+                    anyhow!("Unable to start window {}: {}", window_number, error)
+                }),
+                None => Err(anyhow!("Unable to start compositor: Window not mixed.")),
+            },
+        }
+    }
+
     /// A function to cue new media on an existing channel
     ///
-    pub fn cue_media(&self, media_cue: MediaCue) -> Result<()> {
+    /// # Note
+    ///
+    /// Media with a start time is started as a group with one channel, so
+    /// any transition or loop media is ignored.
+    ///
+    pub fn cue_media(&mut self, media_cue: MediaCue) -> Result<()> {
+        // If a start time was specified, start the media as a group
+        if let Some(start_at) = media_cue.start_at {
+            return self.cue_group(CueGroup {
+                media_cues: vec![media_cue],
+                loop_group: false,
+                start_at: Some(start_at),
+            });
+        }
+
         // Make sure there is an existing channel
         if let Some(channel) = self.channels.get(&media_cue.channel) {
-            // Stop the previous media
-            channel
-                .playbin
-                .set_state(gst::State::Null)
-                .context("Unable to stop media.")?;
+            // Make sure the channel allows transitions, if specified
+            if media_cue.transition.is_some() && channel.mixer.is_none() {
+                return Err(anyhow!(
+                    "Unable to cue media: Transitions not enabled on this channel."
+                ));
+            }
+
+            // Cancel any transition or fade in progress
+            let generation = channel
+                .settings
+                .transition_generation
+                .fetch_add(1, Ordering::SeqCst)
+                + 1;
+            channel.settings.cancel_fades();
+
+            // Clear any loop media queued by the previous media
+            channel.settings.loop_flag.store(false, Ordering::SeqCst);
+
+            // Check whether this media was preloaded on the idle deck (other
+            // preloaded media is kept for a later cue, unless the idle deck
+            // is needed for a transition)
+            let (is_preloaded, is_holding) = match channel.preload_mutex.lock() {
+                Ok(mut preload) if preload.as_ref() == Some(&media_cue.uri) => {
+                    *preload = None;
+                    (true, false)
+                }
+                Ok(mut preload) if media_cue.transition.is_some() => {
+                    *preload = None;
+                    (false, false)
+                }
+                Ok(preload) => (false, preload.is_some()),
+                _ => (false, false),
+            };
+
+            // Use the idle deck for preloaded media or a transition, otherwise
+            // replace the media on the active deck
+            let outgoing = channel.settings.active_deck.load(Ordering::SeqCst);
+            let incoming = if is_preloaded || media_cue.transition.is_some() {
+                (outgoing + 1) % DECK_COUNT
+            } else {
+                outgoing
+            };
+            let playbin = &channel.decks[incoming];
+
+            // If the media was not preloaded, load the media on the deck
+            if !is_preloaded {
+                // Stop the previous media on the deck
+                playbin
+                    .set_state(gst::State::Null)
+                    .context("Unable to stop media.")?;
+
+                // Add the uri to the deck (on the clock for this instance)
+                MediaPlayback::release_clock(playbin, self.instance_clock());
+                playbin.set_property("uri", &media_cue.uri);
+            }
 
-            // Add the uri to this channel
-            channel.playbin.set_property("uri", &media_cue.uri);
+            // Start silent if fading in, otherwise at the channel volume
+            let level = MediaPlayback::current_level(channel);
+            if media_cue.fade_in.is_some() {
+                playbin.set_property("volume", 0.0f64);
+            } else {
+                playbin.set_property("volume", level);
+            }
 
-            // Make sure the new media is playing
+            // Switch to the new deck (events from the outgoing deck are now ignored)
             channel
-                .playbin
-                .set_state(gst::State::Playing)
-                .context("Unable to start playing media.")?;
+                .settings
+                .active_deck
+                .store(incoming, Ordering::SeqCst);
+            channel.settings.set_idle(false);
+
+            // Match the transition from the previous media
+            match media_cue.transition.clone() {
+                // Blend the new media in while the previous media continues
+                Some(transition @ Transition::Crossfade { .. }) => {
+                    playbin
+                        .set_state(gst::State::Playing)
+                        .context("Unable to start playing media.")?;
+                    MediaPlayback::start_transition(
+                        channel, outgoing, incoming, transition, generation,
+                    );
+                }
+
+                // Hold the new media until the previous media has faded out
+                Some(transition @ Transition::FadeThroughBlack { .. }) => {
+                    playbin
+                        .set_state(gst::State::Paused)
+                        .context("Unable to load media.")?;
+                    MediaPlayback::start_transition(
+                        channel, outgoing, incoming, transition, generation,
+                    );
+                }
+
+                // Otherwise, replace the previous media instantly
+                None => {
+                    // Make sure the new media is playing
+                    playbin
+                        .set_state(gst::State::Playing)
+                        .context("Unable to start playing media.")?;
+
+                    // Show only the new media
+                    channel.show_deck(incoming);
+
+                    // Stop any media on the other decks (except preloaded media)
+                    for (index, deck) in channel.decks.iter().enumerate() {
+                        if index != incoming && !is_holding {
+                            deck.set_state(gst::State::Null)
+                                .context("Unable to stop media.")?;
+                        }
+                    }
+                }
+            }
+
+            // If the media was preloaded, notify that the media started (the
+            // start of the stream was ignored while the deck was idle)
+            if is_preloaded {
+                self.event_send.send(PlaybackEvent::MediaStarted {
+                    channel: media_cue.channel,
+                    uri: Some(media_cue.uri.clone()),
+                });
+            }
+
+            // If a fade in was specified, fade to the channel volume
+            if let Some(fade) = media_cue.fade_in.clone() {
+                MediaPlayback::start_fade(channel, playbin, level, fade, false);
+            }
+
+            // If a fade out was specified, wait for the end of the media
+            if let Some(fade) = media_cue.fade_out.clone() {
+                MediaPlayback::start_fade_out(channel, playbin, fade);
+            }
+
+            // Save the cue (with the fades and transition)
+            if let Ok(mut cue) = channel.cue_mutex.lock() {
+                *cue = Some(media_cue.clone());
+            }
 
             // Try to get a lock on the loop mutex
-            if let Ok(mut media) = channel.loop_mutex.lock() {
+            if let Ok(mut media) = channel.settings.loop_mutex.lock() {
                 // Replace the media with the local loop or channel loop
                 *media = media_cue.loop_media.or(channel.channel_loop.clone());
 
@@ -223,128 +684,2052 @@ impl MediaPlayback {
         Ok(())
     }
 
-    /// A function to change the state of a existing channel
+    /// A function to load media on the idle deck of an existing channel, so
+    /// that a later cue of the same media starts instantly
     ///
-    pub fn change_state(&self, channel_state: ChannelState) -> Result<()> {
+    pub fn preload_media(&self, media_cue: MediaCue) -> Result<()> {
         // Make sure there is an existing channel
-        if let Some(channel) = self.channels.get(&channel_state.channel) {
-            // Match the new state
-            match channel_state.state {
-                // Switch to playing
-                PlaybackState::Playing => {
-                    channel
-                        .playbin
-                        .set_state(gst::State::Playing)
-                        .context("Unable to play media.")?;
-                }
+        if let Some(channel) = self.channels.get(&media_cue.channel) {
+            // Make sure the channel has an idle deck
+            if channel.mixer.is_none() {
+                return Err(anyhow!(
+                    "Unable to preload media: Transitions not enabled on this channel."
+                ));
+            }
 
-                // Switch to Paused
-                PlaybackState::Paused => {
-                    channel
-                        .playbin
-                        .set_state(gst::State::Paused)
-                        .context("Unable to pause media.")?;
-                }
+            // Find the idle deck
+            let idle = (channel.settings.active_deck.load(Ordering::SeqCst) + 1) % DECK_COUNT;
+            let playbin = &channel.decks[idle];
+
+            // Try to get a lock on the preload mutex
+            let mut preload = match channel.preload_mutex.lock() {
+                Ok(preload) => preload,
+                _ => return Err(anyhow!("Unable to preload media.")),
+            };
+
+            // Make sure the idle deck is not still finishing a transition
+            if preload.is_none()
+                && (playbin.current_state() != gst::State::Null
+                    || playbin.pending_state() != gst::State::VoidPending)
+            {
+                return Err(anyhow!("Unable to preload media: Transition in progress."));
             }
 
+            // Stop any previously preloaded media
+            playbin
+                .set_state(gst::State::Null)
+                .context("Unable to stop media.")?;
+
+            // Add the uri to the deck and load the media without playing
+            MediaPlayback::release_clock(playbin, self.instance_clock());
+            playbin.set_property("uri", &media_cue.uri);
+            playbin
+                .set_state(gst::State::Paused)
+                .context("Unable to preload media.")?;
+
+            // Save the preloaded media
+            *preload = Some(media_cue.uri);
+
         // Otherwise, throw an error
         } else {
-            return Err(anyhow!("Unable to change state: Channel not defined."));
+            return Err(anyhow!("Unable to preload media: Channel not defined."));
         }
 
         // Indicate success
         Ok(())
     }
 
-    /// A function to seek within the media on an existing channel
+    /// A function to load media on an existing channel and hold the media
+    /// paused, ready to play instantly. Any transition, fade, or preloaded
+    /// media on the channel is discarded.
     ///
-    pub fn seek(&self, channel_seek: ChannelSeek) -> Result<()> {
+    pub fn hold_media(&self, media_cue: MediaCue) -> Result<()> {
         // Make sure there is an existing channel
-        if let Some(channel) = self.channels.get(&channel_seek.channel) {
-            // Check the length of the current media on the channel
-            if let Some(duration) = channel.playbin.query_duration::<gst::ClockTime>() {
-                // If there is enough time in the media, seek to that point
-                if duration.mseconds() > channel_seek.position {
-                    channel
-                        .playbin
-                        .seek_simple(
-                            gst::SeekFlags::FLUSH,
-                            gst::ClockTime::from_mseconds(channel_seek.position),
-                        )
-                        .context("Unable to seek media.")?;
+        if let Some(channel) = self.channels.get(&media_cue.channel) {
+            // Load the media paused on the active deck
+            MediaPlayback::load_paused(channel, &media_cue.uri, self.instance_clock())?;
 
-                // Otherwise, seek to the end
-                } else {
-                    // Seek to the end and pause
-                    channel
-                        .playbin
-                        .seek_simple(
-                            gst::SeekFlags::FLUSH,
-                            gst::ClockTime::from_mseconds(duration.mseconds() - 300),
-                        )
-                        .context("Unable to seek media.")?;
-                }
+            // Try to get a lock on the loop mutex
+            if let Ok(mut media) = channel.settings.loop_mutex.lock() {
+                // Replace the media with the local loop or channel loop
+                *media = media_cue.loop_media.or(channel.channel_loop.clone());
 
-            // If nothing is playing, return an error
+            // Otherwise, throw an error
             } else {
-                return Err(anyhow!("Unable to seek media: No media playing."));
+                return Err(anyhow!("Unable to change loop media."));
             }
 
         // Otherwise, throw an error
         } else {
-            return Err(anyhow!("Unable to seek media: Channel not defined."));
+            return Err(anyhow!("Unable to hold media: Channel not defined."));
         }
 
         // Indicate success
         Ok(())
     }
 
-    // A helper function to create a signal watch to handle looping media
-    fn create_loop_callback(
-        playbin: &gst::Element,
-        loop_mutex: Arc<Mutex<Option<String>>>,
-    ) -> Result<gst::bus::BusWatchGuard> {
-        // Try to access the playbin bus
-        let bus = match playbin.bus() {
-            Some(bus) => bus,
-            None => return Err(anyhow!("Unable to set loop media: Invalid bus.")),
-        };
+    /// A function to load media on several existing channels and start every
+    /// channel at the same moment on a shared clock. Any pause or seek of one
+    /// of the channels is applied to every channel in the group.
+    ///
+    /// # Note
+    ///
+    /// Transitions and loop media in the media cues are ignored. The group
+    /// ends for a channel as soon as other media is cued on the channel.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the start time has already
+    /// passed, or if the start time is specified before the network clock
+    /// has synchronized.
+    ///
+    pub fn cue_group(&mut self, cue_group: CueGroup) -> Result<()> {
+        // Make sure the start time can be met, if specified
+        let start_at = cue_group.start_at.map(gst::ClockTime::from_mseconds);
+        if let Some(start_at) = start_at {
+            // Make sure the clock is synchronized with the other instances
+            let clock = self.output_clock.clock();
+            if !clock.is_synced() {
+                return Err(anyhow!(
+                    "Unable to cue group: Network clock not synchronized."
+                ));
+            }
 
-        // Create a week reference to the playbin
-        let channel_weak = playbin.downgrade();
+            // Make sure the start time is in the future
+            if clock.time().is_none_or(|now| start_at <= now) {
+                return Err(anyhow!(
+                    "Unable to cue group: Start time has already passed."
+                ));
+            }
+        }
 
-        // Connect the signal handler for the end of stream notification
-        if let Ok(watch_guard) = bus.add_watch(move |_, msg| {
-            // If the end of stream message is received
-            if let gst::MessageView::Eos(..) = msg.view() {
-                // Wait for access to the current loop media
-                if let Ok(possible_media) = loop_mutex.lock() {
-                    // If the media was specified
-                    if let Some(media) = possible_media.clone() {
-                        // Try to get a strong reference to the channel
-                        let channel = match channel_weak.upgrade() {
-                            Some(channel) => channel,
-                            None => return glib::ControlFlow::Continue, // Fail silently, but try again
-                        };
+        // Start every member from the beginning
+        self.start_new_group(cue_group, 0, PlaybackState::Playing, start_at, None)
+    }
 
-                        // Try to stop any playing media
-                        if let Err(_) = channel.set_state(gst::State::Null) {
-                            // Share the error
-                            error!("Unable to stop previously playing media.");
-                        }
+    /// A function to load media on several existing channels and resume
+    /// every channel together from the provided position (in milliseconds)
+    /// and in the provided state. Playing media is advanced by the time
+    /// spent loading, so that the group resumes from where it would have
+    /// been.
+    ///
+    /// # Note
+    ///
+    /// The start time in the cue group is ignored.
+    ///
+    pub fn resume_group(
+        &mut self,
+        cue_group: CueGroup,
+        position: u64,
+        state: PlaybackState,
+    ) -> Result<()> {
+        self.start_new_group(cue_group, position, state, None, Some(Instant::now()))
+    }
 
-                        // If media was specified, add the loop uri to this channel
-                        channel.set_property("uri", &media);
+    // A helper function to load media on several existing channels and start
+    // every channel together on the shared clock
+    fn start_new_group(
+        &mut self,
+        cue_group: CueGroup,
+        position: u64,
+        state: PlaybackState,
+        start_at: Option<gst::ClockTime>,
+        since: Option<Instant>,
+    ) -> Result<()> {
+        // Make sure there is media in the group
+        if cue_group.media_cues.is_empty() {
+            return Err(anyhow!("Unable to cue group: No media specified."));
+        }
 
-                        // Try to start playing the media
-                        if let Err(_) = channel.set_state(gst::State::Playing) {
-                            // Share the error
-                            error!("Unable to start new media.");
-                        }
-                    }
-                }
+        // Make sure each channel is defined and used only once
+        let mut channel_numbers = Vec::new();
+        for media_cue in cue_group.media_cues.iter() {
+            if !self.channels.contains_key(&media_cue.channel) {
+                return Err(anyhow!(
+                    "Unable to cue group: Channel {} not defined.",
+                    media_cue.channel
+                ));
             }
-
+            if channel_numbers.contains(&media_cue.channel) {
+                return Err(anyhow!(
+                    "Unable to cue group: Channel {} is used more than once.",
+                    media_cue.channel
+                ));
+            }
+            channel_numbers.push(media_cue.channel);
+        }
+
+        // Use the clock shared by every output for every member
+        let clock = self.output_clock.clock().clone();
+
+        // Load the media on each channel
+        let mut members = Vec::new();
+        for media_cue in cue_group.media_cues {
+            // Get the channel (checked above)
+            let Some(channel) = self.channels.get(&media_cue.channel) else {
+                continue;
+            };
+
+            // Load the media paused and switch to the shared clock
+            let generation =
+                MediaPlayback::load_paused(channel, &media_cue.uri, self.instance_clock())?;
+            let playbin = channel.active();
+            MediaPlayback::share_clock(playbin, &clock);
+
+            // Clear the loop media (the group loops instead)
+            if let Ok(mut media) = channel.settings.loop_mutex.lock() {
+                *media = None;
+            }
+
+            // If a fade in was specified, fade to the channel volume
+            if let Some(fade) = media_cue.fade_in {
+                playbin.set_property("volume", 0.0f64);
+                let level = MediaPlayback::current_level(channel);
+                MediaPlayback::start_fade(channel, playbin, level, fade, false);
+            }
+
+            // If a fade out was specified, wait for the end of the media
+            if let Some(fade) = media_cue.fade_out {
+                MediaPlayback::start_fade_out(channel, playbin, fade);
+            }
+
+            // Add the channel to the group
+            members.push(GroupMember {
+                channel: media_cue.channel,
+                deck: playbin.downgrade(),
+                settings: channel.settings.clone(),
+                generation,
+            });
+        }
+
+        // Discard any groups without remaining members
+        self.groups
+            .retain(|group| group.members.iter().any(|member| member.deck().is_some()));
+
+        // Add the new group and start every member together
+        let group = InternalGroup {
+            members,
+            clock,
+            is_looping: cue_group.loop_group,
+            generation: Arc::new(AtomicU64::new(0)),
+        };
+        MediaPlayback::start_group(&group, Some(position), state, start_at, since);
+        self.groups.push(group);
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to list the channels in the same group as the provided
+    /// channel (including the channel itself). If the channel is not in a
+    /// group, only the channel is listed.
+    ///
+    pub fn group_channels(&self, channel_number: u32) -> Vec<u32> {
+        match self.find_group(channel_number) {
+            Some(group) => group
+                .members
+                .iter()
+                .filter(|member| member.deck().is_some())
+                .map(|member| member.channel)
+                .collect(),
+            None => vec![channel_number],
+        }
+    }
+
+    /// A function to change the state of a existing channel
+    ///
+    pub fn change_state(&self, channel_state: ChannelState) -> Result<()> {
+        // If the channel is in a group, change the whole group
+        if let Some(group) = self.find_group(channel_state.channel) {
+            MediaPlayback::change_group_state(group, channel_state.state);
+            return Ok(());
+        }
+
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.get(&channel_state.channel) {
+            // Change every deck with media loaded (to include any transition)
+            for deck in channel.decks.iter() {
+                // Skip decks without media
+                if deck.current_state() == gst::State::Null
+                    && deck.pending_state() == gst::State::VoidPending
+                {
+                    continue;
+                }
+
+                // Match the new state
+                match channel_state.state {
+                    // Switch to playing
+                    PlaybackState::Playing => {
+                        deck.set_state(gst::State::Playing)
+                            .context("Unable to play media.")?;
+                    }
+
+                    // Switch to Paused
+                    PlaybackState::Paused => {
+                        deck.set_state(gst::State::Paused)
+                            .context("Unable to pause media.")?;
+                    }
+                }
+            }
+
+        // Otherwise, throw an error
+        } else {
+            return Err(anyhow!("Unable to change state: Channel not defined."));
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to change the volume of an existing channel
+    ///
+    pub fn change_volume(&self, channel_volume: ChannelVolume) -> Result<()> {
+        // Make sure the volume is valid
+        if !(0.0..=MAX_VOLUME).contains(&channel_volume.volume) {
+            return Err(anyhow!("Unable to change volume: Invalid volume level."));
+        }
+
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.get(&channel_volume.channel) {
+            // Try to save the new volume level
+            if let Ok(mut level) = channel.settings.level_mutex.lock() {
+                *level = channel_volume.volume;
+
+            // Otherwise, throw an error
+            } else {
+                return Err(anyhow!("Unable to change volume level."));
+            }
+
+            // Change the mute setting on every deck, if specified
+            if let Some(mute) = channel_volume.mute {
+                for deck in channel.decks.iter() {
+                    deck.set_property("mute", mute);
+                }
+            }
+
+            // If a fade was specified, fade to the new level
+            if let Some(fade) = channel_volume.fade {
+                MediaPlayback::start_fade(
+                    channel,
+                    channel.active(),
+                    channel_volume.volume,
+                    fade,
+                    false,
+                );
+
+            // Otherwise, cancel any volume fade and change the level immediately
+            // (a fade out waiting for the end of the media is kept)
+            } else {
+                channel
+                    .settings
+                    .volume_generation
+                    .fetch_add(1, Ordering::SeqCst);
+                channel
+                    .active()
+                    .set_property("volume", channel_volume.volume);
+            }
+
+        // Otherwise, throw an error
+        } else {
+            return Err(anyhow!("Unable to change volume: Channel not defined."));
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to seek within the media on an existing channel
+    ///
+    pub fn seek(&self, channel_seek: ChannelSeek) -> Result<()> {
+        // If the channel is in a group, seek the whole group
+        if let Some(group) = self.find_group(channel_seek.channel) {
+            // Keep the current state of the group
+            let state = match group.members.iter().find_map(|member| member.deck()) {
+                Some(deck) if deck.current_state() == gst::State::Playing => PlaybackState::Playing,
+                Some(_) => PlaybackState::Paused,
+                None => return Err(anyhow!("Unable to seek media: No media playing.")),
+            };
+
+            // Seek every member together
+            MediaPlayback::start_group(group, Some(channel_seek.position), state, None, None);
+            return Ok(());
+        }
+
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.get(&channel_seek.channel) {
+            // Check the length of the current media on the channel
+            let playbin = channel.active();
+            if let Some(duration) = playbin.query_duration::<gst::ClockTime>() {
+                // Mark the seek as in progress
+                channel.settings.seek_flag.store(true, Ordering::SeqCst);
+
+                // If there is enough time in the media, seek to that point
+                if duration.mseconds() > channel_seek.position {
+                    playbin
+                        .seek_simple(
+                            gst::SeekFlags::FLUSH,
+                            gst::ClockTime::from_mseconds(channel_seek.position),
+                        )
+                        .context("Unable to seek media.")?;
+
+                // Otherwise, seek to the end
+                } else {
+                    // Seek to the end and pause
+                    playbin
+                        .seek_simple(
+                            gst::SeekFlags::FLUSH,
+                            gst::ClockTime::from_mseconds(duration.mseconds().saturating_sub(300)),
+                        )
+                        .context("Unable to seek media.")?;
+                }
+
+            // If nothing is playing, return an error
+            } else {
+                return Err(anyhow!("Unable to seek media: No media playing."));
+            }
+
+        // Otherwise, throw an error
+        } else {
+            return Err(anyhow!("Unable to seek media: Channel not defined."));
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to update the video frame location and size of an existing
+    /// channel. The change is ignored if the channel isn't defined or has no
+    /// video frame.
+    ///
+    pub fn resize_channel(&mut self, channel_allocation: &ChannelAllocation) {
+        // Make sure there is an existing channel with a video frame
+        if let Some(channel) = self.channels.get_mut(&channel_allocation.channel)
+            && let Some(frame) = channel.media_channel.video_frame.as_mut()
+        {
+            // Update the location and size of the frame
+            frame.top = channel_allocation.video_frame.top;
+            frame.left = channel_allocation.video_frame.left;
+            frame.height = channel_allocation.video_frame.height;
+            frame.width = channel_allocation.video_frame.width;
+
+            // Scale the mixed video to the new size, if mixed
+            if let Some(mixer) = channel.mixer.as_ref() {
+                mixer.resize(frame.width, frame.height);
+            }
+
+            // Move the channel in the window compositor, if any
+            if let Some(compositor) = self.compositors.get(&frame.window_number) {
+                compositor.place_channel(channel_allocation.channel, frame);
+            }
+        }
+    }
+
+    /// A function to update the video frame location of an existing channel
+    /// by one pixel in one direction. The change is ignored if the channel
+    /// isn't defined or has no video frame.
+    ///
+    pub fn align_channel(&mut self, channel_realignment: &ChannelRealignment) {
+        // Make sure there is an existing channel with a video frame
+        if let Some(channel) = self.channels.get_mut(&channel_realignment.channel)
+            && let Some(frame) = channel.media_channel.video_frame.as_mut()
+        {
+            // Change the frame based on the direction change
+            match channel_realignment.direction {
+                Direction::Up => frame.top -= 1,
+                Direction::Down => frame.top += 1,
+                Direction::Left => frame.left -= 1,
+                Direction::Right => frame.left += 1,
+            }
+
+            // Move the channel in the window compositor, if any
+            if let Some(compositor) = self.compositors.get(&frame.window_number) {
+                compositor.place_channel(channel_realignment.channel, frame);
+            }
+        }
+    }
+
+    /// A function to change the stacking order of an existing channel. The
+    /// function returns the updated definition of every channel in the
+    /// window (as the layer of each channel is renumbered).
+    ///
+    pub fn change_layer(&mut self, channel_layer: &ChannelLayer) -> Result<ChannelList> {
+        // Make sure the channel has a video frame
+        let window_number = self.frame_window(channel_layer.channel, "Unable to change layer")?;
+
+        // Move the channel to the new position and renumber every channel
+        let mut order = self.window_order(window_number);
+        if let Some(current) = order
+            .iter()
+            .position(|channel| *channel == channel_layer.channel)
+        {
+            let position = channel_layer.layer.position(current, order.len());
+            order.remove(current);
+            order.insert(position, channel_layer.channel);
+        }
+        self.renumber(&order);
+
+        // Change the order in the window compositor, if any
+        if let Some(compositor) = self.compositors.get_mut(&window_number) {
+            compositor.change_layer(channel_layer.channel, &channel_layer.layer);
+        }
+
+        // Return the updated definitions
+        Ok(order
+            .iter()
+            .filter_map(|channel| self.channels.get(channel))
+            .map(|channel| channel.media_channel.clone())
+            .collect())
+    }
+
+    /// A function to change the opacity of an existing channel. The function
+    /// returns the updated definition of the channel.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the opacity is invalid, or if the
+    /// channel is shown with a video overlay (which is always drawn opaque)
+    /// instead of mixed by a window compositor.
+    ///
+    pub fn change_opacity(&mut self, channel_opacity: &ChannelOpacity) -> Result<MediaChannel> {
+        // Make sure the opacity is valid
+        if !(0.0..=1.0).contains(&channel_opacity.opacity) {
+            return Err(anyhow!("Unable to change opacity: Invalid opacity."));
+        }
+
+        // Make sure the channel has a video frame
+        let window_number =
+            self.frame_window(channel_opacity.channel, "Unable to change opacity")?;
+
+        // Change the opacity in the window compositor, if any
+        if let Some(compositor) = self.compositors.get_mut(&window_number) {
+            compositor.change_opacity(
+                channel_opacity.channel,
+                channel_opacity.opacity,
+                channel_opacity.fade.clone(),
+            );
+
+        // Otherwise, make sure the user interface can change the opacity
+        } else if self.video_output == VideoOutput::Overlay {
+            return Err(anyhow!(
+                "Unable to change opacity: Window compositor required for this display."
+            ));
+        }
+
+        // Update the definition
+        self.update_definition(channel_opacity.channel, |media_channel| {
+            media_channel.opacity = Some(channel_opacity.opacity);
+        })
+    }
+
+    /// A function to hide or show the video frame of an existing channel. The
+    /// function returns the updated definition of the channel.
+    ///
+    pub fn change_visibility(
+        &mut self,
+        channel_visibility: &ChannelVisibility,
+    ) -> Result<MediaChannel> {
+        // Make sure the channel has a video frame
+        let window_number =
+            self.frame_window(channel_visibility.channel, "Unable to change visibility")?;
+
+        // Hide or show the channel in the window compositor, if any
+        if let Some(compositor) = self.compositors.get_mut(&window_number) {
+            compositor.change_visibility(channel_visibility.channel, channel_visibility.visible);
+        }
+
+        // Update the definition
+        self.update_definition(channel_visibility.channel, |media_channel| {
+            media_channel.hidden = !channel_visibility.visible;
+        })
+    }
+
+    /// A function to change the idle background of an existing channel
+    ///
+    /// # Note
+    ///
+    /// The background is only changed here for a channel in a window
+    /// compositor. For any other channel, the background must also be passed
+    /// to the user interface, which shows the background in place of the
+    /// video.
+    ///
+    pub fn change_background(
+        &mut self,
+        channel_number: u32,
+        background: Option<Background>,
+    ) -> Result<()> {
+        // Make sure there is an existing channel
+        let Some(channel) = self.channels.get_mut(&channel_number) else {
+            return Err(anyhow!("Unable to change background: Channel not defined."));
+        };
+
+        // Make sure the channel has a video frame
+        if channel.media_channel.video_frame.is_none() {
+            return Err(anyhow!(
+                "Unable to change background: No video frame defined."
+            ));
+        }
+
+        // Make sure the image exists, if specified
+        if let Some(Background::Image { path }) = background.as_ref()
+            && !Path::new(path).is_file()
+        {
+            return Err(anyhow!("Unable to change background: Image not found."));
+        }
+
+        // Replace the background in the window compositor, if composited
+        if let Ok(idle_layer) = channel.settings.idle_mutex.lock()
+            && let Some(idle_layer) = idle_layer.as_ref()
+        {
+            idle_layer.set_background(background.clone())?;
+        }
+
+        // Update the definition
+        channel.media_channel.idle_background = background;
+        Ok(())
+    }
+
+    /// A function to report the current status of an existing channel
+    ///
+    pub fn channel_status(&self, channel_number: u32) -> Result<ChannelStatus> {
+        // Make sure there is an existing channel
+        if let Some(channel) = self.channels.get(&channel_number) {
+            // Compose and return the status
+            Ok(MediaPlayback::compose_status(channel))
+
+        // Otherwise, throw an error
+        } else {
+            Err(anyhow!("Unable to get status: Channel not defined."))
+        }
+    }
+
+    /// A function to check if any channel displays video in a window
+    ///
+    pub fn is_window_used(&self, window_number: u32) -> bool {
+        self.channels.values().any(|channel| {
+            channel
+                .media_channel
+                .video_frame
+                .as_ref()
+                .is_some_and(|frame| frame.window_number == window_number)
+        })
+    }
+
+    // A helper method to change the definition of an existing channel and
+    // return the updated definition
+    fn update_definition(
+        &mut self,
+        channel_number: u32,
+        change: impl FnOnce(&mut MediaChannel),
+    ) -> Result<MediaChannel> {
+        match self.channels.get_mut(&channel_number) {
+            Some(channel) => {
+                change(&mut channel.media_channel);
+                Ok(channel.media_channel.clone())
+            }
+            None => Err(anyhow!("Unable to update channel: Channel not defined.")),
+        }
+    }
+
+    // A helper method to list the channels in a window from the bottom to
+    // the top of the stacking order
+    fn window_order(&self, window_number: u32) -> Vec<u32> {
+        let mut order: Vec<(Option<u32>, u32)> = self
+            .channels
+            .values()
+            .filter(|channel| MediaPlayback::window_number(channel) == Some(window_number))
+            .map(|channel| (channel.media_channel.layer, channel.media_channel.channel))
+            .collect();
+        order.sort_by_key(|(layer, channel)| (layer.unwrap_or(u32::MAX), *channel));
+        order.into_iter().map(|(_, channel)| channel).collect()
+    }
+
+    // A helper method to number the layer of each channel from the bottom
+    // (in the order provided)
+    fn renumber(&mut self, order: &[u32]) {
+        for (index, channel_number) in order.iter().enumerate() {
+            if let Some(channel) = self.channels.get_mut(channel_number) {
+                channel.media_channel.layer = Some(index as u32);
+            }
+        }
+    }
+
+    // A helper method to place a new channel in the stacking order of its
+    // window (at the layer provided, or above every other channel) and match
+    // the order in the window compositor, if any
+    fn restack(&mut self, channel_number: u32, layer: Option<u32>) {
+        // Make sure the channel has a video frame
+        let Some(window_number) = self
+            .channels
+            .get(&channel_number)
+            .and_then(MediaPlayback::window_number)
+        else {
+            return;
+        };
+
+        // Insert the channel among the other channels and renumber every channel
+        let mut order: Vec<u32> = self
+            .window_order(window_number)
+            .into_iter()
+            .filter(|channel| *channel != channel_number)
+            .collect();
+        let position = layer.map_or(order.len(), |layer| (layer as usize).min(order.len()));
+        order.insert(position, channel_number);
+        self.renumber(&order);
+
+        // Move the channel in the window compositor, if any
+        if let Some(compositor) = self.compositors.get_mut(&window_number) {
+            compositor.change_layer(channel_number, &LayerChange::Index(position as u32));
+        }
+    }
+
+    // A helper method to find the window of the video frame for a channel
+    // (with the action described in any error)
+    fn frame_window(&self, channel_number: u32, action: &str) -> Result<u32> {
+        match self.channels.get(&channel_number) {
+            Some(channel) => match channel.media_channel.video_frame.as_ref() {
+                Some(frame) => Ok(frame.window_number),
+                None => Err(anyhow!("{}: No video frame defined.", action)),
+            },
+            None => Err(anyhow!("{}: Channel not defined.", action)),
+        }
+    }
+
+    /// A function to check if a channel is mixed by a window compositor
+    /// (rather than shown separately in the window)
+    ///
+    pub fn is_composited(&self, channel_number: u32) -> bool {
+        self.compositors
+            .values()
+            .any(|compositor| compositor.has_channel(channel_number))
+    }
+
+    /// A function to create, update, or remove the compositor for a window
+    /// to match the window definition. If a new compositor is created, the
+    /// function returns the video sink for the mixed video and the
+    /// confirmation to send once the sink is embedded in the window. The
+    /// output of the new compositor does not start until wait_for_compositor()
+    /// is called and the compositor is ready.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the window is used by a channel
+    /// and the compositor would be created or removed (the channels must be
+    /// redefined to change how they are shown), or if the compositor is
+    /// specified without the window dimensions.
+    ///
+    pub fn update_compositor(
+        &mut self,
+        window: &WindowDefinition,
+    ) -> Result<Option<(gst::Element, VideoReady)>> {
+        // If headless, there is nothing to show
+        if self.video_output == VideoOutput::Headless {
+            return Ok(None);
+        }
+
+        // Switch based on the current compositor
+        let is_existing = self.compositors.contains_key(&window.window_number);
+        match (window.compositor, is_existing) {
+            // Update the existing compositor
+            (true, true) => {
+                if let Some(compositor) = self.compositors.get_mut(&window.window_number) {
+                    compositor.update(window)?;
+                }
+                Ok(None)
+            }
+
+            // If no change is needed, do nothing
+            (false, false) => Ok(None),
+
+            // Otherwise, make sure the window is not in use
+            _ if self.is_window_used(window.window_number) => Err(anyhow!(
+                "Unable to change compositor: Window is used by a channel."
+            )),
+
+            // Create a new compositor
+            (true, false) => {
+                let (ready, ready_receive) = VideoReady::new();
+                let compositor = WindowCompositor::new(
+                    window,
+                    self.video_output,
+                    &self.output_clock,
+                    ready_receive,
+                )?;
+                let video_sink = compositor.video_sink();
+                self.compositors.insert(window.window_number, compositor);
+                self.waiting
+                    .remove(&ReadyOutput::Window(window.window_number));
+                Ok(Some((video_sink, ready)))
+            }
+
+            // Remove the existing compositor
+            (false, true) => {
+                self.compositors.remove(&window.window_number);
+                self.waiting
+                    .remove(&ReadyOutput::Window(window.window_number));
+                Ok(None)
+            }
+        }
+    }
+
+    /// A method to wait in the background for the user interface to embed
+    /// the video sink of a new window compositor. Once the user interface
+    /// confirms (or after the timeout), next_ready() returns the window to
+    /// start with start_ready(). Compositors already started (or waiting) are
+    /// unchanged.
+    ///
+    pub fn wait_for_compositor(&mut self, window_number: u32) -> Result<()> {
+        // Take the confirmation line for the compositor, if not yet waiting
+        let ready_receive = match self.compositors.get_mut(&window_number) {
+            Some(compositor) => compositor.take_ready(),
+            None => return Err(anyhow!("Unable to start compositor: Window not mixed.")),
+        };
+
+        // Wait for the user interface to embed the video sink, if needed
+        if let Some(ready_receive) = ready_receive {
+            self.wait_for_ready(ReadyOutput::Window(window_number), ready_receive);
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A function to remove the compositor for a window, if there is one
+    ///
+    pub fn remove_compositor(&mut self, window_number: u32) {
+        self.compositors.remove(&window_number);
+        self.waiting.remove(&ReadyOutput::Window(window_number));
+    }
+
+    /// A function to return the most recent media cue on a channel, if any
+    ///
+    pub fn last_cue(&self, channel_number: u32) -> Option<MediaCue> {
+        self.channels
+            .get(&channel_number)
+            .and_then(|channel| channel.cue_mutex.lock().ok())
+            .and_then(|cue| cue.clone())
+    }
+
+    /// A function to report the current status of every channel, sorted by
+    /// channel number
+    ///
+    pub fn all_status(&self) -> ChannelStatusList {
+        // Compose the status of every channel
+        let mut status_list: ChannelStatusList = self
+            .channels
+            .values()
+            .map(|channel| MediaPlayback::compose_status(channel))
+            .collect();
+
+        // Sort the channels and return the list
+        status_list.sort_by_key(|status| status.media_channel.channel);
+        status_list
+    }
+
+    // A helper function to create a new channel, connected to the outputs and
+    // the window compositor (if any), but not yet added to the channels
+    fn create_channel(
+        &mut self,
+        media_channel: MediaChannel,
+    ) -> Result<(InternalChannel, Option<VideoStream>)> {
+        // Send the video to the window compositor, if the window has one
+        let is_composited = media_channel
+            .video_frame
+            .as_ref()
+            .is_some_and(|frame| self.compositors.contains_key(&frame.window_number));
+        let video_output = match is_composited {
+            true => VideoOutput::Composited,
+            false => self.video_output,
+        };
+
+        // Create a unique name for the outputs (so that nothing connects to
+        // the outputs of an earlier definition of the channel)
+        let name = ChannelMixer::unique_name(media_channel.channel);
+
+        // Create the mixer for the channel output, if the channel allows
+        // transitions
+        let mixer = if media_channel.transitions {
+            Some(Arc::new(ChannelMixer::new(
+                &media_channel,
+                video_output,
+                name.clone(),
+                &self.output_clock,
+            )?))
+        } else {
+            None
+        };
+
+        // Create the outputs for a channel without a mixer (the only deck
+        // plays directly to the outputs)
+        let direct_sink = match mixer {
+            Some(_) => None,
+            None => ChannelMixer::make_direct_sink(&media_channel, video_output, &name)?,
+        };
+
+        // If a video window was specified (and the video is shown separately)
+        let mut video_stream = None;
+        let mut ready_receive = None;
+        if let Some(video_frame) = media_channel.video_frame.clone()
+            && matches!(video_output, VideoOutput::Overlay | VideoOutput::Widget)
+        {
+            // Compose the allocation
+            let allocation = VideoFrame {
+                top: video_frame.top,
+                left: video_frame.left,
+                height: video_frame.height,
+                width: video_frame.width,
+            };
+
+            // Try to get the video sink from the mixer (or the direct output)
+            let video_sink = match mixer
+                .as_ref()
+                .and_then(|mixer| mixer.video_sink())
+                .or(direct_sink.clone())
+            {
+                Some(sink) => sink,
+                _ => return Err(anyhow!("Unable to create video stream.")),
+            };
+
+            // Send the new video stream to the user interface (and wait for
+            // confirmation before starting the outputs)
+            let (ready, receive) = VideoReady::new();
+            ready_receive = Some(receive);
+            video_stream = Some(VideoStream {
+                window_number: video_frame.window_number,
+                channel: media_channel.channel,
+                allocation,
+                video_sink,
+                idle_background: media_channel.idle_background.clone(),
+                ready,
+            });
+        } // Otherwise, any window creation (if needed) is left to gstreamer
+
+        // Create the settings to share with the playback callbacks
+        let settings = ChannelSettings {
+            active_deck: Arc::new(AtomicUsize::new(0)),
+            loop_mutex: Arc::new(Mutex::new(media_channel.loop_media.clone())),
+            seek_flag: Arc::new(AtomicBool::new(false)),
+            loop_flag: Arc::new(AtomicBool::new(false)),
+            end_flag: Arc::new(AtomicBool::new(false)),
+            level_mutex: Arc::new(Mutex::new(1.0)), // default to the original level of the media
+            fade_generation: Arc::new(AtomicU64::new(0)),
+            volume_generation: Arc::new(AtomicU64::new(0)),
+            transition_generation: Arc::new(AtomicU64::new(0)),
+            ending_mutex: Arc::new(Mutex::new(None)),
+            is_idle: Arc::new(AtomicBool::new(true)), // start with the idle background shown, until media plays
+            idle_mutex: Arc::new(Mutex::new(None)),
+            interface_send: video_stream.as_ref().map(|_| self.interface_send.clone()),
+            channel: media_channel.channel,
+        };
+
+        // Create each deck for the channel
+        let mut decks = Vec::new();
+        let mut watch_guards = Vec::new();
+        let deck_count = if mixer.is_some() { DECK_COUNT } else { 1 };
+        for deck in 0..deck_count {
+            // Create a new playbin and connect it to the mixer (or the outputs)
+            let playbin = gst::ElementFactory::make_with_name("playbin", None)
+                .context("Unable to create playbin.")?;
+            match mixer.as_ref() {
+                Some(mixer) => mixer.connect_deck(&playbin, deck)?,
+                None => ChannelMixer::connect_direct(
+                    &playbin,
+                    &media_channel.audio_device,
+                    direct_sink.as_ref(),
+                )?,
+            }
+
+            // Play on the network clock, if specified
+            MediaPlayback::release_clock(&playbin, self.instance_clock());
+
+            // Create the loop media and playback event callback
+            watch_guards.push(MediaPlayback::create_bus_callback(
+                media_channel.channel,
+                deck,
+                &playbin,
+                settings.clone(),
+                self.event_send.clone(),
+            )?);
+
+            // Create the gapless loop media callback
+            MediaPlayback::create_loop_callback(deck, &playbin, settings.clone());
+
+            // Add the deck to the list
+            decks.push(playbin);
+        }
+
+        // Compose the channel and start with the first deck visible and audible
+        let channel = InternalChannel {
+            channel_loop: media_channel.loop_media.clone(),
+            media_channel,
+            decks,
+            mixer,
+            settings,
+            preload_mutex: Mutex::new(None),
+            cue_mutex: Mutex::new(None),
+            watch_guards,
+            ready_mutex: Mutex::new(ready_receive),
+        };
+        channel.show_deck(0);
+
+        // Start the outputs now, unless waiting for the user interface
+        if video_stream.is_none() {
+            MediaPlayback::start_outputs(&channel)?;
+        }
+
+        // Connect the mixed video to the window compositor, if specified
+        if let Some(video_frame) = channel.media_channel.video_frame.as_ref()
+            && let Some(compositor) = self.compositors.get_mut(&video_frame.window_number)
+        {
+            // Use the mixed video from the channel mixer (or receive the
+            // video from the only deck)
+            let source = match channel.mixer.as_ref().and_then(|mixer| mixer.video_bin()) {
+                Some(bin) => Ok(bin.upcast()),
+                None => WindowCompositor::source_for(&name),
+            };
+            match source.and_then(|source| {
+                compositor.add_channel(
+                    channel.media_channel.channel,
+                    source,
+                    video_frame,
+                    channel.media_channel.idle_background.clone(),
+                )
+            }) {
+                // Share the idle background with the playback callbacks (showing
+                // the background only if the media has not already started)
+                Ok(idle_layer) => {
+                    if let Ok(mut current) = channel.settings.idle_mutex.lock() {
+                        idle_layer.set_idle(channel.settings.is_idle.load(Ordering::SeqCst));
+                        *current = Some(idle_layer);
+                    }
+
+                    // Apply the opacity and visibility of the channel
+                    let media_channel = &channel.media_channel;
+                    compositor.change_opacity(
+                        media_channel.channel,
+                        media_channel.opacity.unwrap_or(1.0),
+                        None,
+                    );
+                    compositor.change_visibility(media_channel.channel, !media_channel.hidden);
+                }
+
+                // Stop the new channel before discarding it
+                Err(error) => {
+                    MediaPlayback::stop_channel(&channel).unwrap_or(());
+                    return Err(error);
+                }
+            }
+        }
+
+        // Return the channel and the video stream, if created
+        Ok((channel, video_stream))
+    }
+
+    // A helper method to wait in the background for the user interface to
+    // embed a video sink (or for the timeout, whichever comes first) and then
+    // pass the output to next_ready()
+    fn wait_for_ready(&mut self, output: ReadyOutput, ready_receive: oneshot::Receiver<()>) {
+        // Note the new wait (replacing any previous wait for the output)
+        self.ready_key += 1;
+        let key = self.ready_key;
+        self.waiting.insert(output, key);
+
+        // Wait in the background
+        let ready_send = self.ready_send.clone();
+        tokio::spawn(async move {
+            // Describe the output
+            let description = match output {
+                ReadyOutput::Channel(channel_number) => format!("channel {}", channel_number),
+                ReadyOutput::Window(window_number) => format!("window {}", window_number),
+            };
+
+            // Wait for the confirmation or the timeout
+            match timeout(Duration::from_millis(READY_TIMEOUT), ready_receive).await {
+                Ok(Ok(())) => (),
+                Ok(Err(_)) => warn!("Video for {} was not embedded in a window.", description),
+                Err(_) => warn!(
+                    "Timed out waiting for the video for {} to be embedded in a window.",
+                    description
+                ),
+            }
+
+            // Pass the output along to be started
+            ready_send.send((output, key)).await.unwrap_or(());
+        });
+    }
+
+    // A helper function to start the outputs of a channel, including any loop
+    // media
+    fn start_outputs(channel: &InternalChannel) -> Result<()> {
+        // Start the mixer output, if any
+        if let Some(mixer) = channel.mixer.as_ref() {
+            mixer.start()?;
+        }
+
+        // If loop media was specified
+        if let Some(loop_uri) = channel.channel_loop.as_ref() {
+            // Set the first deck to the loop uri
+            channel.decks[0].set_property("uri", loop_uri);
+
+            // Start playing the media
+            channel.decks[0]
+                .set_state(gst::State::Playing)
+                .context("Unable to start playing media.")?;
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    // A helper function to stop the media on a channel. If a fade out is
+    // specified, the media is stopped once the fade is complete.
+    fn stop_media(channel: &InternalChannel, fade_out: Option<AudioFade>) -> Result<()> {
+        // Cancel any transition in progress
+        channel
+            .settings
+            .transition_generation
+            .fetch_add(1, Ordering::SeqCst);
+
+        // Clear any preloaded media
+        if let Ok(mut preload) = channel.preload_mutex.lock() {
+            *preload = None;
+        }
+
+        // Stop the media on every idle deck immediately
+        let active = channel.settings.active_deck.load(Ordering::SeqCst);
+        for (index, deck) in channel.decks.iter().enumerate() {
+            if index != active {
+                deck.set_state(gst::State::Null)
+                    .context("Unable to stop media.")?;
+            }
+        }
+        channel.show_deck(active);
+
+        // If a fade was specified, fade the channel to silence and then stop
+        if let Some(fade) = fade_out {
+            MediaPlayback::start_fade(channel, channel.active(), 0.0, fade, true);
+
+        // Otherwise, stop the channel immediately
+        } else {
+            // Cancel any fade in progress
+            channel.settings.cancel_fades();
+
+            // Stop the media and show the idle background
+            channel
+                .active()
+                .set_state(gst::State::Null)
+                .context("Unable to stop media.")?;
+            channel.settings.set_idle(true);
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    // A helper function to cancel everything in progress on a channel and
+    // stop the media on every deck
+    fn stop_channel(channel: &InternalChannel) -> Result<()> {
+        // Cancel any transition or fade in progress
+        channel
+            .settings
+            .transition_generation
+            .fetch_add(1, Ordering::SeqCst);
+        channel.settings.cancel_fades();
+
+        // Stop the media on every deck
+        for deck in channel.decks.iter() {
+            deck.set_state(gst::State::Null)
+                .context("Unable to stop media.")?;
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    // A helper function to return the window number of a channel, if any
+    fn window_number(channel: &InternalChannel) -> Option<u32> {
+        channel
+            .media_channel
+            .video_frame
+            .as_ref()
+            .map(|frame| frame.window_number)
+    }
+
+    // A helper function to stop any media on a channel and load new media
+    // paused on the active deck at the channel volume. Any transition, fade,
+    // or preloaded media is discarded. Returns the new transition generation.
+    fn load_paused(
+        channel: &InternalChannel,
+        uri: &str,
+        clock: Option<&gst::Clock>,
+    ) -> Result<u64> {
+        // Cancel any transition or fade in progress
+        let generation = channel
+            .settings
+            .transition_generation
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        channel.settings.cancel_fades();
+
+        // Clear any loop media or preloaded media
+        channel.settings.loop_flag.store(false, Ordering::SeqCst);
+        channel.settings.end_flag.store(false, Ordering::SeqCst);
+        if let Ok(mut preload) = channel.preload_mutex.lock() {
+            *preload = None;
+        }
+
+        // Stop the media on every deck
+        for deck in channel.decks.iter() {
+            deck.set_state(gst::State::Null)
+                .context("Unable to stop media.")?;
+        }
+
+        // Load the media on the active deck at the channel volume
+        let playbin = channel.active();
+        MediaPlayback::release_clock(playbin, clock);
+        playbin.set_property("uri", uri);
+        playbin.set_property("volume", MediaPlayback::current_level(channel));
+        playbin
+            .set_state(gst::State::Paused)
+            .context("Unable to load media.")?;
+
+        // Show only the active deck
+        channel.show_deck(channel.settings.active_deck.load(Ordering::SeqCst));
+        channel.settings.set_idle(false);
+
+        // Return the new generation
+        Ok(generation)
+    }
+
+    // A helper function to return the clock shared with other instances, if
+    // specified
+    fn instance_clock(&self) -> Option<&gst::Clock> {
+        self.network_clock
+            .as_ref()
+            .map(|network_clock| network_clock.clock())
+    }
+
+    // A helper function to return a deck to the clock for this instance (the
+    // network clock, if specified, or otherwise a clock chosen by the deck).
+    // The deck sets the base time whenever it starts playing.
+    fn release_clock(deck: &gst::Element, clock: Option<&gst::Clock>) {
+        if let Some(pipeline) = deck.downcast_ref::<gst::Pipeline>() {
+            match clock {
+                Some(clock) => pipeline.use_clock(Some(clock)),
+                None => pipeline.auto_clock(),
+            }
+        }
+        deck.set_start_time(gst::ClockTime::ZERO);
+    }
+
+    // A helper function to switch a deck to the shared clock of a group (the
+    // group sets the base time whenever the group starts playing)
+    fn share_clock(deck: &gst::Element, clock: &gst::Clock) {
+        if let Some(pipeline) = deck.downcast_ref::<gst::Pipeline>() {
+            pipeline.use_clock(Some(clock));
+        }
+        deck.set_start_time(gst::ClockTime::NONE);
+    }
+
+    // A helper function to find the group which includes a channel, if any
+    fn find_group(&self, channel_number: u32) -> Option<&InternalGroup> {
+        self.groups.iter().rev().find(|group| {
+            group
+                .members
+                .iter()
+                .any(|member| member.channel == channel_number && member.deck().is_some())
+        })
+    }
+
+    // A helper function to change the state of every member of a group. The
+    // members are paused immediately, and resume together from the position
+    // of the first member.
+    fn change_group_state(group: &InternalGroup, state: PlaybackState) {
+        match state {
+            // Resume every member together
+            PlaybackState::Playing => {
+                let position = group
+                    .members
+                    .iter()
+                    .find_map(|member| member.deck())
+                    .and_then(|deck| deck.query_position::<gst::ClockTime>())
+                    .map(|position| position.mseconds());
+                MediaPlayback::start_group(group, position, PlaybackState::Playing, None, None);
+            }
+
+            // Cancel any pending start and pause every member
+            PlaybackState::Paused => {
+                group.generation.fetch_add(1, Ordering::SeqCst);
+                for deck in group.members.iter().filter_map(|member| member.deck()) {
+                    if deck.set_state(gst::State::Paused).is_err() {
+                        error!("Unable to pause media.");
+                    }
+                }
+            }
+        }
+    }
+
+    // A helper function to pause every member of a group, seek each member
+    // to the position (if specified), and then start every member at the same
+    // moment on the shared clock (at the start time, if specified). If the
+    // time the position was recorded is specified, playing media is advanced
+    // by the time since. Any earlier change to the group is cancelled.
+    //
+    // If the group loops, every member plays a segment (rather than to the
+    // end of the stream). The longest media loops without a gap, and shorter
+    // media is prerolled as soon as it ends to wait for the next loop, so
+    // that every member (on every instance) restarts together.
+    fn start_group(
+        group: &InternalGroup,
+        position: Option<u64>,
+        state: PlaybackState,
+        start_at: Option<gst::ClockTime>,
+        since: Option<Instant>,
+    ) {
+        // Claim a new group generation (and cancel any older change)
+        let generation = group.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let group_generation = group.generation.clone();
+
+        // Pause every member while the group is synchronized
+        for member in group.members.iter() {
+            member.settings.end_flag.store(false, Ordering::SeqCst);
+            if let Some(deck) = member.deck()
+                && deck.set_state(gst::State::Paused).is_err()
+            {
+                error!("Unable to pause media.");
+            }
+        }
+
+        // Copy the details of the group
+        let members = group.members.clone();
+        let clock = group.clock.clone();
+        let is_looping = group.is_looping;
+
+        // Spawn the synchronization in the background
+        tokio::spawn(async move {
+            // Wait for every member to load
+            if !MediaPlayback::wait_for_group(&members, &group_generation, generation).await {
+                return;
+            }
+
+            // If a position was specified, seek every member and wait again
+            let mut offset = 0;
+            if let Some(mut position) = position {
+                // Advance playing media by the time since the position was recorded
+                if let Some(since) = since
+                    && state == PlaybackState::Playing
+                {
+                    position += since.elapsed().as_millis() as u64;
+                }
+                for member in members.iter() {
+                    if let Some(deck) = member.deck() {
+                        MediaPlayback::seek_member(member, &deck, position, is_looping);
+                    }
+                }
+                if !MediaPlayback::wait_for_group(&members, &group_generation, generation).await {
+                    return;
+                }
+                offset = position;
+            }
+
+            // Stop here if the group should remain paused
+            if state == PlaybackState::Paused {
+                return;
+            }
+
+            // Start every member at the same moment on the shared clock
+            let delay = gst::ClockTime::from_mseconds(GROUP_START_DELAY);
+            let start = match start_at {
+                Some(start) => start,
+                None => clock.time().unwrap_or(gst::ClockTime::ZERO) + delay,
+            };
+            for deck in members.iter().filter_map(|member| member.deck()) {
+                deck.set_base_time(start);
+                if deck.set_state(gst::State::Playing).is_err() {
+                    error!("Unable to start playing media.");
+                }
+            }
+
+            // Stop here unless the group loops
+            if !is_looping {
+                return;
+            }
+
+            // Find the longest media (which sets the length of every loop)
+            let durations: Vec<Option<gst::ClockTime>> = members
+                .iter()
+                .map(|member| {
+                    member
+                        .deck()
+                        .and_then(|deck| deck.query_duration::<gst::ClockTime>())
+                })
+                .collect();
+            let Some(longest) = durations.iter().flatten().max().copied() else {
+                warn!("Unable to loop group: Unknown media length.");
+                return;
+            };
+            let leader = durations
+                .iter()
+                .position(|duration| *duration == Some(longest))
+                .unwrap_or(0);
+
+            // Note the start of the next loop (from the position of the first loop)
+            let mut next = start + longest.saturating_sub(gst::ClockTime::from_mseconds(offset));
+
+            // Restart each member as the media ends
+            let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+            loop {
+                // Wait for the next check
+                timer.tick().await;
+
+                // Stop if the group changed or no members remain
+                if group_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                if members.iter().all(|member| member.deck().is_none()) {
+                    return;
+                }
+
+                // Preroll shorter media as soon as it ends, to start at the next loop
+                for (index, member) in members.iter().enumerate() {
+                    if durations[index] == Some(longest) {
+                        continue;
+                    }
+                    if let Some(deck) = member.deck()
+                        && member.settings.end_flag.swap(false, Ordering::SeqCst)
+                    {
+                        if deck.set_state(gst::State::Paused).is_err() {
+                            error!("Unable to pause media.");
+                        }
+                        MediaPlayback::seek_member(member, &deck, 0, true);
+                        deck.set_base_time(next);
+                        if deck.set_state(gst::State::Playing).is_err() {
+                            error!("Unable to start playing media.");
+                        }
+                    }
+                }
+
+                // Continue the longest media from the beginning without a gap
+                for (index, member) in members.iter().enumerate() {
+                    if durations[index] != Some(longest) {
+                        continue;
+                    }
+                    if let Some(deck) = member.deck()
+                        && member.settings.end_flag.swap(false, Ordering::SeqCst)
+                    {
+                        // Start the next segment where the last segment ended
+                        if deck
+                            .seek_simple(gst::SeekFlags::SEGMENT, gst::ClockTime::ZERO)
+                            .is_err()
+                        {
+                            error!("Unable to loop media on channel {}.", member.channel);
+                        }
+
+                        // Schedule the loop after this loop (once per loop)
+                        if index == leader {
+                            next += longest;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    // A helper function to wait until every remaining member of a group has
+    // loaded its media (or until the timeout). Returns false if the group
+    // changed or no members remain.
+    async fn wait_for_group(
+        members: &[GroupMember],
+        group_generation: &AtomicU64,
+        generation: u64,
+    ) -> bool {
+        // Check the members periodically
+        let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+        for _ in 0..(PRELOAD_TIMEOUT / FADE_INTERVAL) {
+            // Wait for the next check
+            timer.tick().await;
+
+            // Stop if the group changed
+            if group_generation.load(Ordering::SeqCst) != generation {
+                return false;
+            }
+
+            // Stop if no members remain
+            let decks: Vec<gst::Element> =
+                members.iter().filter_map(|member| member.deck()).collect();
+            if decks.is_empty() {
+                return false;
+            }
+
+            // Continue once every deck is loaded
+            if decks.iter().all(|deck| {
+                deck.current_state() != gst::State::Null
+                    && deck.pending_state() == gst::State::VoidPending
+            }) {
+                return true;
+            }
+        }
+
+        // Continue anyway after the timeout
+        warn!("Timed out waiting for group media to load.");
+        true
+    }
+
+    // A helper function to seek the deck of a group member (seeking close to
+    // the end if the position is beyond the end of the media). If the group
+    // loops, the deck plays a segment which ends without an end of stream.
+    fn seek_member(member: &GroupMember, deck: &gst::Element, position: u64, is_looping: bool) {
+        // Limit the position to the length of the media
+        let position = match deck.query_duration::<gst::ClockTime>() {
+            Some(duration) if duration.mseconds() <= position => {
+                duration.mseconds().saturating_sub(300)
+            }
+            _ => position,
+        };
+
+        // Mark the seek as in progress and seek to the position
+        let mut flags = gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE;
+        if is_looping {
+            flags |= gst::SeekFlags::SEGMENT;
+        }
+        member.settings.seek_flag.store(true, Ordering::SeqCst);
+        if deck
+            .seek_simple(flags, gst::ClockTime::from_mseconds(position))
+            .is_err()
+        {
+            error!("Unable to seek media on channel {}.", member.channel);
+        }
+    }
+
+    // A helper function to get the current volume level of a channel
+    fn current_level(channel: &InternalChannel) -> f64 {
+        match channel.settings.level_mutex.lock() {
+            Ok(level) => *level,
+            _ => 1.0, // default to the original level of the media
+        }
+    }
+
+    // A helper function to fade the volume of a deck to a new level in the
+    // background, and optionally stop the media when the fade is complete.
+    // Any volume fade already in progress on the channel is cancelled. A fade
+    // which stops the media also cancels any fade out.
+    fn start_fade(
+        channel: &InternalChannel,
+        deck: &gst::Element,
+        target: f64,
+        fade: AudioFade,
+        stop_after: bool,
+    ) {
+        // Claim a new generation (and cancel any older fade)
+        let fade_generation = if stop_after {
+            channel
+                .settings
+                .volume_generation
+                .fetch_add(1, Ordering::SeqCst);
+            channel.settings.fade_generation.clone()
+        } else {
+            channel.settings.volume_generation.clone()
+        };
+        let generation = fade_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        // Note the starting level and the level to restore after stopping
+        let start = deck.property::<f64>("volume");
+        let restore = MediaPlayback::current_level(channel);
+
+        // Copy the settings and create a weak reference to the playbin
+        let settings = channel.settings.clone();
+        let playbin_weak = deck.downgrade();
+
+        // Spawn the fade in the background
+        tokio::spawn(async move {
+            // Calculate the number of steps in the fade
+            let steps = (fade.duration / FADE_INTERVAL).max(1);
+            let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+            timer.tick().await; // the first tick completes immediately
+
+            // Step through the fade
+            for step in 1..=steps {
+                // Wait for the next step
+                timer.tick().await;
+
+                // Stop if a newer fade has started
+                if fade_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                // Try to get a strong reference to the playbin
+                let playbin = match playbin_weak.upgrade() {
+                    Some(playbin) => playbin,
+                    None => return,
+                };
+
+                // Set the new volume level
+                let progress = step as f64 / steps as f64;
+                playbin.set_property("volume", fade.curve.level(start, target, progress));
+            }
+
+            // If the media should be stopped
+            if stop_after {
+                if let Some(playbin) = playbin_weak.upgrade() {
+                    // Try to stop the media
+                    if let Err(_) = playbin.set_state(gst::State::Null) {
+                        error!("Unable to stop media.");
+                    }
+
+                    // Restore the channel volume for the next media
+                    playbin.set_property("volume", restore);
+                }
+
+                // Show the idle background
+                settings.set_idle(true);
+            }
+        });
+    }
+
+    // A helper function to wait in the background until the media is close
+    // to the end and then fade the volume of a deck to silence
+    fn start_fade_out(channel: &InternalChannel, deck: &gst::Element, fade: AudioFade) {
+        // Note the current fade generation (a newer fade cancels the fade out)
+        let generation = channel.settings.fade_generation.load(Ordering::SeqCst);
+        let fade_generation = channel.settings.fade_generation.clone();
+
+        // Create a weak reference to the playbin
+        let playbin_weak = deck.downgrade();
+
+        // Spawn the fade out in the background
+        tokio::spawn(async move {
+            // Check the remaining time periodically
+            let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+            let start = loop {
+                // Wait for the next check
+                timer.tick().await;
+
+                // Stop if a newer fade has started or the media changed
+                if fade_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                // Try to get a strong reference to the playbin
+                let playbin = match playbin_weak.upgrade() {
+                    Some(playbin) => playbin,
+                    None => return,
+                };
+
+                // Check the remaining time in the media
+                if let (Some(position), Some(duration)) = (
+                    playbin.query_position::<gst::ClockTime>(),
+                    playbin.query_duration::<gst::ClockTime>(),
+                ) {
+                    // Once the fade should begin, note the current level
+                    if duration.saturating_sub(position).mseconds() <= fade.duration {
+                        break playbin.property::<f64>("volume");
+                    }
+                }
+            };
+
+            // Step through the fade over the remaining time
+            let steps = (fade.duration / FADE_INTERVAL).max(1);
+            for step in 1..=steps {
+                // Wait for the next step
+                timer.tick().await;
+
+                // Stop if a newer fade has started or the media changed
+                if fade_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                // Try to get a strong reference to the playbin
+                let playbin = match playbin_weak.upgrade() {
+                    Some(playbin) => playbin,
+                    None => return,
+                };
+
+                // Set the new volume level
+                let progress = step as f64 / steps as f64;
+                playbin.set_property("volume", fade.curve.level(start, 0.0, progress));
+            }
+        });
+    }
+
+    // A helper function to blend from the outgoing deck to the incoming deck
+    // in the background, and stop the outgoing media when the transition is
+    // complete. A newer transition or cue on the channel cancels the transition.
+    fn start_transition(
+        channel: &InternalChannel,
+        outgoing: usize,
+        incoming: usize,
+        transition: Transition,
+        generation: u64,
+    ) {
+        // Copy the mixer (checked when the media was cued) and the transition generation
+        let Some(mixer) = channel.mixer.clone() else {
+            return;
+        };
+        let transition_generation = channel.settings.transition_generation.clone();
+
+        // Create weak references to both decks
+        let outgoing_weak = channel.decks[outgoing].downgrade();
+        let incoming_weak = channel.decks[incoming].downgrade();
+
+        // Spawn the transition in the background
+        tokio::spawn(async move {
+            // Wait for the new media to be ready (or give up waiting)
+            let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+            for _ in 0..(PRELOAD_TIMEOUT / FADE_INTERVAL) {
+                // Wait for the next check
+                timer.tick().await;
+
+                // Stop if a newer transition has started
+                if transition_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                // Try to get a strong reference to the incoming deck
+                let deck = match incoming_weak.upgrade() {
+                    Some(deck) => deck,
+                    None => return,
+                };
+
+                // Continue once the media is loaded
+                if deck.current_state() != gst::State::Null
+                    && deck.pending_state() == gst::State::VoidPending
+                {
+                    break;
+                }
+            }
+
+            // Note the starting level of each deck
+            let outgoing_start = mixer.level(outgoing);
+            let incoming_start = mixer.level(incoming);
+
+            // Match the type of transition
+            match transition {
+                // Blend both decks at the same time
+                Transition::Crossfade { duration } => {
+                    if !MediaPlayback::ramp_levels(
+                        &mixer,
+                        &transition_generation,
+                        generation,
+                        &[
+                            (outgoing, outgoing_start, 0.0),
+                            (incoming, incoming_start, 1.0),
+                        ],
+                        duration,
+                    )
+                    .await
+                    {
+                        return;
+                    }
+                }
+
+                // Fade out the outgoing deck, then start and fade in the incoming deck
+                Transition::FadeThroughBlack { duration } => {
+                    if !MediaPlayback::ramp_levels(
+                        &mixer,
+                        &transition_generation,
+                        generation,
+                        &[(outgoing, outgoing_start, 0.0)],
+                        duration / 2,
+                    )
+                    .await
+                    {
+                        return;
+                    }
+
+                    // Start playing the incoming media
+                    if let Some(deck) = incoming_weak.upgrade()
+                        && deck.set_state(gst::State::Playing).is_err()
+                    {
+                        error!("Unable to start playing media.");
+                    }
+
+                    // Fade in the incoming deck
+                    if !MediaPlayback::ramp_levels(
+                        &mixer,
+                        &transition_generation,
+                        generation,
+                        &[(incoming, incoming_start, 1.0)],
+                        duration / 2,
+                    )
+                    .await
+                    {
+                        return;
+                    }
+                }
+            }
+
+            // Stop the outgoing media
+            if let Some(deck) = outgoing_weak.upgrade()
+                && deck.set_state(gst::State::Null).is_err()
+            {
+                error!("Unable to stop media.");
+            }
+        });
+    }
+
+    // A helper function to change the mixer level of one or more decks over
+    // the duration (in milliseconds). Each ramp is a deck, a starting level,
+    // and a target level. Returns false if a newer transition has started.
+    async fn ramp_levels(
+        mixer: &ChannelMixer,
+        transition_generation: &AtomicU64,
+        generation: u64,
+        ramps: &[(usize, f64, f64)],
+        duration: u64,
+    ) -> bool {
+        // Calculate the number of steps in the ramp
+        let steps = (duration / FADE_INTERVAL).max(1);
+        let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+        timer.tick().await; // the first tick completes immediately
+
+        // Step through the ramp
+        for step in 1..=steps {
+            // Wait for the next step
+            timer.tick().await;
+
+            // Stop if a newer transition has started
+            if transition_generation.load(Ordering::SeqCst) != generation {
+                return false;
+            }
+
+            // Set the new level of each deck
+            let progress = step as f64 / steps as f64;
+            for (deck, start, target) in ramps.iter() {
+                mixer.set_level(*deck, start + (target - start) * progress);
+            }
+        }
+
+        // Indicate the ramp completed
+        true
+    }
+
+    // A helper function to query the active deck for the status of a channel
+    fn compose_status(channel: &InternalChannel) -> ChannelStatus {
+        // Convert the current state of the active deck
+        let playbin = channel.active();
+        let state = match playbin.current_state() {
+            gst::State::Playing => Some(PlaybackState::Playing),
+            gst::State::Paused => Some(PlaybackState::Paused),
+            _ => None, // no media is loaded
+        };
+
+        // Copy the current loop media, if available
+        let loop_media = match channel.settings.loop_mutex.lock() {
+            Ok(media) => media.clone(),
+            _ => None,
+        };
+
+        // Return the completed status
+        ChannelStatus {
+            media_channel: channel.media_channel.clone(),
+            current_uri: playbin.property::<Option<String>>("current-uri"),
+            loop_media,
+            state,
+            position: playbin
+                .query_position::<gst::ClockTime>()
+                .map(|position| position.mseconds()),
+            duration: playbin
+                .query_duration::<gst::ClockTime>()
+                .map(|duration| duration.mseconds()),
+        }
+    }
+
+    // A helper function to queue the loop media just before the current media
+    // ends, so that the loop media follows without a gap. Only the active deck
+    // of the channel loops media.
+    fn create_loop_callback(deck: usize, playbin: &gst::Element, settings: ChannelSettings) {
+        // Connect the signal handler for the end of the current media
+        playbin.connect("about-to-finish", false, move |values| {
+            // Try to get the playbin from the signal
+            let playbin = values.first()?.get::<gst::Element>().ok()?;
+
+            // Ignore the signal unless this deck is currently playing
+            if settings.active_deck.load(Ordering::SeqCst) != deck {
+                return None;
+            }
+
+            // Wait for access to the current loop media
+            if let Ok(possible_media) = settings.loop_mutex.lock()
+                && let Some(media) = possible_media.clone()
+            {
+                // Note the media which is ending (the end is shared once the
+                // loop media starts)
+                if let Ok(mut ending) = settings.ending_mutex.lock() {
+                    *ending = playbin.property::<Option<String>>("current-uri");
+                }
+
+                // Queue the loop uri to play next
+                playbin.set_property("uri", &media);
+                settings.loop_flag.store(true, Ordering::SeqCst);
+            }
+
+            // The signal has no return value
+            None
+        });
+    }
+
+    // A helper function to create a signal watch to share playback events
+    // with any listeners. Only the active deck of the channel shares events,
+    // except for errors and warnings.
+    fn create_bus_callback(
+        channel_number: u32,
+        deck: usize,
+        playbin: &gst::Element,
+        settings: ChannelSettings,
+        event_send: EventSend,
+    ) -> Result<gst::bus::BusWatchGuard> {
+        // Try to access the playbin bus
+        let bus = match playbin.bus() {
+            Some(bus) => bus,
+            None => return Err(anyhow!("Unable to set loop media: Invalid bus.")),
+        };
+
+        // Create a week reference to the playbin
+        let channel_weak = playbin.downgrade();
+
+        // Connect the signal handler for the playbin messages
+        if let Ok(watch_guard) = bus.add_watch(move |_, msg| {
+            // Try to get a strong reference to the channel
+            let channel = match channel_weak.upgrade() {
+                Some(channel) => channel,
+                None => return glib::ControlFlow::Continue, // Fail silently, but try again
+            };
+
+            // Check whether this deck is currently playing on the channel
+            let is_active = settings.active_deck.load(Ordering::SeqCst) == deck;
+
+            // Match the message type
+            match msg.view() {
+                // If the end of stream message is received
+                gst::MessageView::Eos(..) if is_active => {
+                    // Note the end of the media and show the idle background
+                    settings.end_flag.store(true, Ordering::SeqCst);
+                    settings.set_idle(true);
+
+                    // Notify that the media completed
+                    event_send.send(PlaybackEvent::MediaEnded {
+                        channel: channel_number,
+                        uri: channel.property::<Option<String>>("current-uri"),
+                    });
+                }
+
+                // If the end of a segment is received (only for looping groups)
+                gst::MessageView::SegmentDone(..) if is_active => {
+                    // Note the end of the media (the group restarts the media)
+                    settings.end_flag.store(true, Ordering::SeqCst);
+
+                    // Notify that the media completed
+                    event_send.send(PlaybackEvent::MediaEnded {
+                        channel: channel_number,
+                        uri: channel.property::<Option<String>>("current-uri"),
+                    });
+                }
+
+                // If new media started on the channel
+                gst::MessageView::StreamStart(..) if is_active => {
+                    // Hide the idle background
+                    settings.set_idle(false);
+
+                    // If the loop media just started
+                    if settings.loop_flag.swap(false, Ordering::SeqCst) {
+                        // Notify that the previous media completed
+                        if let Ok(mut ending) = settings.ending_mutex.lock() {
+                            event_send.send(PlaybackEvent::MediaEnded {
+                                channel: channel_number,
+                                uri: ending.take(),
+                            });
+                        }
+
+                        // Cancel any fade and restore the channel volume
+                        settings.cancel_fades();
+                        if let Ok(level) = settings.level_mutex.lock() {
+                            channel.set_property("volume", *level);
+                        }
+
+                        // Notify that the loop media restarted
+                        if let Ok(possible_media) = settings.loop_mutex.lock()
+                            && let Some(media) = possible_media.clone()
+                        {
+                            event_send.send(PlaybackEvent::LoopRestarted {
+                                channel: channel_number,
+                                uri: media,
+                            });
+                        }
+
+                    // Otherwise, notify that new media started
+                    } else {
+                        event_send.send(PlaybackEvent::MediaStarted {
+                            channel: channel_number,
+                            uri: channel.property::<Option<String>>("current-uri"),
+                        });
+                    }
+                }
+
+                // If the state of the playbin changed (ignore the internal elements)
+                gst::MessageView::StateChanged(state_changed) if is_active => {
+                    // Only notify for the final state of the playbin itself
+                    if msg.src() == Some(channel.upcast_ref::<gst::Object>())
+                        && state_changed.pending() == gst::State::VoidPending
+                    {
+                        // Convert the new state
+                        let state = match state_changed.current() {
+                            gst::State::Playing => Some(PlaybackState::Playing),
+                            gst::State::Paused => Some(PlaybackState::Paused),
+                            _ => None, // no media is loaded
+                        };
+
+                        // Notify of the new state
+                        event_send.send(PlaybackEvent::StateChanged {
+                            channel: channel_number,
+                            state,
+                        });
+                    }
+                }
+
+                // If an asynchronous state change completed
+                gst::MessageView::AsyncDone(..) if is_active => {
+                    // If this completes a seek, notify of the new position
+                    if settings.seek_flag.swap(false, Ordering::SeqCst) {
+                        event_send.send(PlaybackEvent::SeekCompleted {
+                            channel: channel_number,
+                            position: channel
+                                .query_position::<gst::ClockTime>()
+                                .map(|position| position.mseconds()),
+                        });
+                    }
+                }
+
+                // If the media is buffering
+                gst::MessageView::Buffering(buffering) if is_active => {
+                    event_send.send(PlaybackEvent::Buffering {
+                        channel: channel_number,
+                        percent: buffering.percent(),
+                    });
+                }
+
+                // If there was an error on the channel
+                gst::MessageView::Error(err) => {
+                    // Share the error
+                    error!("Error on channel {}: {}", channel_number, err.error());
+                    event_send.send(PlaybackEvent::Error {
+                        channel: channel_number,
+                        message: format!("{}", err.error()),
+                    });
+                }
+
+                // If there was a warning on the channel
+                gst::MessageView::Warning(warning) => {
+                    // Share the warning
+                    warn!("Warning on channel {}: {}", channel_number, warning.error());
+                    event_send.send(PlaybackEvent::Warning {
+                        channel: channel_number,
+                        message: format!("{}", warning.error()),
+                    });
+                }
+
+                // Ignore all other messages
+                _ => (),
+            }
+
             // Continue with other signal handlers
             glib::ControlFlow::Continue
 
@@ -365,16 +2750,105 @@ impl Drop for MediaPlayback {
     /// This method sets any active playbins to NULL and removes the watch signals
     ///
     fn drop(&mut self) {
-        // For every playbin in the active channels
+        // For every channel in the active channels
         for (_, channel) in self.channels.drain() {
-            // Set the playbin state to null
-            channel
-                .playbin
-                .set_state(gst::State::Null)
-                .unwrap_or(gst::StateChangeSuccess::Success);
+            // Set the state of every deck to null
+            for deck in channel.decks.iter() {
+                deck.set_state(gst::State::Null)
+                    .unwrap_or(gst::StateChangeSuccess::Success);
+            }
+
+            // Drop the watch guards (the mixer, if any, stops when dropped)
+            drop(channel.watch_guards)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            // Drop the watch guard
-            drop(channel.watch_guard)
+    // A helper function to create a media cue for the group tests
+    fn media_cue(channel: u32) -> MediaCue {
+        MediaCue {
+            channel,
+            uri: "file:///tmp/video.mp4".to_string(),
+            loop_media: None,
+            fade_in: None,
+            fade_out: None,
+            transition: None,
+            start_at: None,
         }
     }
+
+    // Test the validation of groups of channels
+    #[test]
+    fn cue_group_validation() {
+        // Create headless playback with one channel
+        let (interface_send, _interface_receive) = InterfaceSend::new();
+        let mut media_playback = MediaPlayback::new(
+            EventSend::new(),
+            interface_send,
+            VideoOutput::Headless,
+            None,
+        )
+        .expect("Unable to create media playback.");
+        media_playback
+            .define_channel(MediaChannel {
+                channel: 1,
+                video_frame: None,
+                audio_device: None,
+                loop_media: None,
+                idle_background: None,
+                transitions: false,
+                layer: None,
+                opacity: None,
+                hidden: false,
+            })
+            .expect("Unable to define channel.");
+
+        // A group needs media
+        let group = |media_cues| CueGroup {
+            media_cues,
+            loop_group: false,
+            start_at: None,
+        };
+        let error = media_playback.cue_group(group(vec![])).unwrap_err();
+        assert!(error.to_string().contains("No media specified"));
+
+        // Every channel must be defined
+        let error = media_playback
+            .cue_group(group(vec![media_cue(1), media_cue(2)]))
+            .unwrap_err();
+        assert!(error.to_string().contains("Channel 2 not defined"));
+
+        // Every channel must be used only once
+        let error = media_playback
+            .cue_group(group(vec![media_cue(1), media_cue(1)]))
+            .unwrap_err();
+        assert!(error.to_string().contains("used more than once"));
+
+        // A start time must be in the future (including for a single cue)
+        let error = media_playback
+            .cue_group(CueGroup {
+                start_at: Some(1000),
+                ..group(vec![media_cue(1)])
+            })
+            .unwrap_err();
+        assert!(error.to_string().contains("already passed"));
+        let error = media_playback
+            .cue_media(MediaCue {
+                start_at: Some(1000),
+                ..media_cue(1)
+            })
+            .unwrap_err();
+        assert!(error.to_string().contains("already passed"));
+
+        // Groups restored from a backup are checked the same way
+        let error = media_playback
+            .resume_group(group(vec![media_cue(3)]), 1000, PlaybackState::Paused)
+            .unwrap_err();
+        assert!(error.to_string().contains("Channel 3 not defined"));
+        assert!(media_playback.groups.is_empty());
+    }
 }
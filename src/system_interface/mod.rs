@@ -20,33 +20,48 @@
 //! to the application window.
 
 // Define submodules
+mod backup_backend;
 mod backup_handler;
+mod backup_writer;
+mod channel_mixer;
+mod cue_list;
 mod media_playback;
+mod network_clock;
+mod scheduler;
+mod standby;
+mod window_compositor;
 
 // Import crate definitions
 use crate::definitions::*;
 
 // Import submodute definitions
-use backup_handler::BackupHandler;
+use backup_handler::{BackupHandler, InstanceUpdate};
+use cue_list::CueListHandler;
 use media_playback::MediaPlayback;
+use scheduler::Scheduler;
+use standby::StandbyMonitor;
 
 // Import standard library features
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-// Import Tokio features
-use tokio::sync::mpsc;
-use tokio::time::sleep;
+// Import Gstreamer Library
+use gstreamer as gst;
 
-// Import FNV HashSet
-use fnv::FnvHashSet;
+// Import Tokio features
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Interval, MissedTickBehavior, interval, sleep};
 
 // Import tracing features
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 // Import anyhow features
 use anyhow::Result;
 
+// Define module constants
+const CHECKPOINT_INTERVAL: u64 = 1000; // the time between checkpoints of the media positions, in milliseconds
+const RESTORE_TIMEOUT: u64 = 10000; // the longest time to wait for media to load when restoring a backup, in milliseconds
+
 /// A structure to contain the system interface and handle all updates to the
 /// to the interface.
 ///
@@ -55,7 +70,15 @@ pub struct SystemInterface {
     web_receive: mpsc::Receiver<WebRequest>, // the receiving line for web requests
     media_playback: MediaPlayback, // the structure for controlling media playback
     backup_handler: BackupHandler, // the structure for managing the live system backup
-    windows: FnvHashSet<u32>,      // a set of already-defined windows (to avoid duplication)
+    cue_list: CueListHandler,      // the structure for stepping through the cue list
+    scheduler: Scheduler,          // the structure for running requests at a future time
+    windows: WindowList,           // a list of already-defined windows (to avoid duplication)
+    show_file: Option<ShowFile>,   // the show file to load at startup, if specified
+    show_directory: Option<PathBuf>, // the directory for saving show files, if specified
+    presets: PresetMap,            // the named presets from the show file
+    api_keys: ApiKeys,             // the API keys from the show file
+    checkpoint: Interval,          // the timer for checkpoints of the media positions
+    standby: StandbyMonitor,       // the monitor of the primary instance, if on standby
 }
 
 // Implement key SystemInterface functionality
@@ -64,46 +87,98 @@ impl SystemInterface {
     ///
     pub async fn new(
         interface_send: InterfaceSend,
-        user_address: Arc<Mutex<String>>,
-        user_server_location: Arc<Mutex<Option<String>>>,
+        event_send: EventSend,
+        settings: &UserSettings,
+        video_output: VideoOutput,
     ) -> Result<(Self, WebSend)> {
         // Create the web send for the web interface
         let (web_send, web_receive) = WebSend::new();
 
         // Try to initialize the media playback module
-        let media_playback = MediaPlayback::new()?;
+        let media_playback = MediaPlayback::new(
+            event_send.clone(),
+            interface_send.clone(),
+            video_output,
+            settings.clock.clone(),
+        )?;
+
+        // Create the cue list handler
+        let cue_list = CueListHandler::new(event_send);
+
+        // Try to load the user defined show file
+        let mut show_file = None;
+        let mut show_directory = None;
+        if let Some(path) = settings.show_file.as_ref() {
+            // Load and validate the show
+            show_file = Some(ShowFile::load(path)?);
+
+            // Default to saving shows in the same directory
+            show_directory = Path::new(path).parent().map(Path::to_path_buf);
+        }
 
-        // Try to extract the user defined address
-        let mut address = DEFAULT_ADDRESS.to_string();
-        if let Ok(lock) = user_address.try_lock() {
-            // Copy the address
-            address = lock.clone();
+        // Use the user defined show directory, if specified
+        if let Some(directory) = settings.show_directory.as_ref() {
+            show_directory = Some(PathBuf::from(directory));
         }
 
-        // Try to extract the user defined server_location
-        let mut server_location = None;
-        if let Ok(lock) = user_server_location.try_lock() {
-            // Copy the address
-            server_location = lock.clone();
+        // Copy the API keys from the show file, if any
+        let api_keys = show_file
+            .as_ref()
+            .map(|show_file| show_file.api_keys.clone())
+            .unwrap_or_default();
+
+        // Make sure there is a backup to follow the primary instance, if on standby
+        let primary = settings.standby.clone();
+        let server_location = settings.server_location.clone();
+        if primary.is_some() && server_location.is_none() {
+            return Err(anyhow!("Standby mode requires a backup location."));
         }
 
         // Initialize the backup handler
-        let backup_handler =
-            BackupHandler::new(address, server_location, interface_send.clone()).await;
+        let backup_handler = BackupHandler::new(
+            settings.address.clone(),
+            server_location,
+            interface_send.clone(),
+        )
+        .await;
+
+        // Create the timer for checkpoints (skipping any missed checkpoints)
+        let mut checkpoint = interval(Duration::from_millis(CHECKPOINT_INTERVAL));
+        checkpoint.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         // Create the new system interface instance
-        let sys_interface = SystemInterface {
+        let mut sys_interface = SystemInterface {
             interface_send,
             web_receive,
             media_playback,
             backup_handler,
-            windows: FnvHashSet::default(),
+            cue_list,
+            scheduler: Scheduler::new(),
+            windows: WindowList::new(),
+            show_file,
+            show_directory,
+            presets: PresetMap::default(),
+            api_keys,
+            checkpoint,
+            standby: StandbyMonitor::new(),
         };
 
+        // If on standby, start following the primary instance
+        if let Some(primary) = primary {
+            sys_interface.start_standby(primary);
+        }
+
         // Regardless, return the new SystemInterface and general send line
         Ok((sys_interface, web_send))
     }
 
+    /// A method to return the API keys from the show file (to be added to
+    /// the keys for the web interface)
+    ///
+    pub fn api_keys(&self) -> ApiKeys {
+        self.api_keys.clone()
+    }
+
     /// A method to run one iteration of the system interface to update the underlying system of any event changes.
     ///
     async fn run_once(&mut self) -> bool {
@@ -111,195 +186,958 @@ impl SystemInterface {
         tokio::select! {
             // Updates from the Web Interface
             Some(request) = self.web_receive.recv() => {
-                // Match the request subtype
-                match request.request {
-                    // If realigning the channel
-                    Request::AlignChannel { channel_realignment } => {
-                        // Pass the new video location to the gtk interface
-                        self.interface_send.send(InterfaceUpdate::Align { channel_realignment: channel_realignment.clone()});
+                // On standby, refuse any request which would change this instance
+                if self.standby.is_standby() && !SystemInterface::is_allowed_on_standby(&request.request) {
+                    error!("Unable to process request: This instance is on standby.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure("Unable to process request: This instance is on standby."))
+                        .unwrap_or(());
 
-                        // Backup the change to the channel
-                        self.backup_handler.backup_channel_align(channel_realignment).await;
+                // If recalling a preset, recall the preset and reply to the web interface
+                } else if let Request::RecallPreset { name } = request.request {
+                    let reply = self.recall_preset(name).await;
+                    request.reply_to.send(reply).unwrap_or(());
 
-                        // Reply success to the web interface
-                        request.reply_to.send(WebReply::success()).unwrap_or(());
-                    }
+                // Otherwise, process the request (and end the loop if closing the program)
+                } else if !self.process_request(request).await {
+                    return false;
+                }
+            }
+
+            // Cues from the cue list
+            Some(cue) = self.cue_list.next_cue() => {
+                // Fire the cue
+                self.fire_cue(cue).await;
+            }
+
+            // Requests from the scheduler
+            Some(request) = self.scheduler.next_request() => {
+                // Backup the remaining requests
+                self.backup_handler.backup_schedule(self.scheduler.list()).await;
+
+                // If closing the program, end the loop
+                if let Request::Close = request {
+                    return false;
+                }
+
+                // On standby, skip the request (the primary runs its own requests)
+                if self.standby.is_standby() {
+                    warn!("Skipping scheduled request while on standby.");
+
+                // Otherwise, process the request or preset (any errors are traced)
+                } else {
+                    match request {
+                        Request::RecallPreset { name } => self.recall_preset(name).await,
+                        other => self.process_internal(other).await,
+                    };
+                }
+            }
+
+            // Outputs which are ready to start (once the video is embedded in the window)
+            Some(output) = self.media_playback.next_ready() => {
+                // Start the output (any errors are traced)
+                if let Err(error) = self.media_playback.start_ready(output) {
+                    error!("{}", error);
+                }
+            }
+
+            // Periodic checkpoints of the media
+            _ = self.checkpoint.tick() => {
+                // Record the real position of the media on every channel (unless on standby)
+                if !self.standby.is_standby() {
+                    self.backup_handler.backup_checkpoint(self.media_playback.all_status()).await;
+                }
+            }
+
+            // Updates from the primary instance (only on standby)
+            Some(update) = self.standby.next_update() => {
+                // Follow the primary (and take over if the primary has stopped)
+                self.follow_primary(update).await;
+            }
+
+            // Another instance took over from this instance (only when running normally)
+            Some(owner) = self.standby.next_owner() => {
+                // Stop the media and follow the other instance
+                self.step_down(owner).await;
+            }
+        }
+
+        // In most cases, indicate to continue normally
+        true
+    }
+
+    /// A method to process a single request and reply to the request. This
+    /// method is shared by every source of requests, and returns false if
+    /// the program should close.
+    ///
+    async fn process_request(&mut self, request: WebRequest) -> bool {
+        // Match the request subtype
+        match request.request {
+            // If realigning the channel
+            Request::AlignChannel {
+                channel_realignment,
+            } => {
+                // Update the channel definition
+                self.media_playback.align_channel(&channel_realignment);
+
+                // Pass the new video location to the gtk interface (unless mixed by the window compositor)
+                if !self
+                    .media_playback
+                    .is_composited(channel_realignment.channel)
+                {
+                    self.interface_send.send(InterfaceUpdate::Align {
+                        channel_realignment: channel_realignment.clone(),
+                    });
+                }
 
-                    // If stopping all the media
-                    Request::AllStop => {
-                        // Try to cue the new media
-                        if let Err(error) = self.media_playback.all_stop() {
-                            // If there was an error, trace the error and reply with the error
+                // Backup the change to the channel
+                self.backup_handler
+                    .backup_channel_align(channel_realignment)
+                    .await;
+
+                // Reply success to the web interface
+                request.reply_to.send(WebReply::success()).unwrap_or(());
+            }
+
+            // If stopping all the media
+            Request::AllStop { fade_out } => {
+                // Try to cue the new media
+                if let Err(error) = self.media_playback.all_stop(fade_out) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the change and indicate success
+                } else {
+                    // Backup the change
+                    self.backup_handler.backup_all_stop().await;
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If defining a new window
+            Request::DefineWindow { window } => {
+                // If the window isn't already defined, add it
+                if !self.is_window_defined(window.window_number) {
+                    // Create the window compositor, if specified
+                    let video_sink = match self.media_playback.update_compositor(&window) {
+                        Ok(video_sink) => video_sink,
+                        Err(error) => {
+                            // Trace the error and reply with the error
                             error!("{}", error);
-                            request.reply_to.send(WebReply::failure(format!("{}", error))).unwrap_or(());
+                            request
+                                .reply_to
+                                .send(WebReply::failure(format!("{}", error)))
+                                .unwrap_or(());
+                            return true;
+                        }
+                    };
+
+                    // Save the window definition
+                    self.windows.push(window.clone());
+
+                    // Send the window definition to the gtk interface
+                    self.interface_send.send(InterfaceUpdate::Window {
+                        window: window.clone(),
+                    });
+
+                    // Send the mixed video to the gtk interface, if created
+                    if let Some((video_sink, ready)) = video_sink {
+                        self.show_compositor(window.window_number, video_sink, ready);
+                    }
+
+                    // Backup the window definition
+                    self.backup_handler.backup_window(window).await;
+
+                    // Reply success to the web interface
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+
+                // Trace the error and reply with the error
+                } else {
+                    error!("Window is already defined.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("Window was already defined.")))
+                        .unwrap_or(());
+                }
+            }
+
+            // If removing a window
+            Request::RemoveWindow { window_number } => {
+                // Make sure the window is defined
+                if !self.is_window_defined(window_number) {
+                    error!("Unable to remove window: Window not defined.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure(
+                            "Unable to remove window: Window not defined.",
+                        ))
+                        .unwrap_or(());
+
+                // Make sure no channel is using the window
+                } else if self.media_playback.is_window_used(window_number) {
+                    error!("Unable to remove window: Window is used by a channel.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure(
+                            "Unable to remove window: Window is used by a channel.",
+                        ))
+                        .unwrap_or(());
+
+                // Otherwise, remove the window
+                } else {
+                    // Remove the window definition and any window compositor
+                    self.windows
+                        .retain(|window| window.window_number != window_number);
+                    self.media_playback.remove_compositor(window_number);
+
+                    // Remove the window from the gtk interface
+                    self.interface_send
+                        .send(InterfaceUpdate::RemoveWindow { window_number });
 
-                        // Otherwise, indicate success
-                        } else {
-                            request.reply_to.send(WebReply::success()).unwrap_or(());
+                    // Backup the change
+                    self.backup_handler
+                        .backup_window_removal(window_number)
+                        .await;
+
+                    // Reply success to the web interface
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If redefining a window
+            Request::RedefineWindow { window } => {
+                // Try to find the existing window definition
+                if let Some(existing) = self
+                    .windows
+                    .iter_mut()
+                    .find(|existing| existing.window_number == window.window_number)
+                {
+                    // Create, update, or remove the window compositor
+                    let video_sink = match self.media_playback.update_compositor(&window) {
+                        Ok(video_sink) => video_sink,
+                        Err(error) => {
+                            // Trace the error and reply with the error
+                            error!("{}", error);
+                            request
+                                .reply_to
+                                .send(WebReply::failure(format!("{}", error)))
+                                .unwrap_or(());
+                            return true;
                         }
+                    };
+
+                    // Replace the window definition
+                    *existing = window.clone();
+
+                    // Send the window definition to the gtk interface (which updates the existing window)
+                    self.interface_send.send(InterfaceUpdate::Window {
+                        window: window.clone(),
+                    });
+
+                    // Send the mixed video to the gtk interface, if created
+                    if let Some((video_sink, ready)) = video_sink {
+                        self.show_compositor(window.window_number, video_sink, ready);
                     }
 
-                    // If defining a new window
-                    Request::DefineWindow { window } => {
-                        // If the window isn't already defined, add it
-                        if self.windows.insert(window.window_number) {
-                            // Send the window definition to the gtk interface
-                            self.interface_send.send(InterfaceUpdate::Window { window: window.clone() });
+                    // Backup the change
+                    self.backup_handler.backup_window_redefine(window).await;
+
+                    // Reply success to the web interface
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
 
-                            // Backup the window definition
-                            self.backup_handler.backup_window(window).await;
+                // Trace the error and reply with the error
+                } else {
+                    error!("Unable to redefine window: Window not defined.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure(
+                            "Unable to redefine window: Window not defined.",
+                        ))
+                        .unwrap_or(());
+                }
+            }
 
-                            // Reply success to the web interface
-                            request.reply_to.send(WebReply::success()).unwrap_or(());
+            // If removing a channel
+            Request::RemoveChannel { channel } => {
+                // Try to remove the channel
+                if let Err(error) = self.media_playback.remove_channel(channel) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
 
-                        // Trace the error and reply with the error
-                        } else {
-                            error!("Window is already defined.");
-                            request.reply_to.send(WebReply::failure(format!("Window was already defined."))).unwrap_or(());
+                // Otherwise, pass the change along and indicate success
+                } else {
+                    // Remove the video from the gtk interface
+                    self.interface_send
+                        .send(InterfaceUpdate::RemoveVideo { channel });
+
+                    // Backup the change
+                    self.backup_handler.backup_channel_removal(channel).await;
+
+                    // Reply success to the web interface
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If redefining a channel
+            Request::RedefineChannel { media_channel } => {
+                // Try to replace the channel definition (the channel is unchanged on failure)
+                match self.media_playback.redefine_channel(media_channel.clone()) {
+                    // If successful
+                    Ok(possible_stream) => {
+                        // Remove the previous video from the gtk interface
+                        self.interface_send.send(InterfaceUpdate::RemoveVideo {
+                            channel: media_channel.channel,
+                        });
+
+                        // If a stream was created, pass it to the gtk interface
+                        if let Some(video_stream) = possible_stream {
+                            self.show_video(video_stream);
                         }
+
+                        // Backup the change
+                        self.backup_handler
+                            .backup_channel_redefine(media_channel)
+                            .await;
+
+                        // Reply success to the web interface
+                        request.reply_to.send(WebReply::success()).unwrap_or(());
                     }
 
-                    // If defining a new channel
-                    Request::DefineChannel { media_channel } => {
-                        // Add the channel definition
-                        match self.media_playback.define_channel(media_channel.clone()) {
-                            // If successful
-                            Ok(possible_stream) => {
-                                // If a stream was created
-                                if let Some(video_stream) = possible_stream {
-                                    // Pass the new video stream to the gtk interface
-                                    self.interface_send.send(InterfaceUpdate::Video { video_stream });
-                                }
-
-                                // Backup the window definition
-                                self.backup_handler.backup_channel(media_channel).await;
-
-                                // Reply success to the web interface
-                                request.reply_to.send(WebReply::success()).unwrap_or(());
-                            }
-
-                            // If there was an error, trace the error and reply with the error
-                            Err(error) => {
-                                error!("{}", error);
-                                request.reply_to.send(WebReply::failure(format!("{}", error))).unwrap_or(());
-                            }
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
 
+            // If defining a new channel
+            Request::DefineChannel { media_channel } => {
+                // Add the channel definition
+                match self.media_playback.define_channel(media_channel.clone()) {
+                    // If successful
+                    Ok(possible_stream) => {
+                        // If a stream was created
+                        if let Some(video_stream) = possible_stream {
+                            // Pass the new video stream to the gtk interface
+                            self.show_video(video_stream);
                         }
+
+                        // Backup the window definition
+                        self.backup_handler.backup_channel(media_channel).await;
+
+                        // Reply success to the web interface
+                        request.reply_to.send(WebReply::success()).unwrap_or(());
                     }
 
-                    // If cuing a new media selection
-                    Request::CueMedia { media_cue } => {
-                        // Try to cue the new media
-                        if let Err(error) = self.media_playback.cue_media(media_cue.clone()) {
-                            // If there was an error, trace the error and reply with the error
-                            error!("{}", error);
-                            request.reply_to.send(WebReply::failure(format!("{}", error))).unwrap_or(());
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
+
+            // If cuing a new media selection
+            Request::CueMedia { media_cue } => {
+                // Try to cue the new media
+                if let Err(error) = self.media_playback.cue_media(media_cue.clone()) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the media and indicate success
+                } else {
+                    // Backup the media
+                    self.backup_handler.backup_media(media_cue).await;
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If cuing media on several channels together
+            Request::CueGroup { cue_group } => {
+                // Try to cue the group
+                if let Err(error) = self.media_playback.cue_group(cue_group.clone()) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the media and indicate success
+                } else {
+                    // Backup the media on every channel as one group
+                    self.backup_handler.backup_group(cue_group).await;
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // Preload media on the channel
+            Request::PreloadMedia { media_cue } => {
+                // Try to preload the media
+                if let Err(error) = self.media_playback.preload_media(media_cue) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, indicate success
+                } else {
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
 
-                        // Otherwise, backup the media and indicate success
-                        } else {
-                            // Backup the media
-                            self.backup_handler.backup_media(media_cue).await;
+            // If changing the state of a channel
+            Request::ChangeState { channel_state } => {
+                // Try to cue the new media
+                if let Err(error) = self.media_playback.change_state(channel_state.clone()) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
 
-                            // Indicate success
-                            request.reply_to.send(WebReply::success()).unwrap_or(());
+                // Otherwise, backup the change and indicate success
+                } else {
+                    // Backup the change (to every channel in the same group)
+                    for channel in self.media_playback.group_channels(channel_state.channel) {
+                        self.backup_handler
+                            .backup_media_state(ChannelState {
+                                channel,
+                                state: channel_state.state.clone(),
+                            })
+                            .await;
+                    }
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If changing the volume of a channel
+            Request::ChangeVolume { channel_volume } => {
+                // Try to change the volume
+                if let Err(error) = self.media_playback.change_volume(channel_volume.clone()) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the change and indicate success
+                } else {
+                    // Backup the change
+                    self.backup_handler.backup_volume(channel_volume).await;
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If resizing a channel
+            Request::ResizeChannel { channel_allocation } => {
+                // Update the channel definition
+                self.media_playback.resize_channel(&channel_allocation);
+
+                // Pass the new video location to the gtk interface (unless mixed by the window compositor)
+                if !self
+                    .media_playback
+                    .is_composited(channel_allocation.channel)
+                {
+                    self.interface_send.send(InterfaceUpdate::Resize {
+                        channel_allocation: channel_allocation.clone(),
+                    });
+                }
+
+                // Backup the change to the channel
+                self.backup_handler
+                    .backup_channel_resize(channel_allocation)
+                    .await;
+
+                // Reply success to the web interface
+                request.reply_to.send(WebReply::success()).unwrap_or(());
+            }
+
+            // If changing the stacking order of a channel
+            Request::ChangeLayer { channel_layer } => {
+                // Try to update the channel
+                match self.media_playback.change_layer(&channel_layer) {
+                    // If successful, pass the change along and indicate success
+                    Ok(channel_list) => {
+                        // Pass the change to the gtk interface (unless mixed by the window compositor)
+                        if !self.media_playback.is_composited(channel_layer.channel) {
+                            self.interface_send
+                                .send(InterfaceUpdate::Layer { channel_layer });
                         }
+
+                        // Backup the new layer of every channel in the window
+                        self.backup_handler
+                            .backup_channel_update(channel_list)
+                            .await;
+
+                        // Reply success to the web interface
+                        request.reply_to.send(WebReply::success()).unwrap_or(());
                     }
 
-                    // If changing the state of a channel
-                    Request::ChangeState { channel_state } => {
-                        // Try to cue the new media
-                        if let Err(error) = self.media_playback.change_state(channel_state.clone()) {
-                            // If there was an error, trace the error and reply with the error
-                            error!("{}", error);
-                            request.reply_to.send(WebReply::failure(format!("{}", error))).unwrap_or(());
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
+
+            // If changing the opacity of a channel
+            Request::ChangeOpacity { channel_opacity } => {
+                // Try to update the channel
+                match self.media_playback.change_opacity(&channel_opacity) {
+                    // If successful, pass the change along and indicate success
+                    Ok(media_channel) => {
+                        // Pass the change to the gtk interface (unless mixed by the window compositor)
+                        if !self.media_playback.is_composited(channel_opacity.channel) {
+                            self.interface_send
+                                .send(InterfaceUpdate::Opacity { channel_opacity });
+                        }
+
+                        // Backup the change
+                        self.backup_handler
+                            .backup_channel_update(vec![media_channel])
+                            .await;
+
+                        // Reply success to the web interface
+                        request.reply_to.send(WebReply::success()).unwrap_or(());
+                    }
 
-                        // Otherwise, backup the change and indicate success
-                        } else {
-                            // Backup the change
-                            self.backup_handler.backup_media_state(channel_state).await;
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
 
-                            // Indicate success
-                            request.reply_to.send(WebReply::success()).unwrap_or(());
+            // If changing the visibility of a channel
+            Request::ChangeVisibility { channel_visibility } => {
+                // Try to update the channel
+                match self.media_playback.change_visibility(&channel_visibility) {
+                    // If successful, pass the change along and indicate success
+                    Ok(media_channel) => {
+                        // Pass the change to the gtk interface (unless mixed by the window compositor)
+                        if !self
+                            .media_playback
+                            .is_composited(channel_visibility.channel)
+                        {
+                            self.interface_send
+                                .send(InterfaceUpdate::Visibility { channel_visibility });
                         }
+
+                        // Backup the change
+                        self.backup_handler
+                            .backup_channel_update(vec![media_channel])
+                            .await;
+
+                        // Reply success to the web interface
+                        request.reply_to.send(WebReply::success()).unwrap_or(());
                     }
 
-                    // If resizing a channel
-                    Request::ResizeChannel { channel_allocation } => {
-                        // Pass the new video location to the gtk interface
-                        self.interface_send.send(InterfaceUpdate::Resize { channel_allocation: channel_allocation.clone() });
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
 
-                        // Backup the change to the channel
-                        self.backup_handler.backup_channel_resize(channel_allocation).await;
+            // If changing the background of a window
+            Request::ChangeBackground {
+                background_change:
+                    BackgroundChange::Window {
+                        window_number,
+                        background,
+                    },
+            } => {
+                // Try to find the existing window definition
+                if let Some(existing) = self
+                    .windows
+                    .iter_mut()
+                    .find(|existing| existing.window_number == window_number)
+                {
+                    // Try to update any window compositor
+                    let mut window = existing.clone();
+                    window.background = background.clone();
+                    if let Err(error) = self.media_playback.update_compositor(&window) {
+                        // If there was an error, trace the error and reply with the error
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+
+                    // Otherwise, save the change, pass it along, backup the change, and indicate success
+                    } else {
+                        // Update the window definition
+                        existing.background = background.clone();
+
+                        // Pass the new background to the gtk interface
+                        self.interface_send.send(InterfaceUpdate::Background {
+                            window_number,
+                            background,
+                        });
+
+                        // Backup the change
+                        self.backup_handler.backup_window_redefine(window).await;
 
                         // Reply success to the web interface
                         request.reply_to.send(WebReply::success()).unwrap_or(());
                     }
 
-                    // If seeking media on a channel
-                    Request::Seek { channel_seek } => {
-                        // Try to cue the new media
-                        if let Err(error) = self.media_playback.seek(channel_seek.clone()) {
-                            // If there was an error, trace the error and reply with the error
-                            error!("{}", error);
-                            request.reply_to.send(WebReply::failure(format!("{}", error))).unwrap_or(());
+                // Trace the error and reply with the error
+                } else {
+                    error!("Unable to change background: Window not defined.");
+                    request
+                        .reply_to
+                        .send(WebReply::failure(
+                            "Unable to change background: Window not defined.",
+                        ))
+                        .unwrap_or(());
+                }
+            }
 
-                        // Otherwise, backup the seek and indicate success
-                        } else {
-                            // Backup the change
-                            self.backup_handler.backup_media_seek(channel_seek).await;
+            // If changing the idle background of a channel
+            Request::ChangeBackground {
+                background_change:
+                    BackgroundChange::Channel {
+                        channel,
+                        background,
+                    },
+            } => {
+                // Try to change the background
+                if let Err(error) = self
+                    .media_playback
+                    .change_background(channel, background.clone())
+                {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
 
-                            // Indicate success
-                            request.reply_to.send(WebReply::success()).unwrap_or(());
-                        }
+                // Otherwise, pass the change along, backup the change, and indicate success
+                } else {
+                    // Pass the change to the gtk interface (unless mixed by the window compositor)
+                    if !self.media_playback.is_composited(channel) {
+                        self.interface_send.send(InterfaceUpdate::IdleBackground {
+                            channel,
+                            background: background.clone(),
+                        });
+                    }
+
+                    // Backup the change
+                    self.backup_handler
+                        .backup_channel_background(channel, background)
+                        .await;
+
+                    // Reply success to the web interface
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If seeking media on a channel
+            Request::Seek { channel_seek } => {
+                // Try to cue the new media
+                if let Err(error) = self.media_playback.seek(channel_seek.clone()) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the seek and indicate success
+                } else {
+                    // Backup the change (to every channel in the same group)
+                    for channel in self.media_playback.group_channels(channel_seek.channel) {
+                        self.backup_handler
+                            .backup_media_seek(ChannelSeek {
+                                channel,
+                                position: channel_seek.position,
+                            })
+                            .await;
+                    }
+
+                    // Indicate success
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If reporting the status of the system
+            Request::GetStatus => {
+                // Compose the status of every window and channel
+                let system_status = SystemStatus {
+                    windows: self.windows.clone(),
+                    channels: self.media_playback.all_status(),
+                };
+
+                // Reply with the status
+                request
+                    .reply_to
+                    .send(WebReply::status(system_status))
+                    .unwrap_or(());
+            }
+
+            // If reporting the monitors connected to the display
+            Request::GetMonitors => request
+                .reply_to
+                .send(WebReply::monitors(self.interface_send.monitors()))
+                .unwrap_or(()),
+
+            // If reporting the status of a single channel
+            Request::GetChannelStatus { channel } => {
+                // Try to get the status of the channel
+                match self.media_playback.channel_status(channel) {
+                    // Reply with the status
+                    Ok(channel_status) => request
+                        .reply_to
+                        .send(WebReply::channel(channel_status))
+                        .unwrap_or(()),
+
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
+                    }
+                }
+            }
+
+            // Load a new cue list
+            Request::LoadCueList { cue_list } => request
+                .reply_to
+                .send(WebReply::cue_list(self.cue_list.load(cue_list)))
+                .unwrap_or(()),
+
+            // Play the standby cue
+            Request::Go => request
+                .reply_to
+                .send(SystemInterface::cue_list_reply(self.cue_list.go()))
+                .unwrap_or(()),
+
+            // Play the cue before the most recent cue
+            Request::Back => request
+                .reply_to
+                .send(SystemInterface::cue_list_reply(self.cue_list.back()))
+                .unwrap_or(()),
+
+            // Play a specific cue
+            Request::GoTo { cue } => request
+                .reply_to
+                .send(SystemInterface::cue_list_reply(self.cue_list.go_to(cue)))
+                .unwrap_or(()),
+
+            // Report the position in the cue list
+            Request::GetCueList => request
+                .reply_to
+                .send(WebReply::cue_list(self.cue_list.status()))
+                .unwrap_or(()),
+
+            // Run a request at a future time
+            Request::ScheduleRequest { schedule } => {
+                // Try to schedule the request
+                match self.scheduler.schedule(schedule) {
+                    // If successful, backup the requests and reply with every request
+                    Ok(scheduled) => {
+                        info!("Scheduled request {}.", scheduled.id);
+                        self.backup_handler
+                            .backup_schedule(self.scheduler.list())
+                            .await;
+                        request
+                            .reply_to
+                            .send(WebReply::scheduled(self.scheduler.list()))
+                            .unwrap_or(());
                     }
 
-                    // If closing the program
-                    Request::Close => {
-                        // End the loop
-                        return false;
+                    // If there was an error, trace the error and reply with the error
+                    Err(error) => {
+                        error!("{}", error);
+                        request
+                            .reply_to
+                            .send(WebReply::failure(format!("{}", error)))
+                            .unwrap_or(());
                     }
                 }
             }
+
+            // Report every request waiting to run
+            Request::ListScheduled => request
+                .reply_to
+                .send(WebReply::scheduled(self.scheduler.list()))
+                .unwrap_or(()),
+
+            // Cancel a request waiting to run
+            Request::CancelScheduled { id } => {
+                // Try to cancel the request
+                if let Err(error) = self.scheduler.cancel(id) {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, backup the requests and reply with every request
+                } else {
+                    self.backup_handler
+                        .backup_schedule(self.scheduler.list())
+                        .await;
+                    request
+                        .reply_to
+                        .send(WebReply::scheduled(self.scheduler.list()))
+                        .unwrap_or(());
+                }
+            }
+
+            // Presets are recalled before the request is processed (and cannot be nested)
+            Request::RecallPreset { .. } => {
+                error!("Unable to recall preset: Presets cannot be nested.");
+                request
+                    .reply_to
+                    .send(WebReply::failure(
+                        "Unable to recall preset: Presets cannot be nested.",
+                    ))
+                    .unwrap_or(());
+            }
+
+            // Save the current state as a show file
+            Request::SaveShow { path } => {
+                // Try to save the show in the show directory
+                let result = ShowFile::save_path(self.show_directory.as_deref(), &path)
+                    .and_then(|path| self.compose_show().save(path));
+                if let Err(error) = result {
+                    // If there was an error, trace the error and reply with the error
+                    error!("{}", error);
+                    request
+                        .reply_to
+                        .send(WebReply::failure(format!("{}", error)))
+                        .unwrap_or(());
+
+                // Otherwise, indicate success
+                } else {
+                    info!("Saved show to {}.", path);
+                    request.reply_to.send(WebReply::success()).unwrap_or(());
+                }
+            }
+
+            // If closing the program
+            Request::Close => {
+                // End the loop
+                return false;
+            }
         }
 
         // In most cases, indicate to continue normally
         true
     }
 
+    // A helper method to process a request from within the system interface
+    // (any errors are traced) and return the reply
+    async fn process_internal(&mut self, request: Request) -> WebReply {
+        // Process the request with a new reply line
+        let (reply_to, mut reply_receive) = oneshot::channel();
+        self.process_request(WebRequest { reply_to, request }).await;
+
+        // Return the reply (the reply is sent before processing completes)
+        reply_receive
+            .try_recv()
+            .unwrap_or(WebReply::failure("Unable to process request."))
+    }
+
     /// A method to run an infinite number of interations of the system
     /// interface to update the underlying system of any media changes.
     ///
     /// At startup, this loop looks for any exsting backup data and loads
     /// that data, if it's found. Media stored remotely may load too slowly
-    /// to be resumed correctly.
+    /// to be resumed correctly. If there is no backup, the windows, channels,
+    /// and media from the show file (if specified) are loaded instead.
+    ///
+    /// On standby, this loop follows the primary instance instead of loading
+    /// the media from the show file. If this instance has its own backup, the
+    /// backup is loaded and this instance runs normally.
     ///
     /// When this loop completes, it will consume the system interface and drop
     /// all associated data.
     ///
     pub async fn run(mut self) {
-        // Check for an existing backup
-        if let Some((mut window_list, mut channel_list, media_playlist)) =
-            self.backup_handler.reload_backup()
+        // Make sure another instance has not taken over from this instance
+        let mut is_restored = false;
+        if let Some(owner) = self.backup_handler.read_owner().await {
+            // Follow the other instance instead of resuming the backup
+            warn!(
+                "Instance {} took over from this instance. Following as standby.",
+                owner
+            );
+            self.start_standby(owner);
+
+        // Otherwise, check for an existing backup
+        } else if let Some((window_list, channel_list, media_playlist, volume_list)) =
+            self.backup_handler.reload_backup().await
         {
-            // Reload the window list (reloaded in the order they were defined)
-            for window in window_list.drain(..) {
-                // If the window isn't already defined, add it
-                if self.windows.insert(window.window_number) {
-                    self.interface_send
-                        .send(InterfaceUpdate::Window { window: window });
-                }
+            // Reload the windows and channels
+            self.restore_definitions(window_list, channel_list).await;
+            is_restored = true;
+
+            // If on standby, this instance already took over from the primary
+            if self.standby.is_standby() {
+                warn!("Standby instance already took over. Running normally.");
+                self.standby.stop();
+                self.backup_handler.resume().await;
             }
 
-            // Reload the channel list (reloaded in the order they were defined)
-            for channel in channel_list.drain(..) {
-                // If the channel is successfully defined
-                if let Ok(possible_stream) = self.media_playback.define_channel(channel) {
-                    // If a stream was created
-                    if let Some(video_stream) = possible_stream {
-                        // Pass the new video stream to the gtk interface
-                        self.interface_send
-                            .send(InterfaceUpdate::Video { video_stream });
-                    }
+            // Reload the volume of each channel
+            for (channel, channel_volume) in volume_list {
+                // Alert the user if the volume failed to change
+                if let Err(error) = self.media_playback.change_volume(channel_volume) {
+                    error!("Unable to restore volume on channel {}: {}", channel, error);
                 }
             }
 
@@ -307,6 +1145,25 @@ impl SystemInterface {
             self.restore_playlist(media_playlist).await;
         }
 
+        // Load the show file, if specified (on standby, the media comes from the primary)
+        if let Some(show_file) = self.show_file.take() {
+            let is_standby = self.standby.is_standby();
+            self.load_show(show_file, is_restored || is_standby).await;
+        }
+
+        // If running normally, watch for another instance taking over
+        if !self.standby.is_standby() {
+            self.standby.watch_owner(&self.backup_handler);
+        }
+
+        // Check for requests which were waiting to run
+        if let Some(schedule_list) = self.backup_handler.reload_schedule().await {
+            // Alert the user if the requests could not be restored
+            if let Err(error) = self.scheduler.restore(schedule_list) {
+                error!("Unable to restore scheduled requests: {}", error);
+            }
+        }
+
         // Loop the structure indefinitely
         loop {
             // Repeat endlessly until run_once reaches close
@@ -314,10 +1171,592 @@ impl SystemInterface {
                 break;
             }
         }
+
+        // Remove the backup before closing
+        self.backup_handler.close().await;
+    }
+
+    // A helper method to fire each part of a cue from the cue list
+    async fn fire_cue(&mut self, cue: Cue) {
+        // Note the cue
+        info!("Firing cue {}.", cue.name.clone().unwrap_or_default());
+
+        // Change the video frames first (any errors are traced)
+        for channel_allocation in cue.channel_allocations {
+            self.process_internal(Request::ResizeChannel { channel_allocation })
+                .await;
+        }
+
+        // Play the new media
+        for media_cue in cue.media_cues {
+            self.process_internal(Request::CueMedia { media_cue }).await;
+        }
+
+        // Change the channel states
+        for channel_state in cue.channel_states {
+            self.process_internal(Request::ChangeState { channel_state })
+                .await;
+        }
+    }
+
+    // A helper method to run each request in a preset, in order
+    async fn recall_preset(&mut self, name: String) -> WebReply {
+        // Make sure the preset exists
+        let requests = match self.presets.get(&name) {
+            Some(requests) => requests.clone(),
+            None => {
+                error!("Unable to recall preset: Preset not defined.");
+                return WebReply::failure("Unable to recall preset: Preset not defined.");
+            }
+        };
+
+        // Run each request, counting any failures (each failure is traced)
+        info!("Recalling preset {}.", name);
+        let mut failures = 0;
+        for request in requests {
+            if !self.process_internal(request).await.is_success() {
+                failures += 1;
+            }
+        }
+
+        // Reply success if every request succeeded
+        if failures == 0 {
+            WebReply::success()
+
+        // Otherwise, trace the error and reply with the error
+        } else {
+            error!(
+                "Unable to recall preset {}: {} request(s) failed.",
+                name, failures
+            );
+            WebReply::failure(format!(
+                "Unable to recall preset {}: {} request(s) failed.",
+                name, failures
+            ))
+        }
+    }
+
+    // A helper method to load a show file. The windows, channels, and media
+    // are skipped if they were already restored from a backup.
+    async fn load_show(&mut self, show_file: ShowFile, is_restored: bool) {
+        // If nothing was restored, load the windows, channels, and media
+        if !is_restored {
+            // Define the windows and channels (in the same way as a backup)
+            info!("Loading show file.");
+            self.restore_definitions(show_file.windows.clone(), show_file.channels.clone())
+                .await;
+
+            // Backup the windows
+            for window in show_file.windows {
+                self.backup_handler.backup_window(window).await;
+            }
+
+            // Backup the channels which were successfully defined
+            for channel in show_file.channels {
+                if self.media_playback.channel_status(channel.channel).is_ok() {
+                    self.backup_handler.backup_channel(channel).await;
+                }
+            }
+
+            // Play the media (any errors are traced)
+            for media_cue in show_file.cues {
+                self.process_internal(Request::CueMedia { media_cue }).await;
+            }
+        }
+
+        // Load the cue list, if specified
+        if let Some(cue_list) = show_file.cue_list {
+            self.cue_list.load(cue_list);
+        }
+
+        // Save the presets
+        self.presets = show_file.presets;
+    }
+
+    // A helper method to compose a show file from the current state
+    fn compose_show(&self) -> ShowFile {
+        // Get the status of every channel
+        let status_list = self.media_playback.all_status();
+
+        // Compose the show, replacing the cues with the current media
+        ShowFile {
+            windows: self.windows.clone(),
+            channels: status_list
+                .iter()
+                .map(|status| status.media_channel.clone())
+                .collect(),
+            cues: status_list
+                .into_iter()
+                .filter_map(|status| {
+                    // Keep the fades and transition of the cue which started the media, if any
+                    let uri = status.current_uri?;
+                    let channel = status.media_channel.channel;
+                    let (fade_in, fade_out, transition) = match self
+                        .media_playback
+                        .last_cue(channel)
+                    {
+                        Some(cue) if cue.uri == uri => (cue.fade_in, cue.fade_out, cue.transition),
+                        _ => (None, None, None),
+                    };
+
+                    // Replace the loop media with the current loop media
+                    Some(MediaCue {
+                        uri,
+                        channel,
+                        loop_media: status.loop_media,
+                        fade_in,
+                        fade_out,
+                        transition,
+                        start_at: None,
+                    })
+                })
+                .collect(),
+            cue_list: self.cue_list.cue_list(),
+            presets: self.presets.clone(),
+            api_keys: ApiKeys::default(), // keys are never written to a saved show
+        }
+    }
+
+    // A helper function to convert a change in the cue list into a reply
+    fn cue_list_reply(result: Result<CueListStatus>) -> WebReply {
+        match result {
+            // Reply with the new position
+            Ok(cue_list_status) => WebReply::cue_list(cue_list_status),
+
+            // If there was an error, trace the error and reply with the error
+            Err(error) => {
+                error!("{}", error);
+                WebReply::failure(format!("{}", error))
+            }
+        }
+    }
+
+    // A helper method to check if a window has already been defined
+    fn is_window_defined(&self, window_number: u32) -> bool {
+        self.windows
+            .iter()
+            .any(|window| window.window_number == window_number)
+    }
+
+    // A helper method to check if a request can be processed while on standby
+    // (only requests which do not change this instance)
+    fn is_allowed_on_standby(request: &Request) -> bool {
+        matches!(
+            request,
+            Request::GetStatus
+                | Request::GetChannelStatus { .. }
+                | Request::GetMonitors
+                | Request::GetCueList
+                | Request::ListScheduled
+                | Request::Close
+        )
+    }
+
+    // A helper method to start following the primary instance (the backup of
+    // this instance is paused until this instance takes over)
+    fn start_standby(&mut self, primary: String) {
+        self.backup_handler.pause();
+        self.standby.follow(primary, &self.backup_handler);
+    }
+
+    // A helper method to stop the media and follow the instance which took
+    // over from this instance
+    async fn step_down(&mut self, owner: String) {
+        // Stop the media (any errors are traced)
+        warn!(
+            "Instance {} took over from this instance. Stopping media ...",
+            owner
+        );
+        self.process_internal(Request::AllStop { fade_out: None })
+            .await;
+
+        // Follow the other instance
+        self.start_standby(owner);
+    }
+
+    // A helper method to copy the state of the primary instance and take over
+    // if the primary has stopped
+    async fn follow_primary(&mut self, update: InstanceUpdate) {
+        // Make sure this instance is still on standby
+        let Some(primary) = self.standby.primary() else {
+            return;
+        };
+
+        // Check the heartbeat of the primary
+        let has_stopped = self.standby.update_heartbeat(update.heartbeat);
+
+        // If the primary was taken over by another instance, follow that instance instead
+        if let Some(owner) = update.owner
+            && owner != self.backup_handler.address()
+        {
+            info!(
+                "Instance {} took over from {}. Following ...",
+                owner, primary
+            );
+            self.standby.follow(owner, &self.backup_handler);
+            return;
+        }
+
+        // If the primary changed, copy the definitions and volumes and note the media
+        if let Some((window_list, channel_list, media_playlist, volume_list)) = update.backup {
+            self.mirror_definitions(window_list, channel_list).await;
+            for (_, channel_volume) in volume_list {
+                self.process_internal(Request::ChangeVolume { channel_volume })
+                    .await;
+            }
+            self.standby.set_playlist(media_playlist);
+        }
+
+        // Stop the media which the primary stopped
+        for channel in self.standby.release_stopped() {
+            info!("Releasing media on channel {}.", channel);
+            if let Err(error) = self.media_playback.clear_media(channel) {
+                error!("Unable to release media on channel {}: {}", channel, error);
+            }
+        }
+
+        // Look through the playlist for media
+        let mut held_media = Vec::new();
+        for (channel, playback) in self.standby.playlist().iter() {
+            // If the media is new, load the media and hold it paused
+            let position = self.standby.primary_position(playback);
+            if !self.standby.is_held(*channel, &playback.media_cue.uri) {
+                info!("Holding media on channel {}.", channel);
+                match self.media_playback.hold_media(playback.media_cue.clone()) {
+                    // Note the media on the channel
+                    Ok(()) => held_media.push((*channel, playback.media_cue.uri.clone())),
+
+                    // Alert the user if the media failed to load
+                    Err(error) => {
+                        error!("Unable to hold media on channel {}: {}", channel, error)
+                    }
+                }
+
+            // Otherwise, once the media is loaded, keep the media near the position of the primary
+            } else if let Ok(status) = self.media_playback.channel_status(*channel)
+                && status.state.is_some()
+                && status
+                    .position
+                    .is_none_or(|current| StandbyMonitor::is_drifted(current, position))
+            {
+                self.media_playback
+                    .seek(ChannelSeek {
+                        channel: *channel,
+                        position,
+                    })
+                    .unwrap_or(());
+            }
+        }
+        for (channel, uri) in held_media {
+            self.standby.set_held(channel, uri);
+        }
+
+        // If the primary has stopped, take over
+        if has_stopped {
+            self.take_over(primary).await;
+        }
+    }
+
+    // A helper method to match the windows and channels of this instance to
+    // the windows and channels of the primary instance (any errors are traced)
+    async fn mirror_definitions(&mut self, window_list: WindowList, channel_list: ChannelList) {
+        // Define or redefine each window (the window is unchanged if the definition matches)
+        for window in window_list.iter().cloned() {
+            if !self.is_window_defined(window.window_number) {
+                self.process_internal(Request::DefineWindow { window })
+                    .await;
+            } else if !self.windows.contains(&window) {
+                self.process_internal(Request::RedefineWindow { window })
+                    .await;
+            }
+        }
+
+        // Remove any channel which the primary removed
+        for status in self.media_playback.all_status() {
+            let channel = status.media_channel.channel;
+            if !channel_list
+                .iter()
+                .any(|media_channel| media_channel.channel == channel)
+            {
+                self.process_internal(Request::RemoveChannel { channel })
+                    .await;
+            }
+        }
+
+        // Define or redefine each channel (the channel is unchanged if the definition matches)
+        for media_channel in channel_list {
+            match self.media_playback.channel_status(media_channel.channel) {
+                Err(_) => {
+                    self.process_internal(Request::DefineChannel { media_channel })
+                        .await;
+                }
+                Ok(status)
+                    if !SystemInterface::is_same_output(&status.media_channel, &media_channel) =>
+                {
+                    // Redefining the channel stops the media, so load it again
+                    self.standby.release(media_channel.channel);
+                    self.process_internal(Request::RedefineChannel { media_channel })
+                        .await;
+                }
+
+                // Otherwise, match the layer, opacity, and visibility without
+                // redefining the channel
+                Ok(status) => {
+                    self.mirror_appearance(status.media_channel, media_channel)
+                        .await
+                }
+            }
+        }
+
+        // Remove any window which the primary removed (once no channel uses it)
+        let removed: Vec<u32> = self
+            .windows
+            .iter()
+            .map(|window| window.window_number)
+            .filter(|number| {
+                !window_list
+                    .iter()
+                    .any(|window| window.window_number == *number)
+            })
+            .collect();
+        for window_number in removed {
+            self.process_internal(Request::RemoveWindow { window_number })
+                .await;
+        }
+    }
+
+    // A helper method to change the layer, opacity, and visibility of a
+    // channel to match the definition from the primary instance
+    async fn mirror_appearance(&mut self, current: MediaChannel, media_channel: MediaChannel) {
+        // Match the layer, if specified
+        let channel = media_channel.channel;
+        if let Some(layer) = media_channel.layer
+            && current.layer != Some(layer)
+        {
+            self.process_internal(Request::ChangeLayer {
+                channel_layer: ChannelLayer {
+                    channel,
+                    layer: LayerChange::Index(layer),
+                },
+            })
+            .await;
+        }
+
+        // Match the opacity, if specified
+        if let Some(opacity) = media_channel.opacity
+            && current.opacity != Some(opacity)
+        {
+            self.process_internal(Request::ChangeOpacity {
+                channel_opacity: ChannelOpacity {
+                    channel,
+                    opacity,
+                    fade: None,
+                },
+            })
+            .await;
+        }
+
+        // Match the visibility
+        if current.hidden != media_channel.hidden {
+            self.process_internal(Request::ChangeVisibility {
+                channel_visibility: ChannelVisibility {
+                    channel,
+                    visible: !media_channel.hidden,
+                },
+            })
+            .await;
+        }
+    }
+
+    // A helper function to check if two channel definitions have the same
+    // outputs (ignoring the layer, opacity, and visibility, which can change
+    // without redefining the channel)
+    fn is_same_output(current: &MediaChannel, media_channel: &MediaChannel) -> bool {
+        MediaChannel {
+            layer: current.layer,
+            opacity: current.opacity,
+            hidden: current.hidden,
+            ..media_channel.clone()
+        } == *current
+    }
+
+    // A helper method to take over from the primary instance and backup the
+    // state of the primary as the state of this instance
+    async fn take_over(&mut self, primary: String) {
+        // Stop following the primary
+        warn!("Primary instance {} stopped. Taking over ...", primary);
+        let playlist = self.standby.playlist().clone();
+        let positions: Vec<(u32, u64)> = playlist
+            .iter()
+            .map(|(channel, playback)| (*channel, self.standby.primary_position(playback)))
+            .collect();
+        self.standby.stop();
+
+        // Resume the media from the position the primary would have reached
+        for (channel, position) in positions {
+            // Backup the media (the media is already loaded)
+            let Some(playback) = playlist.get(&channel) else {
+                continue;
+            };
+            self.backup_handler.backup_playback(playback.clone()).await;
+
+            // Seek to the position and change to the state of the primary (any errors are traced)
+            self.process_internal(Request::Seek {
+                channel_seek: ChannelSeek { channel, position },
+            })
+            .await;
+            self.process_internal(Request::ChangeState {
+                channel_state: ChannelState {
+                    channel,
+                    state: playback.state.clone(),
+                },
+            })
+            .await;
+        }
+
+        // Copy the state of this instance to the backup and mark the primary as replaced
+        self.backup_handler.resume().await;
+        self.backup_handler.claim_instance(&primary).await;
+
+        // Watch for another instance taking over from this instance
+        self.standby.watch_owner(&self.backup_handler);
+    }
+
+    // A helper method to define windows and channels from a backup or show
+    // file (each is defined in the order listed)
+    async fn restore_definitions(&mut self, window_list: WindowList, channel_list: ChannelList) {
+        // Define each window
+        for window in window_list {
+            // If the window isn't already defined, add it
+            if !self.is_window_defined(window.window_number) {
+                // Create the window compositor, if specified
+                let window_number = window.window_number;
+                let video_sink = match self.media_playback.update_compositor(&window) {
+                    Ok(video_sink) => video_sink,
+                    Err(error) => {
+                        error!("Unable to define window {}: {}", window_number, error);
+                        None
+                    }
+                };
+
+                // Save the window and pass it to the gtk interface (with any mixed video)
+                self.windows.push(window.clone());
+                self.interface_send.send(InterfaceUpdate::Window { window });
+                if let Some((video_sink, ready)) = video_sink {
+                    self.show_compositor(window_number, video_sink, ready);
+                }
+            }
+        }
+
+        // Define each channel
+        for channel in channel_list {
+            // Try to define the channel
+            let channel_number = channel.channel;
+            match self.media_playback.define_channel(channel) {
+                // If a stream was created, pass it to the gtk interface
+                Ok(Some(video_stream)) => {
+                    self.show_video(video_stream);
+                }
+
+                // If no stream was needed, do nothing
+                Ok(None) => (),
+
+                // Alert the user if the channel could not be defined
+                Err(error) => {
+                    error!("Unable to define channel {}: {}", channel_number, error);
+                }
+            }
+        }
+    }
+
+    // A helper method to pass a new video stream to the gtk interface and
+    // wait for the video to be embedded in the window before starting the
+    // channel (so that the video sink never opens a window of its own)
+    fn show_video(&mut self, video_stream: VideoStream) {
+        // Pass the new video stream to the gtk interface
+        let channel = video_stream.channel;
+        self.interface_send
+            .send(InterfaceUpdate::Video { video_stream });
+
+        // Match the layer, opacity, and visibility of the channel
+        if let Ok(status) = self.media_playback.channel_status(channel) {
+            let media_channel = status.media_channel;
+            if let Some(layer) = media_channel.layer {
+                self.interface_send.send(InterfaceUpdate::Layer {
+                    channel_layer: ChannelLayer {
+                        channel,
+                        layer: LayerChange::Index(layer),
+                    },
+                });
+            }
+            if let Some(opacity) = media_channel.opacity {
+                self.interface_send.send(InterfaceUpdate::Opacity {
+                    channel_opacity: ChannelOpacity {
+                        channel,
+                        opacity,
+                        fade: None,
+                    },
+                });
+            }
+            if media_channel.hidden {
+                self.interface_send.send(InterfaceUpdate::Visibility {
+                    channel_visibility: ChannelVisibility {
+                        channel,
+                        visible: false,
+                    },
+                });
+            }
+        }
+
+        // Start the channel outputs once the video is ready
+        if let Err(error) = self.media_playback.wait_for_channel(channel) {
+            error!("Unable to start channel {}: {}", channel, error);
+        }
+    }
+
+    // A helper method to pass the mixed video for a window to the gtk
+    // interface and wait for the video to be embedded in the window before
+    // starting the window compositor
+    fn show_compositor(&mut self, window_number: u32, video_sink: gst::Element, ready: VideoReady) {
+        // Pass the mixed video to the gtk interface
+        self.interface_send.send(InterfaceUpdate::Compositor {
+            window_number,
+            video_sink,
+            ready,
+        });
+
+        // Start the window compositor once the video is ready
+        if let Err(error) = self.media_playback.wait_for_compositor(window_number) {
+            error!("Unable to start window {}: {}", window_number, error);
+        }
     }
 
     // A helper method to reload the media playlist from a backup
     async fn restore_playlist(&mut self, mut playlist: MediaPlaylist) {
+        // Note when the media was started
+        let start = Instant::now();
+
+        // Separate the channels which play together in a group
+        let groups = BackupHandler::take_groups(&mut playlist);
+        let mut grouped = Vec::new();
+        for (cue_group, seek_to, state) in groups {
+            // Note the channels in the group
+            let channels: Vec<u32> = cue_group.media_cues.iter().map(|cue| cue.channel).collect();
+            info!("Playing grouped media on channels {:?}.", channels);
+
+            // Restart the group together at the saved position (compensated when it starts)
+            if let Err(error) =
+                self.media_playback
+                    .resume_group(cue_group, seek_to.as_millis() as u64, state)
+            {
+                error!(
+                    "Unable to restart media on channels {:?}: {}",
+                    channels, error
+                );
+            }
+            grouped.extend(channels);
+        }
+
         // Look through the playlist for media
         for (channel, playback) in playlist.iter() {
             // For each channel, cue the media
@@ -329,14 +1768,24 @@ impl SystemInterface {
             }
         }
 
-        // Wait for all the media to start playing and count the delay
-        sleep(Duration::from_millis(500)).await;
-        let delay_millis: u64 = 500; // the delay above
+        // Wait for all the media to load (or until the timeout)
+        while start.elapsed() < Duration::from_millis(RESTORE_TIMEOUT)
+            && !playlist.keys().chain(grouped.iter()).all(|channel| {
+                self.media_playback
+                    .channel_status(*channel)
+                    .is_ok_and(|status| status.state.is_some())
+            })
+        {
+            sleep(Duration::from_millis(10)).await;
+        }
 
         // Look through the playlist for seek position
         for (channel, playback) in playlist.iter() {
-            // Calculate the new seek position
-            let position = playback.seek_to.as_millis() as u64 + delay_millis; // compensate for our additional delays
+            // Calculate the new seek position, compensating playing media for the time spent loading
+            let mut position = playback.seek_to.as_millis() as u64;
+            if playback.state == PlaybackState::Playing {
+                position += start.elapsed().as_millis() as u64;
+            }
             info!(
                 "Seeking channel {} to {}.{:0>3}.",
                 channel,
@@ -0,0 +1,213 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to share the media clock between instances of Apollo on the
+//! network. One instance publishes its clock, and the other instances follow
+//! that clock so that media plays at the same moment on every machine.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import GLib Library
+use glib;
+
+// Import Gstreamer Library
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_net as gst_net;
+
+// Import tracing features
+use tracing::{info, warn};
+
+// Import anyhow features
+use anyhow::{Context, Result};
+
+/// A structure to hold the clock and base time shared by every output
+/// pipeline on this instance (so that the running time of every output, and
+/// of every group of channels, is the same)
+///
+#[derive(Clone, Debug)]
+pub struct OutputClock {
+    clock: gst::Clock,         // the clock for every output
+    base_time: gst::ClockTime, // the base time for every output
+}
+
+// Implement key functionality for the output clock
+impl OutputClock {
+    /// A function to create a new output clock with a base time of the
+    /// current time on the clock
+    ///
+    pub fn new(clock: gst::Clock) -> OutputClock {
+        let base_time = clock.time().unwrap_or(gst::ClockTime::ZERO);
+        OutputClock { clock, base_time }
+    }
+
+    /// A method to return the clock for every output
+    ///
+    pub fn clock(&self) -> &gst::Clock {
+        &self.clock
+    }
+
+    /// A method to switch a pipeline to the shared clock and base time. This
+    /// method should be called before the pipeline starts playing.
+    ///
+    pub fn apply(&self, pipeline: &gst::Pipeline) {
+        pipeline.use_clock(Some(&self.clock));
+        pipeline.set_start_time(gst::ClockTime::NONE);
+        pipeline.set_base_time(self.base_time);
+    }
+}
+
+/// A structure to hold the clock shared with other instances on the network
+///
+#[derive(Debug)]
+pub struct NetworkClock {
+    clock: gst::Clock, // the clock for every deck on this instance
+    _provider: Option<gst_net::NetTimeProvider>, // the time provider which publishes the clock, if publishing
+}
+
+// Implement key functionality for the network clock
+impl NetworkClock {
+    /// A function to publish or follow the network clock. When following,
+    /// the clock synchronizes in the background (start times are refused
+    /// until the clock is synchronized).
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the address is invalid.
+    ///
+    pub fn new(clock_source: ClockSource) -> Result<NetworkClock> {
+        match clock_source {
+            // Publish the clock of this instance
+            ClockSource::Publish { address } => {
+                // Create the clock and the time provider
+                let (host, port) = NetworkClock::parse_address(&address)?;
+                let clock = NetworkClock::realtime_clock();
+                let provider = gst_net::NetTimeProvider::new(&clock, Some(host), port);
+                info!("Publishing network clock at {}.", address);
+
+                // Return the network clock
+                Ok(NetworkClock {
+                    clock,
+                    _provider: Some(provider),
+                })
+            }
+
+            // Follow the clock of another instance
+            ClockSource::Follow { address } => {
+                // Create the clock
+                let (host, port) = NetworkClock::parse_address(&address)?;
+                let clock: gst::Clock =
+                    gst_net::NetClientClock::new(None, host, port, gst::ClockTime::ZERO).upcast();
+
+                // Report whenever the clock synchronizes or loses synchronization
+                let clock_address = address.clone();
+                clock.connect_synced(move |_, is_synced| {
+                    if is_synced {
+                        info!("Following network clock at {}.", clock_address);
+                    } else {
+                        warn!("Lost network clock at {}. Retrying ...", clock_address);
+                    }
+                });
+                if !clock.is_synced() {
+                    info!("Waiting for network clock at {} ...", address);
+                }
+
+                // Return the network clock
+                Ok(NetworkClock {
+                    clock,
+                    _provider: None,
+                })
+            }
+        }
+    }
+
+    /// A method to return the clock shared with other instances
+    ///
+    pub fn clock(&self) -> &gst::Clock {
+        &self.clock
+    }
+
+    /// A function to create a clock which reports the time since the Unix
+    /// epoch (so that start times can be given as a time of day)
+    ///
+    pub fn realtime_clock() -> gst::Clock {
+        glib::Object::builder::<gst::SystemClock>()
+            .property("clock-type", gst::ClockType::Realtime)
+            .build()
+            .upcast()
+    }
+
+    // A helper function to split an address into the host and port
+    fn parse_address(address: &str) -> Result<(&str, i32)> {
+        // Split the address at the last colon
+        let (host, port) = address
+            .rsplit_once(':')
+            .context("Invalid clock address: Port not specified.")?;
+
+        // Try to parse the port
+        let port = port
+            .parse::<u16>()
+            .context("Invalid clock address: Invalid port.")?;
+        Ok((host, port as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test following a clock published on the same machine
+    #[test]
+    fn loopback_clock() {
+        // Initialize gstreamer
+        gst::init().expect("Unable to initialize gstreamer.");
+
+        // Publish the clock and follow it
+        let address = String::from("127.0.0.1:27658");
+        let server = NetworkClock::new(ClockSource::Publish {
+            address: address.clone(),
+        })
+        .expect("Unable to publish network clock.");
+        let client = NetworkClock::new(ClockSource::Follow { address })
+            .expect("Unable to follow network clock.");
+
+        // Wait for the client to synchronize (without blocking at creation)
+        client
+            .clock()
+            .wait_for_sync(gst::ClockTime::from_seconds(10))
+            .expect("Network clock did not synchronize.");
+        assert!(client.clock().is_synced());
+
+        // Make sure both clocks report nearly the same time of day
+        let server_time = server.clock().time().expect("Invalid server time.");
+        let client_time = client.clock().time().expect("Invalid client time.");
+        let difference = server_time.mseconds().abs_diff(client_time.mseconds());
+        assert!(difference < 100, "Clocks differ by {} ms.", difference);
+    }
+
+    // Test the validation of clock addresses
+    #[test]
+    fn parse_address() {
+        assert_eq!(
+            NetworkClock::parse_address("127.0.0.1:27658").unwrap(),
+            ("127.0.0.1", 27658)
+        );
+        assert!(NetworkClock::parse_address("127.0.0.1").is_err());
+        assert!(NetworkClock::parse_address("127.0.0.1:port").is_err());
+    }
+}
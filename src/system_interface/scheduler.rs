@@ -0,0 +1,421 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to hold requests until a future time. The timing is computed
+//! against the Gstreamer system clock, the same clock which times the media
+//! pipelines. Requests for a wall clock time are checked against the system
+//! time before they run, so that a change to the system time never runs a
+//! request early or skips it.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import standard library features
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Import Gstreamer Library
+use gst::prelude::*;
+use gstreamer as gst;
+
+// Import Tokio features
+use tokio::sync::mpsc;
+
+// Import FNV HashMap
+use fnv::FnvHashMap;
+
+// Import the chrono features
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+// Import the tracing features
+use tracing::error;
+
+// Import anyhow features
+use anyhow::Result;
+
+// Define module constants
+const WALL_CLOCK_CHECK: u64 = 1000; // the longest time between checks of the system time for wall clock requests, in milliseconds
+const MAX_GAP: i64 = 180; // the longest change to daylight saving time, in minutes
+
+/// A helper type to store a scheduled request and its clock entry
+///
+#[derive(Debug)]
+struct PendingRequest {
+    scheduled: ScheduledRequest,      // the request and the time to run it
+    clock_id: gst::SingleShotClockId, // the clock entry which wakes the scheduler
+}
+
+/// A structure to hold requests until a future time
+///
+#[derive(Debug)]
+pub struct Scheduler {
+    next_id: u64, // the identifier for the next scheduled request
+    pending: FnvHashMap<u64, PendingRequest>, // the map of identifiers to pending requests
+    clock: gst::Clock, // the clock which times every request
+    fire_send: mpsc::UnboundedSender<u64>, // the sending line for requests which are due
+    fire_receive: mpsc::UnboundedReceiver<u64>, // the receiving line for requests which are due
+}
+
+// Implement key functionality for the scheduler
+impl Scheduler {
+    /// A function to create a new, empty scheduler
+    ///
+    /// # Note
+    ///
+    /// Gstreamer must be initialized before the scheduler is created.
+    ///
+    pub fn new() -> Scheduler {
+        // Create the line for requests which are due
+        let (fire_send, fire_receive) = mpsc::unbounded_channel();
+
+        // Return the new scheduler
+        Scheduler {
+            next_id: 1,
+            pending: FnvHashMap::default(),
+            clock: gst::SystemClock::obtain(),
+            fire_send,
+            fire_receive,
+        }
+    }
+
+    /// A method to schedule a new request and return the scheduled request
+    ///
+    pub fn schedule(&mut self, schedule: Schedule) -> Result<ScheduledRequest> {
+        // Calculate the time to run the request
+        let (fire_at, wall_clock) = match (schedule.delay, schedule.time) {
+            // A delay from now
+            (Some(delay), _) => (Scheduler::now() + delay, false),
+
+            // A wall clock time
+            (None, Some(time)) => (Scheduler::next_wall_clock(&time)?, true),
+
+            // Otherwise, return an error
+            (None, None) => {
+                return Err(anyhow!("Unable to schedule request: No delay or time."));
+            }
+        };
+
+        // Compose the scheduled request
+        let scheduled = ScheduledRequest {
+            id: self.next_id,
+            fire_at,
+            wall_clock,
+            request: *schedule.request,
+        };
+        self.next_id += 1;
+
+        // Add the request to the clock
+        self.add(scheduled.clone())?;
+
+        // Return the scheduled request
+        Ok(scheduled)
+    }
+
+    /// A method to restore a list of scheduled requests from a backup.
+    /// Requests which should have run already will run immediately.
+    ///
+    pub fn restore(&mut self, schedule_list: ScheduleList) -> Result<()> {
+        // Add each request to the clock
+        for scheduled in schedule_list {
+            // Make sure the identifiers stay unique
+            self.next_id = self.next_id.max(scheduled.id + 1);
+
+            // Add the request
+            self.add(scheduled)?;
+        }
+
+        // Indicate success
+        Ok(())
+    }
+
+    /// A method to cancel a request waiting to run
+    ///
+    pub fn cancel(&mut self, id: u64) -> Result<()> {
+        // Make sure the request exists
+        match self.pending.remove(&id) {
+            // Remove the request from the clock
+            Some(pending) => {
+                pending.clock_id.unschedule();
+                Ok(())
+            }
+
+            // Otherwise, throw an error
+            None => Err(anyhow!("Unable to cancel request: Request not found.")),
+        }
+    }
+
+    /// A method to list every request waiting to run, in the order they will run
+    ///
+    pub fn list(&self) -> ScheduleList {
+        // Copy every request
+        let mut schedule_list: ScheduleList = self
+            .pending
+            .values()
+            .map(|pending| pending.scheduled.clone())
+            .collect();
+
+        // Sort the requests and return the list
+        schedule_list.sort_by_key(|scheduled| (scheduled.fire_at, scheduled.id));
+        schedule_list
+    }
+
+    /// A method to wait for the next request which is due
+    ///
+    /// # Note
+    ///
+    /// This method is cancel safe and can be used in a select statement.
+    ///
+    pub async fn next_request(&mut self) -> Option<Request> {
+        // Wait for a request which has not been cancelled
+        loop {
+            // Wait for the next request
+            let id = self.fire_receive.recv().await?;
+
+            // Make sure the request is still pending
+            let pending = match self.pending.remove(&id) {
+                Some(pending) => pending,
+                None => continue,
+            };
+
+            // If a wall clock request is not yet due, check again later
+            if pending.scheduled.wall_clock && pending.scheduled.fire_at > Scheduler::now() {
+                if self.add(pending.scheduled).is_err() {
+                    error!("Unable to reschedule request {}.", id);
+                }
+                continue;
+            }
+
+            // Otherwise, return the request
+            return Some(pending.scheduled.request);
+        }
+    }
+
+    // A helper method to add a request to the clock. Wall clock requests
+    // wake the scheduler at least once a second to check the system time.
+    fn add(&mut self, scheduled: ScheduledRequest) -> Result<()> {
+        // Calculate the remaining time before the request should run
+        let mut remaining = scheduled.fire_at.saturating_sub(Scheduler::now());
+        if scheduled.wall_clock {
+            remaining = remaining.min(WALL_CLOCK_CHECK);
+        }
+
+        // Create the clock entry
+        let now = self
+            .clock
+            .time()
+            .ok_or(anyhow!("Unable to schedule request: Invalid clock."))?;
+        let clock_id = self
+            .clock
+            .new_single_shot_id(now + gst::ClockTime::from_mseconds(remaining));
+
+        // Wake the scheduler when the request is due
+        let fire_send = self.fire_send.clone();
+        let id = scheduled.id;
+        clock_id
+            .wait_async(move |_, _, _| {
+                fire_send.send(id).unwrap_or(());
+            })
+            .map_err(|_| anyhow!("Unable to schedule request: Invalid clock."))?;
+
+        // Save the pending request
+        self.pending.insert(
+            scheduled.id,
+            PendingRequest {
+                scheduled,
+                clock_id,
+            },
+        );
+
+        // Indicate success
+        Ok(())
+    }
+
+    // A helper function to return the current time in milliseconds since the Unix epoch
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    // A helper function to find the next occurance of a local wall clock
+    // time, in milliseconds since the Unix epoch
+    fn next_wall_clock(time: &str) -> Result<u64> {
+        // Try to parse the time
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .map_err(|_| anyhow!("Unable to schedule request: Invalid time {}.", time))?;
+
+        // Find the next occurance in the local timezone
+        let now = Local::now();
+        let fire =
+            Scheduler::next_occurance(time, now.date_naive(), now.timestamp_millis(), |local| {
+                local
+                    .and_local_timezone(Local)
+                    .earliest()
+                    .map(|fire| fire.timestamp_millis())
+            })
+            .ok_or(anyhow!("Unable to schedule request: Invalid time."))?;
+
+        // Return the time
+        Ok(fire as u64)
+    }
+
+    // A helper function to find the next occurance of a wall clock time after
+    // now (in milliseconds since the Unix epoch), starting today. The resolve
+    // function converts a local time to milliseconds since the Unix epoch
+    // (the earliest time, if the local time occurs twice), or returns None if
+    // the local time is skipped by a change to daylight saving time.
+    fn next_occurance(
+        time: NaiveTime,
+        today: NaiveDate,
+        now: i64,
+        resolve: impl Fn(NaiveDateTime) -> Option<i64>,
+    ) -> Option<i64> {
+        // Try the time today, and otherwise tomorrow
+        [Some(today), today.succ_opt()]
+            .into_iter()
+            .flatten()
+            .filter_map(|date| {
+                // Convert the local time
+                let local = date.and_time(time);
+                resolve(local).or_else(|| {
+                    // If the time was skipped, use the first minute after the change
+                    let minute = local.with_second(0)?.with_nanosecond(0)?;
+                    (1..=MAX_GAP).find_map(|step| resolve(minute + TimeDelta::minutes(step)))
+                })
+            })
+            .find(|fire| *fire > now)
+    }
+}
+
+// Implement the drop trait for the scheduler
+impl Drop for Scheduler {
+    /// This method removes every pending request from the clock
+    ///
+    fn drop(&mut self) {
+        for (_, pending) in self.pending.drain() {
+            pending.clock_id.unschedule();
+        }
+    }
+}
+
+// Tests of the scheduler module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Define test constants
+    const HOUR: i64 = 3_600_000; // one hour, in milliseconds
+
+    // A helper function to create a local time on a date in 2026
+    fn local(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    // A helper function to convert local times in a timezone five hours behind
+    // UTC, which moves one hour ahead from 2:00 on March 8 to 2:00 on November 1
+    fn resolve(time: NaiveDateTime) -> Option<i64> {
+        let utc = time.and_utc().timestamp_millis();
+        if time < local(3, 8, 2, 0) {
+            Some(utc + 5 * HOUR)
+        } else if time < local(3, 8, 3, 0) {
+            None // skipped when the clocks move ahead
+        } else if time < local(11, 1, 2, 0) {
+            Some(utc + 4 * HOUR) // the earliest time, if the time occurs twice
+        } else {
+            Some(utc + 5 * HOUR)
+        }
+    }
+
+    // A helper function to find the next occurance of a time after a local time
+    fn next(time: &str, now: NaiveDateTime) -> Option<i64> {
+        Scheduler::next_occurance(
+            NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap(),
+            now.date(),
+            resolve(now).unwrap(),
+            resolve,
+        )
+    }
+
+    // Test a time later today and a time which has already passed
+    #[test]
+    fn later_and_past_times() {
+        // A time later today runs today
+        assert_eq!(
+            next("19:30:00", local(6, 1, 12, 0)),
+            resolve(local(6, 1, 19, 30))
+        );
+
+        // A time which has passed runs tomorrow
+        assert_eq!(
+            next("09:00:00", local(6, 1, 12, 0)),
+            resolve(local(6, 2, 9, 0))
+        );
+
+        // The current time runs tomorrow (not immediately)
+        assert_eq!(
+            next("12:00:00", local(6, 1, 12, 0)),
+            resolve(local(6, 2, 12, 0))
+        );
+
+        // The last day of the year repeats on the first day of the next year
+        assert_eq!(
+            next("08:00:00", local(12, 31, 9, 0)),
+            resolve(
+                NaiveDate::from_ymd_opt(2027, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(8, 0, 0)
+                    .unwrap()
+            )
+        );
+    }
+
+    // Test times around the changes to daylight saving time
+    #[test]
+    fn daylight_saving_time() {
+        // A skipped time runs when the clocks move ahead (not tomorrow)
+        assert_eq!(
+            next("02:30:00", local(3, 8, 0, 0)),
+            resolve(local(3, 8, 3, 0))
+        );
+        assert_eq!(
+            next("02:30:00", local(3, 8, 0, 0)).unwrap() - resolve(local(3, 8, 0, 0)).unwrap(),
+            2 * HOUR
+        );
+
+        // A repeated time runs the first time
+        assert_eq!(
+            next("01:30:00", local(11, 1, 0, 0)),
+            Some(local(11, 1, 1, 30).and_utc().timestamp_millis() + 4 * HOUR)
+        );
+
+        // A time after the clocks move back is the correct length of time away
+        assert_eq!(
+            next("12:00:00", local(11, 1, 0, 0)).unwrap() - resolve(local(11, 1, 0, 0)).unwrap(),
+            13 * HOUR
+        );
+    }
+
+    // Test an invalid time
+    #[test]
+    fn invalid_time() {
+        assert!(Scheduler::next_wall_clock("25:00:00").is_err());
+        assert!(Scheduler::next_wall_clock("7pm").is_err());
+    }
+}
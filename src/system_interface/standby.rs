@@ -0,0 +1,384 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to watch a primary instance of Apollo while this instance is on
+//! standby. The module tracks the heartbeat of the primary and the media held
+//! on each channel of this instance. While running normally, the module also
+//! watches for another instance which has taken over from this instance.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import submodule definitions
+use super::backup_handler::{BackupHandler, InstanceUpdate};
+
+// Import standard library features
+use std::future;
+use std::time::{Duration, Instant};
+
+// Import Tokio features
+use tokio::sync::mpsc;
+
+// Import FNV HashMap
+use fnv::FnvHashMap;
+
+// Define module constants
+const POLL_INTERVAL: u64 = 500; // the time between checks of the primary (or of the owner of this instance), in milliseconds
+const FAILURE_TIMEOUT: u64 = 3000; // the time without a new heartbeat before taking over, in milliseconds
+const MAX_DRIFT: u64 = 5000; // the largest difference from the primary before held media is moved, in milliseconds
+
+/// A structure to watch the primary instance while on standby
+///
+#[derive(Debug)]
+pub struct StandbyMonitor {
+    primary: Option<String>, // the address of the primary instance, if on standby
+    heartbeat: Option<u64>,  // the most recent heartbeat from the primary
+    last_change: Instant,    // the time the heartbeat last changed
+    held_media: FnvHashMap<u32, String>, // the uri of the media held on each channel
+    playlist: MediaPlaylist, // the most recent media playlist of the primary
+    updates: Option<mpsc::Receiver<InstanceUpdate>>, // the checks of the primary, if on standby
+    owner: Option<mpsc::Receiver<String>>, // the check for an instance which took over from this instance, if running normally
+}
+
+// Implement key functionality for the standby monitor
+impl StandbyMonitor {
+    /// A function to create a new standby monitor. The monitor does nothing
+    /// until it is told to follow a primary or watch for a new owner.
+    ///
+    pub fn new() -> StandbyMonitor {
+        StandbyMonitor {
+            primary: None,
+            heartbeat: None,
+            last_change: Instant::now(),
+            held_media: FnvHashMap::default(),
+            playlist: MediaPlaylist::default(),
+            updates: None,
+            owner: None,
+        }
+    }
+
+    /// A method to start following the primary instance with the provided
+    /// address. The primary is checked in the background, through the backup.
+    ///
+    pub fn follow(&mut self, primary: String, backup_handler: &BackupHandler) {
+        // Stop watching for a new owner and reset the state of the primary
+        self.owner = None;
+        self.heartbeat = None;
+        self.last_change = Instant::now();
+        self.held_media.clear();
+        self.playlist.clear();
+
+        // Start checking the primary
+        self.updates =
+            backup_handler.watch_instance(&primary, Duration::from_millis(POLL_INTERVAL));
+        self.primary = Some(primary);
+    }
+
+    /// A method to start watching for another instance which has taken over
+    /// from this instance (while this instance runs normally).
+    ///
+    pub fn watch_owner(&mut self, backup_handler: &BackupHandler) {
+        self.owner = backup_handler.watch_owner(Duration::from_millis(POLL_INTERVAL));
+    }
+
+    /// A method to return the address of the primary, if on standby
+    ///
+    pub fn primary(&self) -> Option<String> {
+        self.primary.clone()
+    }
+
+    /// A method to check if this instance is on standby
+    ///
+    pub fn is_standby(&self) -> bool {
+        self.primary.is_some()
+    }
+
+    /// A method to stop watching the primary (once this instance takes over)
+    ///
+    pub fn stop(&mut self) {
+        self.primary = None;
+        self.updates = None;
+        self.held_media.clear();
+        self.playlist.clear();
+    }
+
+    /// A method to record the newest heartbeat from the primary. Returns true
+    /// if the primary has stopped updating the heartbeat.
+    ///
+    /// # Note
+    ///
+    /// The heartbeat is only compared with previous heartbeats, so the clocks
+    /// on the two machines do not need to match. If the primary has never
+    /// written a heartbeat (or closed normally and removed it), the primary
+    /// is not considered failed.
+    ///
+    pub fn update_heartbeat(&mut self, heartbeat: Option<u64>) -> bool {
+        self.check_heartbeat(heartbeat, Instant::now())
+    }
+
+    /// A method to return the most recent media playlist of the primary
+    ///
+    pub fn playlist(&self) -> &MediaPlaylist {
+        &self.playlist
+    }
+
+    /// A method to replace the media playlist of the primary
+    ///
+    pub fn set_playlist(&mut self, playlist: MediaPlaylist) {
+        self.playlist = playlist;
+    }
+
+    /// A method to calculate the current position of media on the primary,
+    /// compensating playing media for the time since the position was
+    /// recorded (in milliseconds).
+    ///
+    pub fn primary_position(&self, playback: &MediaPlayback) -> u64 {
+        self.position_at(playback, Instant::now())
+    }
+
+    /// A method to check if media at the provided position has moved too far
+    /// from the provided position on the primary (in milliseconds)
+    ///
+    pub fn is_drifted(position: u64, primary_position: u64) -> bool {
+        position.abs_diff(primary_position) > MAX_DRIFT
+    }
+
+    /// A method to check if the media is already held on a channel
+    ///
+    pub fn is_held(&self, channel: u32, uri: &str) -> bool {
+        self.held_media
+            .get(&channel)
+            .is_some_and(|held| held.as_str() == uri)
+    }
+
+    /// A method to note the media held on a channel
+    ///
+    pub fn set_held(&mut self, channel: u32, uri: String) {
+        self.held_media.insert(channel, uri);
+    }
+
+    /// A method to remove the note of the media held on a channel (so the
+    /// media is loaded again)
+    ///
+    pub fn release(&mut self, channel: u32) {
+        self.held_media.remove(&channel);
+    }
+
+    /// A method to remove the note of the media held on any channel which is
+    /// no longer in the playlist of the primary. The channels are returned.
+    ///
+    pub fn release_stopped(&mut self) -> Vec<u32> {
+        let stopped: Vec<u32> = self
+            .held_media
+            .keys()
+            .filter(|channel| !self.playlist.contains_key(channel))
+            .cloned()
+            .collect();
+        for channel in stopped.iter() {
+            self.held_media.remove(channel);
+        }
+        stopped
+    }
+
+    /// A method to wait for the next check of the primary. If not on standby,
+    /// this method never returns.
+    ///
+    /// # Note
+    ///
+    /// This method is cancel safe.
+    ///
+    pub async fn next_update(&mut self) -> Option<InstanceUpdate> {
+        // If not on standby, wait forever
+        let Some(updates) = self.updates.as_mut() else {
+            return future::pending().await;
+        };
+
+        // Otherwise, wait for the next check (and stop if the checks end)
+        let update = updates.recv().await;
+        if update.is_none() {
+            self.updates = None;
+        }
+        update
+    }
+
+    /// A method to wait for another instance to take over from this instance.
+    /// If not watching for a new owner, this method never returns.
+    ///
+    /// # Note
+    ///
+    /// This method is cancel safe.
+    ///
+    pub async fn next_owner(&mut self) -> Option<String> {
+        // If not watching, wait forever
+        let Some(owner) = self.owner.as_mut() else {
+            return future::pending().await;
+        };
+
+        // Otherwise, wait for a new owner (and stop watching afterward)
+        let new_owner = owner.recv().await;
+        self.owner = None;
+        new_owner
+    }
+
+    // A helper method to record the newest heartbeat at the provided time
+    fn check_heartbeat(&mut self, heartbeat: Option<u64>, now: Instant) -> bool {
+        // If the heartbeat changed, note the time
+        if heartbeat != self.heartbeat {
+            self.heartbeat = heartbeat;
+            self.last_change = now;
+            return false;
+        }
+
+        // Otherwise, check if the heartbeat has stopped
+        self.heartbeat.is_some()
+            && now.saturating_duration_since(self.last_change)
+                > Duration::from_millis(FAILURE_TIMEOUT)
+    }
+
+    // A helper method to calculate the position of media on the primary at
+    // the provided time
+    fn position_at(&self, playback: &MediaPlayback, now: Instant) -> u64 {
+        // Start from the last recorded position
+        let mut position = playback.seek_to.as_millis() as u64;
+
+        // Add the time since the position was recorded, if playing
+        if playback.state == PlaybackState::Playing {
+            if let Some(heartbeat) = self.heartbeat
+                && playback.updated > 0
+            {
+                position += heartbeat.saturating_sub(playback.updated);
+            }
+            position += now.saturating_duration_since(self.last_change).as_millis() as u64;
+        }
+        position
+    }
+}
+
+// Tests of the standby module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test detecting a failed primary from the heartbeat
+    #[test]
+    fn update_heartbeat() {
+        // Create the monitor
+        let mut monitor = StandbyMonitor::new();
+        let start = Instant::now();
+        let timeout = Duration::from_millis(FAILURE_TIMEOUT + 1);
+
+        // A primary without a heartbeat is never failed
+        assert!(!monitor.check_heartbeat(None, start));
+        assert!(!monitor.check_heartbeat(None, start + timeout));
+
+        // A new heartbeat is never failed
+        assert!(!monitor.check_heartbeat(Some(1000), start));
+        assert!(!monitor.check_heartbeat(Some(1000), start + Duration::from_millis(100)));
+
+        // A changing heartbeat resets the timeout
+        assert!(!monitor.check_heartbeat(Some(2000), start + Duration::from_millis(2000)));
+        assert!(!monitor.check_heartbeat(Some(2000), start + timeout));
+
+        // A heartbeat which stopped changing is failed
+        assert!(monitor.check_heartbeat(Some(2000), start + Duration::from_millis(2000) + timeout));
+
+        // A heartbeat which was removed (a normal close) is not failed
+        assert!(!monitor.check_heartbeat(None, start + timeout * 2));
+        assert!(!monitor.check_heartbeat(None, start + timeout * 3));
+    }
+
+    // Test the position of media on the primary
+    #[test]
+    fn primary_position() {
+        // Create the monitor with a heartbeat
+        let mut monitor = StandbyMonitor::new();
+        let start = Instant::now();
+        monitor.check_heartbeat(Some(12_000), start);
+
+        // Create playing media recorded before the heartbeat
+        let mut playback = MediaPlayback {
+            media_cue: MediaCue {
+                channel: 1,
+                uri: "video.mp4".to_string(),
+                loop_media: None,
+                fade_in: None,
+                fade_out: None,
+                transition: None,
+                start_at: None,
+            },
+            seek_to: Duration::from_millis(1000),
+            state: PlaybackState::Playing,
+            updated: 10_000,
+            group: None,
+        };
+
+        // Playing media advances to the heartbeat and then with the clock
+        assert_eq!(3000, monitor.position_at(&playback, start));
+        assert_eq!(
+            3500,
+            monitor.position_at(&playback, start + Duration::from_millis(500))
+        );
+
+        // Paused media does not move
+        playback.state = PlaybackState::Paused;
+        assert_eq!(
+            1000,
+            monitor.position_at(&playback, start + Duration::from_millis(500))
+        );
+
+        // Only large differences move the held media
+        assert!(!StandbyMonitor::is_drifted(1000, 1000 + MAX_DRIFT));
+        assert!(StandbyMonitor::is_drifted(1000, 1001 + MAX_DRIFT));
+        assert!(StandbyMonitor::is_drifted(1001 + MAX_DRIFT, 0));
+    }
+
+    // Test releasing the media which the primary stopped
+    #[test]
+    fn release_stopped() {
+        // Hold media on two channels
+        let mut monitor = StandbyMonitor::new();
+        monitor.set_held(1, "video.mp4".to_string());
+        monitor.set_held(2, "audio.mp3".to_string());
+
+        // Keep only the first channel in the playlist
+        let mut playlist = MediaPlaylist::default();
+        playlist.insert(
+            1,
+            MediaPlayback {
+                media_cue: MediaCue {
+                    channel: 1,
+                    uri: "video.mp4".to_string(),
+                    loop_media: None,
+                    fade_in: None,
+                    fade_out: None,
+                    transition: None,
+                    start_at: None,
+                },
+                seek_to: Duration::from_secs(0),
+                state: PlaybackState::Playing,
+                updated: 0,
+                group: None,
+            },
+        );
+        monitor.set_playlist(playlist);
+
+        // Make sure only the second channel is released
+        assert_eq!(vec![2], monitor.release_stopped());
+        assert!(monitor.is_held(1, "video.mp4"));
+        assert!(!monitor.is_held(2, "audio.mp3"));
+    }
+}
@@ -0,0 +1,583 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to mix the video from every channel in an application window into
+//! a single video. The location, size, and stacking order of each channel are
+//! set on the compositor pad for that channel, so the window needs only one
+//! video surface. The idle background of each channel is mixed here as well,
+//! in place of the channel video.
+
+// Import crate definitions
+use crate::definitions::*;
+
+// Import other structures into this module
+use super::channel_mixer::{ChannelMixer, IdleLayer};
+use super::media_playback::FADE_INTERVAL;
+use super::network_clock::OutputClock;
+
+// Import standard library features
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Import Gstreamer Library
+use gst::prelude::*;
+use gstreamer as gst;
+
+// Import Tokio features
+use tokio::sync::oneshot;
+use tokio::time::interval;
+
+// Import FNV HashMap
+use fnv::FnvHashMap;
+
+// Import anyhow features
+use anyhow::{Context, Result};
+
+/// A helper structure to hold the elements which connect a channel to the
+/// compositor
+///
+#[derive(Debug)]
+struct CompositorInput {
+    source: gst::Element,  // the mixed video (or video source) for the channel
+    convert: gst::Element, // the converter between the source and the compositor
+    pad: gst::Pad,         // the compositor pad for the channel
+    idle_layer: IdleLayer, // the idle background shown in place of the channel
+    opacity: f64,          // the opacity of the channel (when visible)
+    is_visible: bool,      // a flag to indicate whether the channel is shown
+    fade_generation: Arc<AtomicU64>, // the counter to identify the most recent opacity change (and cancel older fades)
+}
+
+/// A structure to hold the output pipeline which mixes every channel in an
+/// application window
+///
+#[derive(Debug)]
+pub struct WindowCompositor {
+    pipeline: gst::Pipeline,   // the output pipeline for this window
+    compositor: gst::Element,  // the compositor which mixes the channels
+    caps_filter: gst::Element, // the filter which sets the size of the mixed video
+    sink: gst::Element,        // the video sink which displays the mixed video
+    background: IdleLayer,     // the window background, behind every channel
+    dimensions: (i32, i32),    // the size of the mixed video
+    inputs: FnvHashMap<u32, CompositorInput>, // the inputs for each channel in the window
+    next_layer: u32,           // the layer for the next channel
+    ready_receive: Option<oneshot::Receiver<()>>, // the confirmation that the video sink is embedded, until the output waits to start
+    _watch_guard: gst::bus::BusWatchGuard, // the guard for the watch function on the output bus
+}
+
+// Implement key functionality for the window compositor
+impl WindowCompositor {
+    /// A function to create a new window compositor. The output is not
+    /// started until start() is called.
+    ///
+    /// # Note
+    ///
+    /// The mixed video is the size of the window dimensions, and is scaled by
+    /// the video sink to fit the window.
+    ///
+    /// The output plays on the clock and base time shared by every output on
+    /// this instance (the same as every channel mixer).
+    ///
+    /// The confirmation is received once the user interface has embedded the
+    /// video sink in the window (so that the output can start without the sink
+    /// opening a window of its own).
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the window dimensions are not
+    /// specified.
+    ///
+    pub fn new(
+        window: &WindowDefinition,
+        video_output: VideoOutput,
+        output_clock: &OutputClock,
+        ready_receive: oneshot::Receiver<()>,
+    ) -> Result<WindowCompositor> {
+        // Make sure the size of the mixed video is specified
+        let dimensions = WindowCompositor::mixed_size(window)?;
+
+        // Create the output pipeline on the shared clock
+        let name = format!("apollo-window-{}", window.window_number);
+        let pipeline = gst::Pipeline::with_name(&name);
+        output_clock.apply(&pipeline);
+
+        // Create the compositor and the video output
+        let compositor = ChannelMixer::make_element("compositor")?;
+        compositor.set_property_from_str("background", "black");
+        let caps_filter = ChannelMixer::make_element("capsfilter")?;
+        let video_convert = ChannelMixer::make_element("videoconvert")?;
+        let sink = ChannelMixer::make_video_sink(video_output, &name)?;
+        pipeline
+            .add_many([&compositor, &caps_filter, &video_convert, &sink])
+            .context("Unable to create window compositor.")?;
+        gst::Element::link_many([&compositor, &caps_filter, &video_convert, &sink])
+            .context("Unable to create window compositor.")?;
+
+        // Create the background layer (always shown, behind every channel)
+        let background = IdleLayer::new(pipeline.upcast_ref(), &compositor, None, 0, true);
+
+        // Create the watch to report any errors
+        let _watch_guard = ChannelMixer::create_error_callback(
+            format!("window {} output", window.window_number),
+            &pipeline,
+        )?;
+
+        // Compose the compositor
+        let mut window_compositor = WindowCompositor {
+            pipeline,
+            compositor,
+            caps_filter,
+            sink,
+            background,
+            dimensions,
+            inputs: FnvHashMap::default(),
+            next_layer: 0,
+            ready_receive: Some(ready_receive),
+            _watch_guard,
+        };
+
+        // Set the size and background of the mixed video
+        window_compositor.update(window)?;
+
+        // Return the new compositor
+        Ok(window_compositor)
+    }
+
+    /// A method to update the size and background of the mixed video to
+    /// match the window definition
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the window dimensions are not
+    /// specified.
+    ///
+    pub fn update(&mut self, window: &WindowDefinition) -> Result<()> {
+        // Save the dimensions and resize the mixed video
+        self.dimensions = WindowCompositor::mixed_size(window)?;
+        self.resize();
+
+        // Replace the background (defaulting to black)
+        self.background
+            .set_background(Some(window.background.clone().unwrap_or(
+                Background::Color {
+                    red: 0,
+                    green: 0,
+                    blue: 0,
+                    alpha: u8::MAX,
+                },
+            )))
+    }
+
+    /// A method to return the video sink for the mixed video
+    ///
+    pub fn video_sink(&self) -> gst::Element {
+        self.sink.clone()
+    }
+
+    /// A method to take the confirmation that the video sink is embedded in
+    /// the window. The confirmation is only returned once.
+    ///
+    pub fn take_ready(&mut self) -> Option<oneshot::Receiver<()>> {
+        self.ready_receive.take()
+    }
+
+    /// A method to start the output of this compositor
+    ///
+    pub fn start(&self) -> Result<()> {
+        self.pipeline
+            .set_state(gst::State::Playing)
+            .context("Unable to start window compositor.")?;
+        Ok(())
+    }
+
+    /// A method to connect the mixed video of a channel to the compositor,
+    /// above every other channel in the window. If the channel is already
+    /// connected, the new video replaces the old video in the same layer.
+    ///
+    /// The source is either the bin which mixes the video of the channel or
+    /// the source which receives the video from the only deck of the channel
+    /// (see source_for()).
+    ///
+    /// The method returns the idle layer for the channel, which starts with
+    /// the idle background shown.
+    ///
+    pub fn add_channel(
+        &mut self,
+        channel: u32,
+        source: gst::Element,
+        video_frame: &VideoFrameWithWindow,
+        idle_background: Option<Background>,
+    ) -> Result<IdleLayer> {
+        // Add the source for the channel to the window pipeline
+        let convert = ChannelMixer::make_element("videoconvert")?;
+        self.pipeline
+            .add_many([&source, &convert])
+            .context("Unable to connect channel to window compositor.")?;
+        source
+            .link(&convert)
+            .context("Unable to connect channel to window compositor.")?;
+
+        // Connect the source to the compositor
+        let pad = ChannelMixer::link_to_mixer(&convert, &self.compositor)?;
+
+        // Start the source
+        for element in [&source, &convert] {
+            element
+                .sync_state_with_parent()
+                .context("Unable to connect channel to window compositor.")?;
+        }
+
+        // Replace the old video in the same layer (with the same opacity), if any
+        let (layer, opacity, is_visible) = match self.inputs.get(&channel) {
+            Some(input) => (
+                WindowCompositor::layer(&input.pad),
+                input.opacity,
+                input.is_visible,
+            ),
+
+            // Otherwise, place the channel above the other channels
+            None => {
+                self.next_layer += 1;
+                (self.next_layer - 1, 1.0, true)
+            }
+        };
+        self.remove_channel(channel);
+
+        // Create the idle layer just above the channel video
+        let idle_layer = IdleLayer::new(
+            self.pipeline.upcast_ref(),
+            &self.compositor,
+            Some(pad.clone()),
+            0,
+            true,
+        );
+        WindowCompositor::set_layer(&pad, &idle_layer, layer);
+        idle_layer.set_level(if is_visible { opacity } else { 0.0 });
+        idle_layer.place(
+            video_frame.left,
+            video_frame.top,
+            video_frame.width,
+            video_frame.height,
+        );
+
+        // Save the input and place the channel in the window
+        self.inputs.insert(
+            channel,
+            CompositorInput {
+                source,
+                convert,
+                pad,
+                idle_layer: idle_layer.clone(),
+                opacity,
+                is_visible,
+                fade_generation: Arc::new(AtomicU64::new(0)),
+            },
+        );
+        self.place_channel(channel, video_frame);
+
+        // Add the idle background, if specified
+        if let Err(error) = idle_layer.set_background(idle_background) {
+            self.remove_channel(channel);
+            return Err(error);
+        }
+        Ok(idle_layer)
+    }
+
+    /// A method to disconnect a channel from the compositor
+    ///
+    pub fn remove_channel(&mut self, channel: u32) {
+        // If the channel is connected, remove the input
+        if let Some(input) = self.inputs.remove(&channel) {
+            // Cancel any fade and remove the idle background
+            input.fade_generation.fetch_add(1, Ordering::SeqCst);
+            input.idle_layer.set_background(None).unwrap_or(());
+
+            if let Some(src_pad) = input.convert.static_pad("src") {
+                src_pad.unlink(&input.pad).unwrap_or(());
+            }
+            for element in [&input.source, &input.convert] {
+                element
+                    .set_state(gst::State::Null)
+                    .unwrap_or(gst::StateChangeSuccess::Success);
+                self.pipeline.remove(element).unwrap_or(());
+            }
+            self.compositor.release_request_pad(&input.pad);
+        }
+    }
+
+    /// A method to change the location and size of a channel in the window
+    ///
+    pub fn place_channel(&self, channel: u32, video_frame: &VideoFrameWithWindow) {
+        // If the channel is connected, update the compositor pad and idle background
+        if let Some(input) = self.inputs.get(&channel) {
+            input.pad.set_property("xpos", video_frame.left);
+            input.pad.set_property("ypos", video_frame.top);
+            input.pad.set_property("width", video_frame.width);
+            input.pad.set_property("height", video_frame.height);
+            input.idle_layer.place(
+                video_frame.left,
+                video_frame.top,
+                video_frame.width,
+                video_frame.height,
+            );
+        }
+    }
+
+    /// A method to change the stacking order of a channel in the window
+    ///
+    pub fn change_layer(&mut self, channel: u32, layer: &LayerChange) {
+        // Sort the channels from the bottom to the top
+        let mut order: Vec<u32> = self.inputs.keys().copied().collect();
+        order.sort_by_key(|channel| WindowCompositor::layer(&self.inputs[channel].pad));
+
+        // Move the channel to the new position
+        let Some(current) = order.iter().position(|other| *other == channel) else {
+            return;
+        };
+        let position = layer.position(current, order.len());
+        order.remove(current);
+        order.insert(position, channel);
+
+        // Renumber every channel (above the background)
+        for (index, channel) in order.iter().enumerate() {
+            let input = &self.inputs[channel];
+            WindowCompositor::set_layer(&input.pad, &input.idle_layer, index as u32);
+        }
+        self.next_layer = order.len() as u32;
+    }
+
+    /// A method to change the opacity of a channel, with an optional fade.
+    /// Any fade already in progress on the channel is cancelled.
+    ///
+    pub fn change_opacity(&mut self, channel: u32, opacity: f64, fade: Option<AudioFade>) {
+        // Make sure the channel is connected
+        let Some(input) = self.inputs.get_mut(&channel) else {
+            return;
+        };
+
+        // Save the new opacity and claim a new fade generation
+        input.opacity = opacity;
+        let generation = input.fade_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        // If the channel is hidden, wait to apply the opacity until it is shown
+        if !input.is_visible {
+            return;
+        }
+
+        // If no fade was specified, change the opacity immediately
+        let Some(fade) = fade else {
+            input.idle_layer.set_level(opacity);
+            return;
+        };
+
+        // Note the starting opacity and copy the idle layer (which applies
+        // the opacity to the video or the idle background)
+        let start = input.idle_layer.level();
+        let idle_layer = input.idle_layer.clone();
+        let fade_generation = input.fade_generation.clone();
+
+        // Spawn the fade in the background
+        tokio::spawn(async move {
+            // Calculate the number of steps in the fade
+            let steps = (fade.duration / FADE_INTERVAL).max(1);
+            let mut timer = interval(Duration::from_millis(FADE_INTERVAL));
+            timer.tick().await; // the first tick completes immediately
+
+            // Step through the fade
+            for step in 1..=steps {
+                // Wait for the next step
+                timer.tick().await;
+
+                // Stop if a newer change has started (or the channel was removed)
+                if fade_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                // Set the new opacity
+                let progress = step as f64 / steps as f64;
+                idle_layer.set_level(fade.curve.level(start, opacity, progress));
+            }
+        });
+    }
+
+    /// A method to hide or show a channel (cancelling any fade in progress)
+    ///
+    pub fn change_visibility(&mut self, channel: u32, is_visible: bool) {
+        if let Some(input) = self.inputs.get_mut(&channel) {
+            input.is_visible = is_visible;
+            input.fade_generation.fetch_add(1, Ordering::SeqCst);
+            input
+                .idle_layer
+                .set_level(if is_visible { input.opacity } else { 0.0 });
+        }
+    }
+
+    /// A method to check if a channel is connected to the compositor
+    ///
+    pub fn has_channel(&self, channel: u32) -> bool {
+        self.inputs.contains_key(&channel)
+    }
+
+    /// A function to create the source which receives the video from the
+    /// only deck of a channel (for channels without a mixer). The name should
+    /// be the unique name of the channel outputs.
+    ///
+    pub fn source_for(name: &str) -> Result<gst::Element> {
+        // Create the video source (hold the last frame if the channel pauses)
+        let source = ChannelMixer::make_element("intervideosrc")?;
+        source.set_property("channel", ChannelMixer::output_name(name));
+        source.set_property("timeout", u64::MAX);
+        Ok(source)
+    }
+
+    // A helper function to find the size of the mixed video from the window
+    // definition
+    fn mixed_size(window: &WindowDefinition) -> Result<(i32, i32)> {
+        let (width, height) = window
+            .dimensions
+            .context("Unable to mix window: Window dimensions not specified.")?;
+        Ok((width.max(1), height.max(1)))
+    }
+
+    // A helper method to set the size of the mixed video (and the background)
+    fn resize(&self) {
+        // Set the size of the mixed video
+        let (width, height) = self.dimensions;
+        let caps = gst::Caps::builder("video/x-raw")
+            .field("width", width)
+            .field("height", height)
+            .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+            .build();
+        self.caps_filter.set_property("caps", &caps);
+
+        // Scale the background to the new size
+        self.background.place(0, 0, width, height);
+    }
+
+    // A helper function to find the layer of a channel from the stacking
+    // order of the compositor pad
+    fn layer(pad: &gst::Pad) -> u32 {
+        pad.property::<u32>("zorder").saturating_sub(1) / 2
+    }
+
+    // A helper function to move a channel and its idle background to a layer
+    // (each layer holds the channel video with the idle background just
+    // above it, and every layer is above the window background)
+    fn set_layer(pad: &gst::Pad, idle_layer: &IdleLayer, layer: u32) {
+        pad.set_property("zorder", layer * 2 + 1);
+        idle_layer.set_zorder(layer * 2 + 2);
+    }
+}
+
+// Implement the drop trait for WindowCompositor
+impl Drop for WindowCompositor {
+    /// This method sets the output pipeline to NULL
+    ///
+    fn drop(&mut self) {
+        self.pipeline
+            .set_state(gst::State::Null)
+            .unwrap_or(gst::StateChangeSuccess::Success);
+    }
+}
+
+// Tests of the window compositor module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the stacking order of the channels in a headless compositor
+    #[test]
+    fn change_layer() {
+        // Initialize gstreamer
+        gst::init().expect("Unable to initialize gstreamer.");
+
+        // A window without dimensions cannot be mixed
+        let mut window = WindowDefinition {
+            window_number: 1,
+            fullscreen: false,
+            dimensions: None,
+            background: None,
+            monitor: None,
+            position: None,
+            compositor: true,
+        };
+        let output_clock = OutputClock::new(gst::SystemClock::obtain());
+        let (_ready, ready_receive) = VideoReady::new();
+        assert!(
+            WindowCompositor::new(&window, VideoOutput::Headless, &output_clock, ready_receive)
+                .is_err()
+        );
+
+        // Create a headless compositor with three channels
+        window.dimensions = Some((640, 480));
+        let (_ready, ready_receive) = VideoReady::new();
+        let mut compositor =
+            WindowCompositor::new(&window, VideoOutput::Headless, &output_clock, ready_receive)
+                .expect("Unable to create compositor.");
+        let video_frame = VideoFrameWithWindow {
+            window_number: 1,
+            top: 0,
+            left: 0,
+            height: 240,
+            width: 320,
+        };
+        let add_channel = |compositor: &mut WindowCompositor, channel: u32| {
+            let source = ChannelMixer::make_element("videotestsrc").expect("Invalid source.");
+            compositor
+                .add_channel(channel, source, &video_frame, None)
+                .expect("Unable to add channel.");
+        };
+        for channel in 1..=3 {
+            add_channel(&mut compositor, channel);
+        }
+
+        // Find the channels from the bottom to the top (above the background)
+        let order = |compositor: &WindowCompositor| {
+            let mut channels: Vec<u32> = compositor.inputs.keys().copied().collect();
+            channels.sort_by_key(|channel| {
+                let zorder = compositor.inputs[channel].pad.property::<u32>("zorder");
+                assert!(zorder % 2 == 1);
+                zorder
+            });
+            channels
+        };
+        assert_eq!(order(&compositor), vec![1, 2, 3]);
+
+        // Move the channels up, down, and to a specific layer
+        compositor.change_layer(1, &LayerChange::Raise);
+        assert_eq!(order(&compositor), vec![2, 1, 3]);
+        compositor.change_layer(3, &LayerChange::Index(0));
+        assert_eq!(order(&compositor), vec![3, 2, 1]);
+        compositor.change_layer(3, &LayerChange::Lower);
+        assert_eq!(order(&compositor), vec![3, 2, 1]);
+        compositor.change_layer(2, &LayerChange::Index(10));
+        assert_eq!(order(&compositor), vec![3, 1, 2]);
+
+        // A replaced channel keeps its layer
+        add_channel(&mut compositor, 1);
+        assert_eq!(order(&compositor), vec![3, 1, 2]);
+
+        // A new channel is added above every other channel
+        compositor.remove_channel(3);
+        add_channel(&mut compositor, 4);
+        assert_eq!(order(&compositor), vec![1, 2, 4]);
+
+        // Changing a missing channel has no effect
+        compositor.change_layer(5, &LayerChange::Index(0));
+        assert_eq!(order(&compositor), vec![1, 2, 4]);
+    }
+}
@@ -22,15 +22,49 @@
 use crate::definitions::*;
 
 //  Import standard library features
-use std::sync::{Arc, Mutex};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
 
 // Import Tokio and warp features
-use tokio::sync::oneshot;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, oneshot};
+use warp::hyper::body::Incoming;
+use warp::hyper::service::Service;
+use warp::ws::{Message, WebSocket};
 use warp::{Filter, http};
 
+// Import TLS and hyper features
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+// Import futures features
+use futures_util::future::{BoxFuture, join_all};
+use futures_util::{FutureExt, SinkExt, StreamExt};
+
+// Import tracing features
+use tracing::{error, warn};
+
 // Import serde feaures
 use serde::de::DeserializeOwned;
 
+// Import FNV HashMap
+use fnv::FnvHashMap;
+
+// Import anyhow features
+use anyhow::{Context, Result, anyhow};
+
+// Embed the control panel in the program
+const PANEL_PAGE: &str = include_str!("panel/index.html");
+const PANEL_SCRIPT: &str = include_str!("panel/panel.js");
+const PANEL_STYLE: &str = include_str!("panel/panel.css");
+
 // Define conversions from data types into a Request
 impl From<WindowDefinition> for Request {
     fn from(window: WindowDefinition) -> Self {
@@ -52,6 +86,11 @@ impl From<ChannelState> for Request {
         Request::ChangeState { channel_state }
     }
 }
+impl From<ChannelVolume> for Request {
+    fn from(channel_volume: ChannelVolume) -> Self {
+        Request::ChangeVolume { channel_volume }
+    }
+}
 impl From<ChannelAllocation> for Request {
     fn from(channel_allocation: ChannelAllocation) -> Self {
         Request::ResizeChannel { channel_allocation }
@@ -64,6 +103,26 @@ impl From<ChannelRealignment> for Request {
         }
     }
 }
+impl From<ChannelLayer> for Request {
+    fn from(channel_layer: ChannelLayer) -> Self {
+        Request::ChangeLayer { channel_layer }
+    }
+}
+impl From<ChannelOpacity> for Request {
+    fn from(channel_opacity: ChannelOpacity) -> Self {
+        Request::ChangeOpacity { channel_opacity }
+    }
+}
+impl From<ChannelVisibility> for Request {
+    fn from(channel_visibility: ChannelVisibility) -> Self {
+        Request::ChangeVisibility { channel_visibility }
+    }
+}
+impl From<BackgroundChange> for Request {
+    fn from(background_change: BackgroundChange) -> Self {
+        Request::ChangeBackground { background_change }
+    }
+}
 impl From<ChannelSeek> for Request {
     fn from(channel_seek: ChannelSeek) -> Self {
         Request::Seek { channel_seek }
@@ -74,46 +133,85 @@ impl From<ChannelSeek> for Request {
 /// to the interface.
 ///
 pub struct WebInterface {
-    web_send: WebSend,                // send line to the system interface
-    user_address: Arc<Mutex<String>>, // user-defined address
+    web_send: WebSend,      // send line to the system interface
+    event_send: EventSend,  // source of playback events for listeners
+    address: String,        // user-defined address
+    listen: ListenSettings, // user-defined additional addresses
+    api_keys: Arc<ApiKeys>, // user-defined keys for access to the interface
 }
 
+/// An enum to describe a request which was refused for lack of access
+///
+#[derive(Debug)]
+enum AccessRejection {
+    /// A variant for a request without a valid key
+    Unauthorized,
+
+    /// A variant for a request with a key that lacks the required role
+    Forbidden,
+}
+
+// Allow the access rejection to be returned as a warp rejection
+impl warp::reject::Reject for AccessRejection {}
+
+/// A structure to describe a request with a body that could not be read
+///
+#[derive(Debug)]
+struct InvalidBody(String);
+
+// Allow the invalid body to be returned as a warp rejection
+impl warp::reject::Reject for InvalidBody {}
+
 // Implement key Web Interface functionality
 impl WebInterface {
     /// A function to create a new web interface. The send channel should
     /// connect directly to the system interface.
     ///
-    pub fn new(web_send: WebSend, user_address: Arc<Mutex<String>>) -> Self {
+    pub fn new(web_send: WebSend, event_send: EventSend, settings: &UserSettings) -> Self {
         // Return the new web interface and runtime handle
         WebInterface {
             web_send,
-            user_address,
+            event_send,
+            address: settings.address.clone(),
+            listen: settings.listen.clone(),
+            api_keys: Arc::new(settings.api_keys.clone()),
         }
     }
 
     /// A method to listen for connections from the internet
     ///
     pub async fn run(&mut self) {
+        // Create the filters which require a key for observers and for operators
+        // (each checked once the route matches, so unknown routes are not found)
+        let observer = WebInterface::with_role(self.api_keys.clone(), Role::Observer);
+        let operator = WebInterface::with_role(self.api_keys.clone(), Role::Operator);
+
         // Create the align channel filter
         let align_channel = warp::post()
             .and(warp::path("alignChannel"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<ChannelRealignment>())
             .and_then(WebInterface::handle_request);
 
-        // Create the all stop filter
+        // Create the all stop filter (with an optional fade out)
         let all_stop = warp::post()
             .and(warp::path("allStop"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
-            .and(WebInterface::with_clone(Request::AllStop))
+            .and(
+                WebInterface::with_optional_json::<AudioFade>()
+                    .map(|fade_out| Request::AllStop { fade_out }),
+            )
             .and_then(WebInterface::handle_request);
 
         // Create the define window filter
         let define_window = warp::post()
             .and(warp::path("defineWindow"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<WindowDefinition>())
             .and_then(WebInterface::handle_request);
@@ -122,76 +220,553 @@ impl WebInterface {
         let define_channel = warp::post()
             .and(warp::path("defineChannel"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<MediaChannel>())
             .and_then(WebInterface::handle_request);
 
+        // Create the remove window filter
+        let remove_window = warp::post()
+            .and(warp::path("removeWindow"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                warp::path::param::<u32>()
+                    .map(|window_number| Request::RemoveWindow { window_number }),
+            )
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the redefine window filter
+        let redefine_window = warp::post()
+            .and(warp::path("redefineWindow"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<WindowDefinition>()
+                    .map(|window| Request::RedefineWindow { window }),
+            )
+            .and_then(WebInterface::handle_request);
+
+        // Create the remove channel filter
+        let remove_channel = warp::post()
+            .and(warp::path("removeChannel"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(warp::path::param::<u32>().map(|channel| Request::RemoveChannel { channel }))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the redefine channel filter
+        let redefine_channel = warp::post()
+            .and(warp::path("redefineChannel"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<MediaChannel>()
+                    .map(|media_channel| Request::RedefineChannel { media_channel }),
+            )
+            .and_then(WebInterface::handle_request);
+
         // Create the cue media filter
         let cue_media = warp::post()
             .and(warp::path("cueMedia"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<MediaCue>())
             .and_then(WebInterface::handle_request);
 
+        // Create the cue group filter
+        let cue_group = warp::post()
+            .and(warp::path("cueGroup"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<CueGroup>()
+                    .map(|cue_group| Request::CueGroup { cue_group }),
+            )
+            .and_then(WebInterface::handle_request);
+
+        // Create the preload media filter
+        let preload_media = warp::post()
+            .and(warp::path("preloadMedia"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<MediaCue>()
+                    .map(|media_cue| Request::PreloadMedia { media_cue }),
+            )
+            .and_then(WebInterface::handle_request);
+
         // Create the change state filter
         let change_state = warp::post()
             .and(warp::path("changeState"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<ChannelState>())
             .and_then(WebInterface::handle_request);
 
+        // Create the change volume filter
+        let change_volume = warp::post()
+            .and(warp::path("changeVolume"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<ChannelVolume>())
+            .and_then(WebInterface::handle_request);
+
         // Create the resize channel filter
         let resize_channel = warp::post()
             .and(warp::path("resizeChannel"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<ChannelAllocation>())
             .and_then(WebInterface::handle_request);
 
+        // Create the layer channel filter
+        let layer_channel = warp::post()
+            .and(warp::path("layerChannel"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<ChannelLayer>())
+            .and_then(WebInterface::handle_request);
+
+        // Create the change opacity filter
+        let change_opacity = warp::post()
+            .and(warp::path("changeOpacity"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<ChannelOpacity>())
+            .and_then(WebInterface::handle_request);
+
+        // Create the change visibility filter
+        let change_visibility = warp::post()
+            .and(warp::path("changeVisibility"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<ChannelVisibility>())
+            .and_then(WebInterface::handle_request);
+
+        // Create the change background filter
+        let change_background = warp::post()
+            .and(warp::path("changeBackground"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<BackgroundChange>())
+            .and_then(WebInterface::handle_request);
+
         // Create the seek filter
         let seek = warp::post()
             .and(warp::path("seek"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_json::<ChannelSeek>())
             .and_then(WebInterface::handle_request);
 
+        // Create the load cue list filter
+        let load_cue_list = warp::post()
+            .and(warp::path("loadCueList"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<CueList>()
+                    .map(|cue_list| Request::LoadCueList { cue_list }),
+            )
+            .and_then(WebInterface::handle_request);
+
+        // Create the go filter
+        let go = warp::post()
+            .and(warp::path("go"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::Go))
+            .and_then(WebInterface::handle_request);
+
+        // Create the back filter
+        let back = warp::post()
+            .and(warp::path("back"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::Back))
+            .and_then(WebInterface::handle_request);
+
+        // Create the go to filter
+        let go_to = warp::post()
+            .and(warp::path("goTo"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(warp::path::param::<usize>().map(|cue| Request::GoTo { cue }))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the schedule request filter
+        let schedule_request = warp::post()
+            .and(warp::path("schedule"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(
+                WebInterface::with_json::<Schedule>()
+                    .map(|schedule| Request::ScheduleRequest { schedule }),
+            )
+            .and_then(WebInterface::handle_request);
+
+        // Create the cancel scheduled filter
+        let cancel_scheduled = warp::post()
+            .and(warp::path("cancelScheduled"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(warp::path::param::<u64>().map(|id| Request::CancelScheduled { id }))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the recall preset filter
+        let recall_preset = warp::post()
+            .and(warp::path("recallPreset"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(warp::path::param::<String>().map(|name| Request::RecallPreset { name }))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the save show filter (with the file location as a JSON string)
+        let save_show = warp::post()
+            .and(warp::path("saveShow"))
+            .and(warp::path::end())
+            .and(operator.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_json::<String>().map(|path| Request::SaveShow { path }))
+            .and_then(WebInterface::handle_request);
+
         // Create the close filter
         let close = warp::post()
             .and(warp::path("close"))
             .and(warp::path::end())
+            .and(operator.clone())
             .and(WebInterface::with_clone(self.web_send.clone()))
             .and(WebInterface::with_clone(Request::Close))
             .and_then(WebInterface::handle_request);
 
-        // Combine the filters
-        let routes = all_stop
+        // Create the status filter
+        let status = warp::get()
+            .and(warp::path("status"))
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::GetStatus))
+            .and_then(WebInterface::handle_request);
+
+        // Create the monitors filter
+        let monitors = warp::get()
+            .and(warp::path("monitors"))
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::GetMonitors))
+            .and_then(WebInterface::handle_request);
+
+        // Create the channel status filter
+        let channel_status = warp::get()
+            .and(warp::path("channel"))
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(warp::path::param::<u32>().map(|channel| Request::GetChannelStatus { channel }))
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and_then(WebInterface::handle_request);
+
+        // Create the cue list status filter
+        let cue_list = warp::get()
+            .and(warp::path("cueList"))
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::GetCueList))
+            .and_then(WebInterface::handle_request);
+
+        // Create the list scheduled filter
+        let list_scheduled = warp::get()
+            .and(warp::path("schedule"))
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and(WebInterface::with_clone(self.web_send.clone()))
+            .and(WebInterface::with_clone(Request::ListScheduled))
+            .and_then(WebInterface::handle_request);
+
+        // Create the playback event stream filter
+        let events = warp::path("events")
+            .and(warp::path::end())
+            .and(observer.clone())
+            .and(warp::ws())
+            .and(WebInterface::with_clone(self.event_send.clone()))
+            .map(|ws: warp::ws::Ws, event_send: EventSend| {
+                ws.on_upgrade(move |websocket| WebInterface::handle_events(websocket, event_send))
+            });
+
+        // Create the control panel filters (the panel contains no private information)
+        let panel_page = warp::get()
+            .and(warp::path::end())
+            .map(|| warp::reply::html(PANEL_PAGE));
+        let panel_script = warp::get()
+            .and(warp::path("panel.js"))
+            .and(warp::path::end())
+            .map(|| {
+                warp::reply::with_header(
+                    PANEL_SCRIPT,
+                    "content-type",
+                    "text/javascript; charset=utf-8",
+                )
+            });
+        let panel_style = warp::get()
+            .and(warp::path("panel.css"))
+            .and(warp::path::end())
+            .map(|| {
+                warp::reply::with_header(PANEL_STYLE, "content-type", "text/css; charset=utf-8")
+            });
+
+        // Combine the filters which only read the status (for observers)
+        let observer_routes = status
+            .or(monitors)
+            .or(channel_status)
+            .or(cue_list)
+            .or(list_scheduled)
+            .or(events);
+
+        // Combine the filters which change the system (for operators)
+        let operator_routes = all_stop
             .or(align_channel)
             .or(define_window)
             .or(define_channel)
+            .or(remove_window)
+            .or(redefine_window)
+            .or(remove_channel)
+            .or(redefine_channel)
             .or(cue_media)
+            .or(cue_group)
+            .or(preload_media)
             .or(change_state)
+            .or(change_volume)
             .or(resize_channel)
+            .or(layer_channel)
+            .or(change_opacity)
+            .or(change_visibility)
+            .or(change_background)
             .or(seek)
+            .or(load_cue_list)
+            .or(go)
+            .or(back)
+            .or(go_to)
+            .or(schedule_request)
+            .or(cancel_scheduled)
+            .or(recall_preset)
+            .or(save_show)
             .or(close);
 
-        // Try to extract the user defined address
-        let mut address = DEFAULT_ADDRESS.to_string();
-        if let Ok(lock) = self.user_address.try_lock() {
-            // Copy the address
-            address = lock.clone();
+        // Combine all the filters (and reply to any refused requests)
+        let routes = panel_page
+            .or(panel_script)
+            .or(panel_style)
+            .or(observer_routes)
+            .or(operator_routes)
+            .recover(WebInterface::handle_rejection);
+
+        // Listen for plaintext connections on the main address (unless
+        // encrypted only) and any others
+        let mut plaintext_addresses = self.listen.addresses.clone();
+        if !self.listen.is_encrypted_only {
+            plaintext_addresses.insert(0, self.address.clone());
         }
 
-        // Handle incoming requests on the media port
-        warp::serve(routes)
-            .run(
-                address
-                    .parse::<std::net::SocketAddr>()
-                    .expect("Unable to listen at specified address."),
-            )
-            .await;
+        // Check every address and the certificate before listening (and refuse to start on any error)
+        let (plaintext_addresses, tls_addresses, tls_acceptor) =
+            match WebInterface::check_listen(&plaintext_addresses, &self.listen) {
+                Ok(checked) => checked,
+                Err(error) => {
+                    error!("Unable to start the web interface: {}", error);
+                    return;
+                }
+            };
+
+        // Listen for plaintext connections
+        let mut servers: Vec<BoxFuture<'static, ()>> = Vec::new();
+        for address in plaintext_addresses {
+            servers.push(warp::serve(routes.clone()).run(address).boxed());
+        }
+
+        // Listen for encrypted connections, if specified
+        if let Some(tls_acceptor) = tls_acceptor {
+            for address in tls_addresses {
+                servers.push(
+                    WebInterface::serve_tls(
+                        address,
+                        tls_acceptor.clone(),
+                        TowerToHyperService::new(warp::service(routes.clone())),
+                    )
+                    .boxed(),
+                );
+            }
+        }
+
+        // Handle incoming requests on every address
+        join_all(servers).await;
+    }
+
+    /// A helper function to parse every listening address and to load the
+    /// TLS certificate and key, if there are any encrypted addresses. Returns
+    /// the plaintext addresses, the encrypted addresses, and the acceptor for
+    /// encrypted connections.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if any address is invalid, if there
+    /// are no addresses at all, or if the certificate or key is missing,
+    /// can't be read, or is not valid.
+    ///
+    fn check_listen(
+        plaintext_addresses: &[String],
+        listen: &ListenSettings,
+    ) -> Result<(Vec<SocketAddr>, Vec<SocketAddr>, Option<TlsAcceptor>)> {
+        // Parse every address
+        let plaintext = plaintext_addresses
+            .iter()
+            .map(|address| WebInterface::parse_address(address))
+            .collect::<Result<Vec<SocketAddr>>>()?;
+        let encrypted = listen
+            .tls_addresses
+            .iter()
+            .map(|address| WebInterface::parse_address(address))
+            .collect::<Result<Vec<SocketAddr>>>()?;
+
+        // Make sure there is somewhere to listen
+        if plaintext.is_empty() && encrypted.is_empty() {
+            return Err(anyhow!("No listening address specified."));
+        }
+
+        // Stop here if there are no encrypted addresses
+        if encrypted.is_empty() {
+            return Ok((plaintext, encrypted, None));
+        }
+
+        // Load the certificate and key
+        let (Some(tls_cert), Some(tls_key)) = (listen.tls_cert.as_ref(), listen.tls_key.as_ref())
+        else {
+            return Err(anyhow!(
+                "Encrypted addresses require a certificate and key."
+            ));
+        };
+        let acceptor = WebInterface::load_tls(tls_cert, tls_key)?;
+
+        // Return the checked settings
+        Ok((plaintext, encrypted, Some(acceptor)))
+    }
+
+    // A function to load the TLS certificate and private key (both in PEM
+    // format) and create the acceptor for encrypted connections
+    fn load_tls(tls_cert: &str, tls_key: &str) -> Result<TlsAcceptor> {
+        // Load the certificate chain and the private key
+        let certs = CertificateDer::pem_file_iter(tls_cert)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("Unable to load certificate {}.", tls_cert))?;
+        if certs.is_empty() {
+            return Err(anyhow!(
+                "Unable to load {}: No certificate found.",
+                tls_cert
+            ));
+        }
+        let key = PrivateKeyDer::from_pem_file(tls_key)
+            .with_context(|| format!("Unable to load private key {}.", tls_key))?;
+
+        // Create the server configuration (the key must match the certificate)
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("Invalid TLS certificate or private key.")?;
+
+        // Return the acceptor
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+
+    /// A function to listen for encrypted connections at the address and
+    /// serve each one with the provided service
+    ///
+    async fn serve_tls<S>(address: SocketAddr, tls_acceptor: TlsAcceptor, service: S)
+    where
+        S: Service<http::Request<Incoming>, Response = warp::reply::Response, Error = Infallible>
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        // Try to listen at the address
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                error!(
+                    "Unable to listen for encrypted connections at {}: {}.",
+                    address, error
+                );
+                return;
+            }
+        };
+
+        // Accept connections indefinitely
+        WebInterface::accept_tls(listener, tls_acceptor, service).await;
+    }
+
+    // A function to accept encrypted connections on the listener, complete
+    // the handshake, and serve each connection in the background
+    async fn accept_tls<S>(listener: TcpListener, tls_acceptor: TlsAcceptor, service: S)
+    where
+        S: Service<http::Request<Incoming>, Response = warp::reply::Response, Error = Infallible>
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        loop {
+            // Wait for the next connection
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(error) => {
+                    warn!("Unable to accept encrypted connection: {}.", error);
+                    continue;
+                }
+            };
+
+            // Complete the handshake and serve the connection (with websocket upgrades)
+            let tls_acceptor = tls_acceptor.clone();
+            let service = service.clone();
+            tokio::spawn(async move {
+                let stream = match tls_acceptor.accept(stream).await {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!("Unable to complete TLS handshake: {}.", error);
+                        return;
+                    }
+                };
+                if let Err(error) = auto::Builder::new(TokioExecutor::new())
+                    .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                    .await
+                {
+                    warn!("Unable to serve encrypted connection: {}.", error);
+                }
+            });
+        }
     }
 
     /// A function to handle define channel requests
@@ -233,6 +808,138 @@ impl WebInterface {
         }
     }
 
+    /// A function to reply to requests which were refused for lack of
+    /// access or with a body that could not be read. All other rejections
+    /// are passed along unchanged.
+    ///
+    async fn handle_rejection(
+        rejection: warp::Rejection,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
+        // Reply with the matching status, if the request lacked access
+        match rejection.find::<AccessRejection>() {
+            Some(AccessRejection::Unauthorized) => Ok(warp::reply::with_status(
+                warp::reply::json(&WebReply::failure("A valid API key is required.")),
+                http::StatusCode::UNAUTHORIZED,
+            )),
+            Some(AccessRejection::Forbidden) => Ok(warp::reply::with_status(
+                warp::reply::json(&WebReply::failure(
+                    "The API key does not permit this request.",
+                )),
+                http::StatusCode::FORBIDDEN,
+            )),
+            None => match rejection.find::<InvalidBody>() {
+                Some(InvalidBody(message)) => Ok(warp::reply::with_status(
+                    warp::reply::json(&WebReply::failure(message.clone())),
+                    http::StatusCode::BAD_REQUEST,
+                )),
+                None => Err(rejection),
+            },
+        }
+    }
+
+    /// A function to forward playback events to a websocket listener until
+    /// the listener disconnects
+    ///
+    async fn handle_events(websocket: WebSocket, event_send: EventSend) {
+        // Subscribe to the playback events
+        let mut event_receive = event_send.subscribe();
+
+        // Split the websocket into the sending and receiving halves
+        let (mut websocket_send, mut websocket_receive) = websocket.split();
+
+        // Loop until the listener disconnects
+        loop {
+            tokio::select! {
+                // Forward any new playback events
+                result = event_receive.recv() => {
+                    match result {
+                        // Send the event to the listener
+                        Ok(event) => {
+                            // Try to serialize the event
+                            let event_string = match serde_json::to_string(&event) {
+                                Ok(string) => string,
+                                Err(error) => {
+                                    warn!("Unable to parse playback event: {}.", error);
+                                    continue;
+                                }
+                            };
+
+                            // Stop if the listener is no longer available
+                            if websocket_send.send(Message::text(event_string)).await.is_err() {
+                                break;
+                            }
+                        }
+
+                        // Warn if the listener is not keeping up
+                        Err(broadcast::error::RecvError::Lagged(count)) => {
+                            warn!("Event listener missed {} playback events.", count);
+                        }
+
+                        // Stop if the event line closed
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+
+                // Watch for the listener to disconnect (incoming messages are ignored)
+                message = websocket_receive.next() => {
+                    match message {
+                        Some(Ok(message)) if !message.is_close() => (),
+                        _ => break,
+                    }
+                }
+            }
+        }
+    }
+
+    // A function to require a key with at least the provided role. The key
+    // may be provided as a bearer token, in the X-Api-Key header, or in the
+    // key query parameter (for browsers, which can't add headers to a
+    // websocket).
+    fn with_role(
+        api_keys: Arc<ApiKeys>,
+        role: Role,
+    ) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+        warp::header::optional::<String>("authorization")
+            .and(warp::header::optional::<String>("x-api-key"))
+            .and(
+                warp::query::<FnvHashMap<String, String>>()
+                    .or(WebInterface::with_clone(FnvHashMap::default()))
+                    .unify(),
+            )
+            .and_then(
+                move |authorization: Option<String>,
+                      api_key: Option<String>,
+                      mut query: FnvHashMap<String, String>| {
+                    let api_keys = api_keys.clone();
+                    async move {
+                        // Use the API key header, then the bearer token, then the query parameter
+                        let key = api_key
+                            .or_else(|| {
+                                authorization.and_then(|value| {
+                                    value.strip_prefix("Bearer ").map(|token| token.to_string())
+                                })
+                            })
+                            .or_else(|| query.remove("key"));
+
+                        // Check the role granted by the key
+                        match api_keys.role(key.as_deref()) {
+                            Some(granted) if granted >= role => Ok(()),
+                            Some(_) => Err(warp::reject::custom(AccessRejection::Forbidden)),
+                            None => Err(warp::reject::custom(AccessRejection::Unauthorized)),
+                        }
+                    }
+                },
+            )
+            .untuple_one()
+    }
+
+    // A function to parse a listening address
+    fn parse_address(address: &str) -> Result<SocketAddr> {
+        address
+            .parse::<SocketAddr>()
+            .with_context(|| format!("Invalid listening address: {}.", address))
+    }
+
     // A function to extract a helper type from the body of the message
     fn with_json<T>() -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone
     where
@@ -242,6 +949,39 @@ impl WebInterface {
         warp::body::content_length_limit(1024 * 16).and(warp::body::json())
     }
 
+    // A function to extract an optional helper type from the body of the
+    // message. An empty body is accepted, but any other body which is not
+    // valid JSON for the type is refused.
+    fn with_optional_json<T>()
+    -> impl Filter<Extract = (Option<T>,), Error = warp::Rejection> + Clone
+    where
+        T: Send + DeserializeOwned,
+    {
+        warp::body::content_length_limit(1024 * 16)
+            .and(warp::body::aggregate())
+            .and_then(WebInterface::read_optional_json::<T, _>)
+    }
+
+    // A function to read an optional helper type from the body of the message
+    async fn read_optional_json<T, B>(mut body: B) -> Result<Option<T>, warp::Rejection>
+    where
+        T: DeserializeOwned,
+        B: warp::Buf,
+    {
+        // Copy the body and accept an empty body
+        let bytes = body.copy_to_bytes(body.remaining());
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+
+        // Otherwise, try to read the body
+        serde_json::from_slice::<T>(&bytes)
+            .map(Some)
+            .map_err(|error| {
+                warp::reject::custom(InvalidBody(format!("Invalid request body: {}.", error)))
+            })
+    }
+
     // A function to add the web send to the filter
     fn with_clone<T>(
         item: T,
@@ -252,3 +992,80 @@ impl WebInterface {
         warp::any().map(move || item.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+    // Test an encrypted connection to the web interface
+    #[tokio::test]
+    async fn serve_tls() {
+        // Create a temporary directory with a self-signed certificate and key
+        let directory =
+            std::env::temp_dir().join(format!("apollo-tls-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert = directory.join("cert.pem");
+        let key = directory.join("key.pem");
+        std::fs::write(&cert, certified.cert.pem()).unwrap();
+        std::fs::write(&key, certified.signing_key.serialize_pem()).unwrap();
+        let mut listen = ListenSettings {
+            tls_addresses: vec!["127.0.0.1:0".to_string()],
+            is_encrypted_only: true,
+            tls_cert: Some(key.display().to_string()),
+            tls_key: Some(key.display().to_string()),
+            ..Default::default()
+        };
+
+        // Invalid addresses and a key in place of the certificate are refused
+        assert!(WebInterface::check_listen(&["localhost".to_string()], &listen).is_err());
+        assert!(WebInterface::check_listen(&[], &listen).is_err());
+
+        // Encrypted addresses alone are allowed
+        listen.tls_cert = Some(cert.display().to_string());
+        let (plaintext, _, tls_acceptor) = WebInterface::check_listen(&[], &listen).unwrap();
+        assert!(plaintext.is_empty());
+
+        // Serve a test route on a loopback port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let routes = warp::path("status").map(|| "ok");
+        tokio::spawn(WebInterface::accept_tls(
+            listener,
+            tls_acceptor.unwrap(),
+            TowerToHyperService::new(warp::service(routes)),
+        ));
+
+        // Connect, trusting only the self-signed certificate
+        let mut roots = RootCertStore::empty();
+        roots.add(certified.cert.der().clone()).unwrap();
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TcpStream::connect(address).await.unwrap();
+        let mut stream = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+
+        // Complete a request over the encrypted connection
+        stream
+            .write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("ok"));
+
+        // Remove the temporary directory
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright (c) 2026 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// The control panel for Apollo. The panel polls the status of every window
+// and channel, listens for playback events, and sends requests to the same
+// endpoints as any other client.
+
+"use strict";
+
+// Define panel constants
+const REFRESH_INTERVAL = 1000; // the time between status updates, in milliseconds
+const RECONNECT_DELAY = 2000; // the time before reconnecting to the events, in milliseconds
+const DEFAULT_WIDTH = 1920; // the assumed window width if it can't be determined
+const DEFAULT_HEIGHT = 1080; // the assumed window height if it can't be determined
+
+// Track the state of the panel
+let status = { windows: [], channels: [] }; // the most recent system status
+let selected = null; // the selected channel, if any
+let dragging = null; // the frame being moved or resized, if any
+let events = null; // the websocket for playback events
+
+// A helper function to create an element with text
+function element(tag, text, className) {
+  const item = document.createElement(tag);
+  if (text !== undefined) item.textContent = text;
+  if (className) item.className = className;
+  return item;
+}
+
+// A helper function to format a time in milliseconds
+function formatTime(milliseconds) {
+  if (milliseconds === null || milliseconds === undefined) return "--:--";
+  const seconds = Math.floor(milliseconds / 1000);
+  const minutes = Math.floor(seconds / 60);
+  return minutes + ":" + String(seconds % 60).padStart(2, "0");
+}
+
+// A helper function to show a message to the operator
+function showMessage(text) {
+  document.getElementById("message").textContent = text || "";
+}
+
+// A helper function to get the API key (saved in the browser)
+function apiKey() {
+  return localStorage.getItem("apolloKey") || "";
+}
+
+// Send a request to Apollo and return the reply (or null on failure)
+async function request(method, path, body) {
+  // Compose the request
+  const options = { method, headers: {} };
+  if (apiKey()) options.headers["X-Api-Key"] = apiKey();
+  if (body !== undefined) {
+    options.headers["Content-Type"] = "application/json";
+    options.body = JSON.stringify(body);
+  }
+
+  // Send the request and unpack the reply
+  try {
+    const response = await fetch(path, options);
+    const reply = await response.json();
+    const content = Object.values(reply)[0];
+    if (!response.ok) {
+      showMessage(content.message || "Request failed.");
+      return null;
+    }
+    return content;
+  } catch (error) {
+    showMessage("Unable to reach Apollo.");
+    return null;
+  }
+}
+
+// Refresh the status of every window and channel
+async function refresh() {
+  const reply = await request("GET", "/status");
+  if (reply && reply.systemStatus) {
+    status = reply.systemStatus;
+    render();
+  }
+}
+
+// Draw the channels and windows
+function render() {
+  renderChannels();
+  if (!dragging) renderWindows();
+}
+
+// Draw the table of channels and the list of channels to cue
+function renderChannels() {
+  // Leave the table alone while the operator is using a control
+  const table = document.querySelector("#channels tbody");
+  if (table.contains(document.activeElement)) return;
+
+  // Add a row for each channel
+  table.replaceChildren();
+  for (const channel of status.channels) {
+    const number = channel.mediaChannel.channel;
+    const frame = channel.mediaChannel.videoFrame;
+    const row = element("tr", undefined, number === selected ? "selected" : "");
+    row.addEventListener("click", () => select(number));
+    row.append(element("td", number));
+    row.append(element("td", frame ? frame.windowNumber : "-"));
+    const media = element("td", channel.currentUri || "-", "media");
+    media.title = channel.currentUri || "";
+    row.append(media);
+    row.append(element("td", channel.state || "-"));
+
+    // Add the position slider (seeking on release)
+    const position = element("td");
+    const slider = element("input");
+    slider.type = "range";
+    slider.min = 0;
+    slider.max = channel.duration || 0;
+    slider.value = channel.position || 0;
+    slider.disabled = !channel.duration;
+    slider.addEventListener("change", async () => {
+      await request("POST", "/seek", { channel: number, position: Number(slider.value) });
+      slider.blur();
+      refresh();
+    });
+    position.append(slider, " " + formatTime(channel.position) + " / " + formatTime(channel.duration));
+    row.append(position);
+
+    // Add the play/pause button
+    const controls = element("td");
+    const toggle = element("button", channel.state === "playing" ? "Pause" : "Play");
+    toggle.disabled = !channel.currentUri;
+    toggle.addEventListener("click", async () => {
+      const state = channel.state === "playing" ? "paused" : "playing";
+      await request("POST", "/changeState", { channel: number, state });
+      toggle.blur();
+      refresh();
+    });
+    controls.append(toggle);
+    row.append(controls);
+    table.append(row);
+  }
+
+  // Update the list of channels to cue (keeping the current choice)
+  const choice = document.getElementById("cue-channel");
+  if (document.activeElement !== choice) {
+    const current = choice.value;
+    choice.replaceChildren();
+    for (const channel of status.channels) {
+      choice.append(element("option", channel.mediaChannel.channel));
+    }
+    if (current) choice.value = current;
+  }
+}
+
+// Draw each window with the frames of its channels
+function renderWindows() {
+  const container = document.getElementById("windows");
+  container.replaceChildren();
+  for (const definition of status.windows) {
+    // Find the channels in this window
+    const channels = status.channels
+      .map((channel) => channel.mediaChannel)
+      .filter((channel) => channel.videoFrame && channel.videoFrame.windowNumber === definition.windowNumber);
+
+    // Find the size of the window (the minimum dimensions or the extent of the frames)
+    let [width, height] = definition.dimensions || [0, 0];
+    for (const channel of channels) {
+      const frame = channel.videoFrame;
+      width = Math.max(width, frame.left + frame.width);
+      height = Math.max(height, frame.top + frame.height);
+    }
+    width = width || DEFAULT_WIDTH;
+    height = height || DEFAULT_HEIGHT;
+
+    // Create the scaled window
+    const view = element("div", undefined, "window");
+    container.append(view);
+    const scale = view.clientWidth / width;
+    view.style.height = height * scale + "px";
+    view.append(element("span", "Window " + definition.windowNumber, "window-label"));
+
+    // Add each frame
+    for (const channel of channels) {
+      const frame = channel.videoFrame;
+      const box = element("div", "Channel " + channel.channel, "frame");
+      if (channel.channel === selected) box.classList.add("selected");
+      box.style.left = frame.left * scale + "px";
+      box.style.top = frame.top * scale + "px";
+      box.style.width = frame.width * scale + "px";
+      box.style.height = frame.height * scale + "px";
+      const handle = element("div", undefined, "handle");
+      box.append(handle);
+      box.addEventListener("mousedown", (event) => startDrag(event, channel.channel, frame, scale, box, event.target === handle));
+      view.append(box);
+    }
+  }
+}
+
+// Select a channel (for alignment with the arrow keys)
+function select(channel) {
+  selected = channel;
+  render();
+}
+
+// Start moving or resizing a frame
+function startDrag(event, channel, frame, scale, box, resize) {
+  event.preventDefault();
+  selected = channel;
+  box.classList.add("selected");
+  dragging = { channel, frame: { ...frame }, scale, box, resize, x: event.clientX, y: event.clientY };
+}
+
+// Update the frame as the mouse moves
+document.addEventListener("mousemove", (event) => {
+  if (!dragging) return;
+  const deltaX = (event.clientX - dragging.x) / dragging.scale;
+  const deltaY = (event.clientY - dragging.y) / dragging.scale;
+  const frame = dragging.frame;
+  if (dragging.resize) {
+    dragging.box.style.width = Math.max(1, frame.width + deltaX) * dragging.scale + "px";
+    dragging.box.style.height = Math.max(1, frame.height + deltaY) * dragging.scale + "px";
+  } else {
+    dragging.box.style.left = (frame.left + deltaX) * dragging.scale + "px";
+    dragging.box.style.top = (frame.top + deltaY) * dragging.scale + "px";
+  }
+});
+
+// Send the new frame when the mouse is released
+document.addEventListener("mouseup", async () => {
+  if (!dragging) return;
+  const { channel, scale, box } = dragging;
+  dragging = null;
+  const videoFrame = {
+    top: Math.round(parseFloat(box.style.top) / scale),
+    left: Math.round(parseFloat(box.style.left) / scale),
+    height: Math.round(parseFloat(box.style.height) / scale),
+    width: Math.round(parseFloat(box.style.width) / scale),
+  };
+  await request("POST", "/resizeChannel", { channel, videoFrame });
+  refresh();
+});
+
+// Nudge the selected frame with the arrow keys
+document.addEventListener("keydown", async (event) => {
+  const directions = { ArrowUp: "up", ArrowDown: "down", ArrowLeft: "left", ArrowRight: "right" };
+  const direction = directions[event.key];
+  if (!direction || selected === null) return;
+  if (["INPUT", "SELECT"].includes(document.activeElement.tagName)) return;
+  event.preventDefault();
+  await request("POST", "/alignChannel", { channel: selected, direction });
+  refresh();
+});
+
+// Cue media from the form
+document.getElementById("cue-form").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const channel = Number(document.getElementById("cue-channel").value);
+  const uri = document.getElementById("cue-uri").value;
+  if (await request("POST", "/cueMedia", { uri, channel })) showMessage("");
+  refresh();
+});
+
+// Listen for playback events (reconnecting whenever the connection drops)
+function connect() {
+  // Compose the address (browsers can't add headers to a websocket)
+  const protocol = location.protocol === "https:" ? "wss:" : "ws:";
+  let address = protocol + "//" + location.host + "/events";
+  if (apiKey()) address += "?key=" + encodeURIComponent(apiKey());
+
+  // Connect and refresh the status on each event
+  const connection = document.getElementById("connection");
+  events = new WebSocket(address);
+  events.onopen = () => {
+    connection.textContent = "Online";
+    connection.className = "online";
+  };
+  events.onmessage = (message) => {
+    const event = JSON.parse(message.data);
+    const [kind, details] = Object.entries(event)[0];
+    if (kind === "error" || kind === "warning") {
+      showMessage("Channel " + details.channel + ": " + details.message);
+    }
+    refresh();
+  };
+  events.onclose = () => {
+    connection.textContent = "Offline";
+    connection.className = "offline";
+    setTimeout(connect, RECONNECT_DELAY);
+  };
+}
+
+// Save the API key and reconnect when it changes
+const keyInput = document.getElementById("api-key");
+keyInput.value = apiKey();
+keyInput.addEventListener("change", () => {
+  localStorage.setItem("apolloKey", keyInput.value);
+  showMessage("");
+  if (events) events.close();
+  refresh();
+});
+
+// Start the panel
+connect();
+refresh();
+setInterval(refresh, REFRESH_INTERVAL);